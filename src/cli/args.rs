@@ -0,0 +1,173 @@
+use super::debug::parse_breakpoint;
+
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Command {
+    Lex,
+    Parse,
+    Check,
+    Fmt,
+    Run,
+    Debug,
+    Repl,
+    Help,
+}
+
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Color {
+    Auto,
+    Always,
+    Never,
+}
+
+
+#[derive(Debug, PartialEq)]
+pub struct Args {
+    pub command: Command,
+    pub format: Format,
+    pub color: Color,
+    pub check: bool,
+    pub indent: Option<usize>,
+    pub frames: u64,
+    pub state: Option<String>,
+    pub trace: bool,
+    pub screen: bool,
+    pub seed: Option<u64>,
+    pub storage: Option<String>,
+    pub keys: Option<String>,
+    pub step_limit: Option<u64>,
+    pub budget: bool,
+    pub lenient: bool,
+    pub vm: bool,
+    pub breakpoints: Vec<(Option<String>, usize)>,
+    pub inputs: Vec<String>,
+}
+
+
+pub fn parse_command(name: &str) -> Result<Command, String> {
+    match name {
+        "lex" => Ok(Command::Lex),
+        "parse" => Ok(Command::Parse),
+        "check" => Ok(Command::Check),
+        "fmt" => Ok(Command::Fmt),
+        "run" => Ok(Command::Run),
+        "debug" => Ok(Command::Debug),
+        "repl" => Ok(Command::Repl),
+        "help" | "-h" | "--help" => Ok(Command::Help),
+        _ => Err(format!("unknown command `{}`", name)),
+    }
+}
+
+
+pub fn parse_format(name: &str) -> Result<Format, String> {
+    match name {
+        "text" => Ok(Format::Text),
+        "json" => Ok(Format::Json),
+        _ => Err(format!("unknown format `{}`, expected `text` or `json`", name)),
+    }
+}
+
+
+pub fn parse_color(name: &str) -> Result<Color, String> {
+    match name {
+        "auto" => Ok(Color::Auto),
+        "always" => Ok(Color::Always),
+        "never" => Ok(Color::Never),
+        _ => Err(format!("unknown color mode `{}`, expected `auto`, `always` or `never`", name)),
+    }
+}
+
+
+pub fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut iterator = args.iter();
+
+    let command = match iterator.next() {
+        Some(name) => parse_command(name)?,
+        None => Command::Help,
+    };
+
+    let mut parsed = Args {
+        command,
+        format: Format::Text,
+        color: Color::Auto,
+        check: false,
+        indent: None,
+        frames: 1,
+        state: None,
+        trace: false,
+        screen: false,
+        seed: None,
+        storage: None,
+        keys: None,
+        step_limit: None,
+        budget: false,
+        lenient: false,
+        vm: false,
+        breakpoints: vec![],
+        inputs: vec![],
+    };
+
+    while let Some(arg) = iterator.next() {
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+
+        let mut value = |name: &str| match inline_value.clone().or_else(|| iterator.next().cloned()) {
+            Some(value) => Ok(value),
+            None => Err(format!("`{}` expects a value", name)),
+        };
+
+        match name {
+            "--format" => parsed.format = parse_format(&value(name)?)?,
+            "--json" => parsed.format = Format::Json,
+            "--color" => parsed.color = parse_color(&value(name)?)?,
+            "--check" => parsed.check = true,
+            "--indent" => {
+                let indent = value(name)?;
+
+                parsed.indent = Some(
+                    indent.parse().map_err(|_| format!("invalid indent width `{}`", indent))?
+                );
+            },
+            "--frames" => {
+                let frames = value(name)?;
+
+                parsed.frames = frames.parse().map_err(|_| format!("invalid frame count `{}`", frames))?;
+            },
+            "--state" => parsed.state = Some(value(name)?),
+            "--trace" => parsed.trace = true,
+            "--storage" => parsed.storage = Some(value(name)?),
+            "--keys" => parsed.keys = Some(value(name)?),
+            "--screen" => parsed.screen = true,
+            "--seed" => {
+                let seed = value(name)?;
+
+                parsed.seed = Some(seed.parse().map_err(|_| format!("invalid seed `{}`", seed))?);
+            },
+            "--step-limit" => {
+                let limit = value(name)?;
+
+                parsed.step_limit = Some(limit.parse().map_err(|_| format!("invalid step limit `{}`", limit))?);
+            },
+            "--budget" => parsed.budget = true,
+            "--lenient" => parsed.lenient = true,
+            "--vm" => parsed.vm = true,
+            "--break" => parsed.breakpoints.push(parse_breakpoint(&value(name)?)?),
+            "-h" | "--help" => parsed.command = Command::Help,
+            "-" => parsed.inputs.push(arg.clone()),
+            _ if name.starts_with('-') => return Err(format!("unknown option `{}`", name)),
+            _ => parsed.inputs.push(arg.clone()),
+        }
+    }
+
+    Ok(parsed)
+}
@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+use stonescript::interpreter::debug::{DebugCommand, Debugger, Pause, PauseReason};
+use stonescript::interpreter::loader::{Loader, MemoryLoader};
+use stonescript::{Interpreter, RuntimeValue};
+
+
+const HELP: &str = "\
+Commands:
+  c, continue     Run until the next breakpoint
+  s, step         Step into the next statement
+  n, next         Step over calls
+  o, out          Run until the current function returns
+  f, frame        Run until the next frame starts
+  b [file:]<line> Add a breakpoint, in an imported script with `file:`
+  d [file:]<line> Remove a breakpoint
+  p <expr>        Print the value of an expression
+  w <expr>        Watch an expression, `unwatch <expr>` removes it
+  locals          Print the current function's variables
+  globals         Print global variables
+  this            Print the fields of the current object
+  bt              Print the call stack
+  q, quit         Stop the script
+";
+
+
+#[derive(Debug, PartialEq)]
+pub enum Action {
+    Resume(DebugCommand),
+    Break(Option<String>, usize),
+    Delete(Option<String>, usize),
+    Print(String),
+    Watch(String),
+    Unwatch(String),
+    Locals,
+    Globals,
+    This,
+    Backtrace,
+    Help,
+}
+
+
+pub fn parse_action(line: &str) -> Result<Action, String> {
+    let line = line.trim();
+    let (name, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let rest = rest.trim();
+
+
+    let expression = || match rest {
+        "" => Err(format!("`{}` expects an expression", name)),
+        _ => Ok(rest.to_string()),
+    };
+
+    match name {
+        "c" | "continue" => Ok(Action::Resume(DebugCommand::Continue)),
+        "s" | "step" | "" => Ok(Action::Resume(DebugCommand::StepIn)),
+        "n" | "next" => Ok(Action::Resume(DebugCommand::StepOver)),
+        "o" | "out" => Ok(Action::Resume(DebugCommand::StepOut)),
+        "f" | "frame" => Ok(Action::Resume(DebugCommand::NextFrame)),
+        "q" | "quit" => Ok(Action::Resume(DebugCommand::Stop)),
+        "b" | "break" => parse_breakpoint(rest).map(|(script, line)| Action::Break(script, line)),
+        "d" | "delete" => parse_breakpoint(rest).map(|(script, line)| Action::Delete(script, line)),
+        "p" | "print" => Ok(Action::Print(expression()?)),
+        "w" | "watch" => Ok(Action::Watch(expression()?)),
+        "unwatch" => Ok(Action::Unwatch(expression()?)),
+        "locals" => Ok(Action::Locals),
+        "globals" => Ok(Action::Globals),
+        "this" => Ok(Action::This),
+        "bt" | "backtrace" => Ok(Action::Backtrace),
+        "h" | "help" => Ok(Action::Help),
+        _ => Err(format!("unknown command `{}`, try `help`", name)),
+    }
+}
+
+
+pub fn parse_breakpoint(text: &str) -> Result<(Option<String>, usize), String> {
+    let (script, line) = match text.rsplit_once(':') {
+        Some((script, line)) => (Some(script.to_string()), line),
+        None => (None, text),
+    };
+
+    match line.parse() {
+        Ok(line) => Ok((script, line)),
+        Err(_) => Err(format!("invalid line number `{}`", line)),
+    }
+}
+
+
+pub struct Console {
+    source: Vec<String>,
+    scripts: HashMap<String, Vec<String>>,
+    loader: Box<dyn Loader>,
+    input: Box<dyn BufRead>,
+    output: Box<dyn Write>,
+}
+
+
+impl Console {
+    pub fn new(source: &str, input: Box<dyn BufRead>, output: Box<dyn Write>) -> Self {
+        Console {
+            source: source.lines().map(str::to_string).collect(),
+            scripts: HashMap::new(),
+            loader: Box::new(MemoryLoader::new()),
+            input,
+            output,
+        }
+    }
+
+    pub fn with_loader(mut self, loader: Box<dyn Loader>) -> Self {
+        self.loader = loader;
+        self
+    }
+
+    fn line(&mut self, script: Option<&str>, line: usize) -> String {
+        let source = match script {
+            None => &self.source,
+            Some(script) => {
+                let loader = &self.loader;
+
+                self.scripts.entry(script.to_string()).or_insert_with(|| {
+                    loader.load(script).map(|source| source.lines().map(str::to_string).collect()).unwrap_or_default()
+                })
+            },
+        };
+
+        line.checked_sub(1).and_then(|index| source.get(index)).cloned().unwrap_or_default()
+    }
+
+    fn show(&mut self, interpreter: &mut Interpreter, pause: &Pause) -> std::io::Result<()> {
+        for line in interpreter.take_output() {
+            writeln!(self.output, "{}", line)?;
+        }
+
+        match pause.reason {
+            PauseReason::Frame => writeln!(self.output, "paused before frame {}", pause.frame)?,
+            reason => {
+                let line = pause.span.line;
+                let text = self.line(pause.script.as_deref(), line);
+                let reason = if reason == PauseReason::Breakpoint { "breakpoint" } else { "step" };
+
+                match &pause.script {
+                    Some(script) => writeln!(self.output, "{} at line {} of `{}` in frame {}", reason, line, script, pause.frame)?,
+                    None => writeln!(self.output, "{} at line {} in frame {}", reason, line, pause.frame)?,
+                }
+
+                writeln!(self.output, "{:>4} | {}", line, text)?;
+            },
+        }
+
+        for (source, value) in interpreter.watches() {
+            writeln!(self.output, "  {} = {}", source, describe(value))?;
+        }
+
+        Ok(())
+    }
+
+    fn prompt(&mut self, interpreter: &mut Interpreter) -> std::io::Result<DebugCommand> {
+        loop {
+            write!(self.output, "(debug) ")?;
+            self.output.flush()?;
+
+            let mut line = String::new();
+
+            if self.input.read_line(&mut line)? == 0 {
+                writeln!(self.output)?;
+                return Ok(DebugCommand::Stop);
+            }
+
+            let action = match parse_action(&line) {
+                Ok(action) => action,
+                Err(message) => {
+                    writeln!(self.output, "{}", message)?;
+                    continue;
+                },
+            };
+
+            match action {
+                Action::Resume(command) => return Ok(command),
+                Action::Break(script, line) => interpreter.add_breakpoint(script.as_deref(), line),
+                Action::Delete(script, line) => {
+                    if !interpreter.remove_breakpoint(script.as_deref(), line) {
+                        writeln!(self.output, "no breakpoint on line {}", line)?;
+                    }
+                },
+                Action::Print(source) => {
+                    writeln!(self.output, "{}", describe(interpreter.inspect(&source)))?;
+                },
+                Action::Watch(source) => {
+                    if let Err(diagnostic) = interpreter.add_watch(&source) {
+                        writeln!(self.output, "{}", diagnostic.message)?;
+                    }
+                },
+                Action::Unwatch(source) => {
+                    if !interpreter.remove_watch(&source) {
+                        writeln!(self.output, "not watching `{}`", source)?;
+                    }
+                },
+                Action::Locals => self.values(interpreter.locals())?,
+                Action::Globals => self.values(interpreter.globals())?,
+                Action::This => self.values(interpreter.object_fields())?,
+                Action::Backtrace => {
+                    for frame in interpreter.call_stack().iter().rev() {
+                        writeln!(self.output, "  {}", frame)?;
+                    }
+                },
+                Action::Help => write!(self.output, "{}", HELP)?,
+            }
+        }
+    }
+
+    fn values(&mut self, values: Vec<(String, RuntimeValue)>) -> std::io::Result<()> {
+        for (name, value) in values {
+            writeln!(self.output, "  {} = {}", name, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+
+impl Debugger for Console {
+    fn paused(&mut self, interpreter: &mut Interpreter, pause: &Pause) -> DebugCommand {
+        self.show(interpreter, pause)
+            .and_then(|_| self.prompt(interpreter))
+            .unwrap_or(DebugCommand::Stop)
+    }
+}
+
+
+fn describe(value: Result<RuntimeValue, stonescript::Diagnostic>) -> String {
+    match value {
+        Ok(RuntimeValue::String(value)) => format!("\"{}\"", value),
+        Ok(value) => value.to_string(),
+        Err(diagnostic) => format!("error: {}", diagnostic.message),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::io::Cursor;
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buffer: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buffer)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_parse_action() {
+        assert_eq!(parse_action("c"), Ok(Action::Resume(DebugCommand::Continue)));
+        assert_eq!(parse_action(""), Ok(Action::Resume(DebugCommand::StepIn)));
+        assert_eq!(parse_action("b 12"), Ok(Action::Break(None, 12)));
+        assert_eq!(parse_action("d Components/Counter:3"), Ok(Action::Delete(Some("Components/Counter".to_string()), 3)));
+        assert_eq!(parse_action("p foe.hp + 1"), Ok(Action::Print("foe.hp + 1".to_string())));
+        assert!(parse_action("b twelve").is_err());
+        assert!(parse_action("w").is_err());
+        assert!(parse_action("jump").is_err());
+    }
+
+    #[test]
+    fn test_console_session() {
+        let source = "func Heal(amount)\n  var healed = hp + amount\n  return healed\nvar total = Heal(3)\n>@total@";
+        let commands = "w total\ns\nlocals\nbt\np amount * 2\nc\n";
+        let output = Shared::default();
+
+        let mut interpreter = Interpreter::new();
+
+        interpreter.state.hp = 4;
+        interpreter.add_breakpoint(None, 4);
+        interpreter.set_debugger(Box::new(Console::new(source, Box::new(Cursor::new(commands)), Box::new(output.clone()))));
+        interpreter.run(&stonescript::parse(source).unwrap()).unwrap();
+
+        assert_eq!(String::from_utf8(output.0.borrow().clone()).unwrap(), "\
+breakpoint at line 4 in frame 0
+   4 | var total = Heal(3)
+(debug) (debug) step at line 2 in frame 0
+   2 |   var healed = hp + amount
+  total = \"total\"
+(debug)   amount = 3
+(debug)   in `Heal` called at 4:13
+(debug) 6
+(debug) ");
+        assert_eq!(interpreter.take_output(), vec!["7"]);
+    }
+
+    #[test]
+    fn test_console_shows_imported_scripts() {
+        let source = "var c = new Counter\nc.Bump(5)";
+        let counter = "var count = 1\nfunc Bump(by)\n  count += by\n";
+        let output = Shared::default();
+
+        let mut interpreter = Interpreter::new();
+        let console = Console::new(source, Box::new(Cursor::new("c\n")), Box::new(output.clone()))
+            .with_loader(Box::new(MemoryLoader::new().with_file("Counter", counter)));
+
+        interpreter.loader = Box::new(MemoryLoader::new().with_file("Counter", counter));
+        interpreter.add_breakpoint(Some("Counter"), 3);
+        interpreter.set_debugger(Box::new(console));
+        interpreter.run(&stonescript::parse(source).unwrap()).unwrap();
+
+        assert_eq!(String::from_utf8(output.0.borrow().clone()).unwrap(), "\
+breakpoint at line 3 of `Counter` in frame 0
+   3 |   count += by
+(debug) ");
+    }
+}
@@ -0,0 +1,70 @@
+use std::fs;
+use std::io::{self, Read};
+
+
+pub const STDIN: &str = "-";
+
+
+#[derive(Debug, PartialEq)]
+pub struct Input {
+    pub name: String,
+    pub path: Option<String>,
+    pub content: String,
+}
+
+
+fn is_pattern(input: &str) -> bool {
+    input.contains(['*', '?', '['])
+}
+
+
+pub fn expand_inputs(inputs: &[String]) -> Result<Vec<String>, String> {
+    if inputs.is_empty() {
+        return Ok(vec![STDIN.to_string()]);
+    }
+
+    let mut paths = vec![];
+
+    for input in inputs {
+        if !is_pattern(input) {
+            paths.push(input.clone());
+            continue;
+        }
+
+        let entries = glob::glob(input)
+            .map_err(|error| format!("invalid pattern `{}`: {}", input, error))?;
+
+        let mut matched = entries
+            .filter_map(Result::ok)
+            .filter(|path| path.is_file())
+            .map(|path| path.to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+
+        if matched.is_empty() {
+            return Err(format!("no files match `{}`", input));
+        }
+
+        matched.sort();
+        paths.append(&mut matched);
+    }
+
+    Ok(paths)
+}
+
+
+pub fn read_input(path: &str) -> Result<Input, String> {
+    if path == STDIN {
+        let mut content = String::new();
+
+        io::stdin()
+            .read_to_string(&mut content)
+            .map_err(|error| format!("<stdin>: {}", error))?;
+
+        return Ok(Input { name: "<stdin>".to_string(), path: None, content });
+    }
+
+    let content = fs::read_to_string(path)
+        .map_err(|error| format!("{}: {}", path, error))?;
+
+    Ok(Input { name: path.to_string(), path: Some(path.to_string()), content })
+}
@@ -0,0 +1,501 @@
+pub mod args;
+pub mod debug;
+pub mod input;
+pub mod repl;
+
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use stonescript::interpreter::loader::FileLoader;
+use stonescript::interpreter::storage::Storage;
+use stonescript::interpreter::vm::Vm;
+use stonescript::{Diagnostic, Expression, FormatOptions, GameState, Interpreter, Renderer, Token};
+
+use args::{parse_args, Args, Color, Command, Format};
+use debug::Console;
+use input::{expand_inputs, read_input, Input};
+use repl::Repl;
+
+
+const USAGE: &str = "\
+Usage: stonescript <command> [options] [files...]
+
+Commands:
+  lex      Print the tokens of each input
+  parse    Print the syntax tree of each input
+  check    Report inputs that fail to parse
+  fmt      Format inputs in place
+  run      Run a script
+  debug    Run a script under the step debugger
+  repl     Start an interactive session
+  help     Print this message
+
+Options:
+  --format <text|json>  Output format for lex, parse and check (default: text)
+  --json                Same as --format json
+  --color <when>        Color diagnostics: auto, always or never (default: auto)
+  --check               With fmt, only report inputs that are not formatted
+  --indent <width>      With fmt, indentation width (default: 2)
+  --frames <n>          With run, number of frames to run (default: 1)
+  --state <file>        With run or repl, load the game state from a .json or .toml file
+  --trace               With run, print game commands as `frame: command`
+  --screen              With run, print the screen after the last frame
+  --seed <n>            With run or repl, seed `rng` and `rngf` (default: random)
+  --storage <file>      With run or repl, keep `storage` values in a JSON file
+  --keys <file>         With run or repl, replay a key timeline of `frame: +held -released tapped`
+  --step-limit <n>      With run or repl, steps a frame may take before it is aborted (default: 100000)
+  --budget              With run, print the steps each frame took as `frame: steps/limit`
+  --lenient             With run, report failing statements as warnings and carry on
+  --vm                  With run, compile the script to bytecode and run it on the VM
+  --break [file:]<line> With debug, pause before the statements on a line (default: first frame)
+
+Files may be paths or glob patterns. Without files, or with `-`, the input
+is read from stdin.
+";
+
+
+pub fn main(args: &[String]) -> ExitCode {
+    let args = match parse_args(args) {
+        Ok(args) => args,
+        Err(message) => return usage_error(&message),
+    };
+
+    if args.command == Command::Help {
+        print!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let paths = match expand_inputs(&args.inputs) {
+        Ok(paths) => paths,
+        Err(message) => return usage_error(&message),
+    };
+
+    let renderer = match args.color {
+        Color::Always => Renderer::colored(),
+        Color::Never => Renderer::plain(),
+        Color::Auto => Renderer {
+            color: io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none(),
+        },
+    };
+
+    if args.command == Command::Repl {
+        return match repl(&args, renderer) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(diagnostic) => {
+                eprint!("{}", renderer.render(&diagnostic, "<repl>", ""));
+                ExitCode::FAILURE
+            },
+        };
+    }
+
+    let mut failed = false;
+
+    for path in paths {
+        let input = match read_input(&path) {
+            Ok(input) => input,
+            Err(message) => {
+                eprintln!("stonescript: {}", message);
+                failed = true;
+                continue;
+            },
+        };
+
+        let result = match args.command {
+            Command::Lex => lex(&input, args.format),
+            Command::Parse => parse(&input, args.format),
+            Command::Check => check(&input, args.format),
+            Command::Fmt => fmt(&input, &args),
+            Command::Run | Command::Debug => run(&input, &args, &renderer),
+            _ => unreachable!(),
+        };
+
+        if let Err(diagnostic) = result {
+            eprint!("{}", renderer.render(&diagnostic, &input.name, &input.content));
+            failed = true;
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+
+fn usage_error(message: &str) -> ExitCode {
+    eprintln!("stonescript: {}", message);
+    eprintln!("Run `stonescript help` for usage.");
+    ExitCode::from(2)
+}
+
+
+fn lex(input: &Input, format: Format) -> Result<(), Diagnostic> {
+    let tokens: Vec<Token> = stonescript::tokenize(&input.content);
+
+    match format {
+        Format::Text => {
+            for token in &tokens {
+                println!("{}:{} {:?}", token.line, token.column, token.token_type);
+            }
+
+            Ok(())
+        },
+        Format::Json => print_json(&input.name, "tokens", &tokens),
+    }
+}
+
+
+fn parse(input: &Input, format: Format) -> Result<(), Diagnostic> {
+    let syntax_tree: Expression = stonescript::parse(&input.content)?;
+
+    match format {
+        Format::Text => {
+            println!("{:#?}", syntax_tree);
+            Ok(())
+        },
+        Format::Json => print_json(&input.name, "tree", &syntax_tree),
+    }
+}
+
+
+fn check(input: &Input, format: Format) -> Result<(), Diagnostic> {
+    let result = stonescript::parse(&input.content).map(|_| ());
+
+    if format == Format::Json {
+        let errors = match &result {
+            Ok(()) => vec![],
+            Err(diagnostic) => vec![diagnostic.clone()],
+        };
+
+        print_json(&input.name, "errors", &errors)?;
+    }
+
+    result
+}
+
+
+fn fmt(input: &Input, args: &Args) -> Result<(), Diagnostic> {
+    let mut options = FormatOptions::default();
+
+    if let Some(indent) = args.indent {
+        options.indent_width = indent;
+    }
+
+    let formatted = stonescript::format(&input.content, &options)?;
+
+    if args.check {
+        if formatted != input.content {
+            return Err(
+                Diagnostic::error("file is not formatted")
+                    .with_help(format!("run `stonescript fmt {}` to format it", input.name))
+            );
+        }
+
+        return Ok(());
+    }
+
+    match &input.path {
+        Some(path) => {
+            if formatted != input.content {
+                fs::write(path, formatted)
+                    .map_err(|error| Diagnostic::error(format!("could not write file: {}", error)))?;
+            }
+        },
+        None => print!("{}", formatted),
+    }
+
+    Ok(())
+}
+
+
+fn run(input: &Input, args: &Args, renderer: &Renderer) -> Result<(), Diagnostic> {
+    let program = stonescript::parse(&input.content)?;
+
+    let root = input.path.as_deref().and_then(|path| Path::new(path).parent());
+    let mut interpreter = interpreter(args, root.unwrap_or(Path::new(".")))?;
+
+    if args.command == Command::Debug {
+        let console = Console::new(&input.content, Box::new(io::stdin().lock()), Box::new(io::stdout()))
+            .with_loader(Box::new(FileLoader::new(root.unwrap_or(Path::new(".")))));
+
+        interpreter.set_debugger(Box::new(console));
+
+        for (script, line) in &args.breakpoints {
+            interpreter.add_breakpoint(script.as_deref(), *line);
+        }
+
+        if args.breakpoints.is_empty() {
+            interpreter.break_on_next_frame();
+        }
+    }
+
+    let mut vm = match args.vm && args.command == Command::Run {
+        true => Some(Vm::compile(&program)?),
+        false => None,
+    };
+
+    for frame in 0..args.frames {
+        let result = match &mut vm {
+            Some(vm) => vm.tick(&mut interpreter),
+            None => interpreter.tick(&program),
+        };
+
+        for line in interpreter.take_output() {
+            println!("{}", line);
+        }
+
+        for warning in interpreter.take_warnings() {
+            eprint!("{}", renderer.render(&warning.to_diagnostic(), &input.name, &input.content));
+        }
+
+        if args.trace {
+            for event in interpreter.take_trace() {
+                println!("{}", event);
+            }
+        }
+
+        if args.budget {
+            let (steps, limit) = (interpreter.steps(), interpreter.step_limit());
+
+            println!("{}: {}/{} ({:.1}%)", frame, steps, limit, steps as f64 * 100.0 / limit.max(1) as f64);
+        }
+
+        result?;
+    }
+
+    if args.screen {
+        match screen_color(args) {
+            true => print!("{}", interpreter.screen.to_ansi()),
+            false => print!("{}", interpreter.screen.to_plain()),
+        }
+    }
+
+    Ok(())
+}
+
+
+fn repl(args: &Args, renderer: Renderer) -> Result<(), Diagnostic> {
+    let mut repl = Repl::new(interpreter(args, Path::new("."))?, renderer, screen_color(args));
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    while !repl.is_done() {
+        print!("{}", repl.prompt());
+        io::stdout().flush().map_err(|error| Diagnostic::error(error.to_string()))?;
+
+        let Some(line) = lines.next() else {
+            println!();
+            break;
+        };
+
+        let line = line.map_err(|error| Diagnostic::error(format!("could not read input: {}", error)))?;
+
+        print!("{}", repl.feed(&line));
+    }
+
+    Ok(())
+}
+
+
+fn interpreter(args: &Args, root: &Path) -> Result<Interpreter, Diagnostic> {
+    let mut interpreter = Interpreter::new();
+
+    interpreter.loader = Box::new(FileLoader::new(root));
+
+    if let Some(path) = &args.state {
+        interpreter.state = GameState::load(path)?;
+    }
+
+    if let Some(path) = &args.keys {
+        let timeline = fs::read_to_string(path)
+            .map_err(|error| Diagnostic::error(format!("could not read `{}`: {}", path, error)))?;
+
+        interpreter.input.load_timeline(&timeline)?;
+    }
+
+    if let Some(path) = &args.storage {
+        interpreter.storage = open_storage(path)?;
+    }
+
+    if let Some(limit) = args.step_limit {
+        interpreter.set_step_limit(limit);
+    }
+
+    interpreter.set_lenient(args.lenient);
+
+    interpreter.random.seed(match args.seed {
+        Some(seed) => seed,
+        None => SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos() as u64),
+    });
+
+    Ok(interpreter)
+}
+
+
+fn screen_color(args: &Args) -> bool {
+    match args.color {
+        Color::Always => true,
+        Color::Never => false,
+        Color::Auto => io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none(),
+    }
+}
+
+
+#[cfg(feature = "serde")]
+fn open_storage(path: &str) -> Result<Box<dyn Storage>, Diagnostic> {
+    Ok(Box::new(stonescript::interpreter::storage::JsonStorage::open(path)?))
+}
+
+
+#[cfg(not(feature = "serde"))]
+fn open_storage(_path: &str) -> Result<Box<dyn Storage>, Diagnostic> {
+    Err(Diagnostic::error("storage files require stonescript to be built with the `serde` feature"))
+}
+
+
+#[cfg(feature = "serde")]
+fn print_json<T: serde::Serialize>(file: &str, key: &str, value: &T) -> Result<(), Diagnostic> {
+    let mut record = serde_json::Map::new();
+
+    record.insert("file".to_string(), serde_json::Value::from(file));
+    record.insert(
+        key.to_string(),
+        serde_json::to_value(value).map_err(|error| Diagnostic::error(error.to_string()))?,
+    );
+
+    println!("{}", serde_json::Value::Object(record));
+
+    Ok(())
+}
+
+
+#[cfg(not(feature = "serde"))]
+fn print_json<T>(_file: &str, _key: &str, _value: &T) -> Result<(), Diagnostic> {
+    Err(Diagnostic::error("JSON output requires stonescript to be built with the `serde` feature"))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::args::*;
+    use super::input::*;
+    use std::fs;
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(&strings(&["parse", "--format", "json", "a.txt", "-"])),
+            Ok(Args {
+                command: Command::Parse,
+                format: Format::Json,
+                color: Color::Auto,
+                check: false,
+                indent: None,
+                frames: 1,
+                state: None,
+                trace: false,
+                screen: false,
+                seed: None,
+                storage: None,
+                keys: None,
+                step_limit: None,
+                budget: false,
+                lenient: false,
+                vm: false,
+                breakpoints: vec![],
+                inputs: strings(&["a.txt", "-"]),
+            })
+        );
+
+        assert_eq!(
+            parse_args(&strings(&["fmt", "--check", "--indent=4", "--color", "never", "b.txt"])),
+            Ok(Args {
+                command: Command::Fmt,
+                format: Format::Text,
+                color: Color::Never,
+                check: true,
+                indent: Some(4),
+                frames: 1,
+                state: None,
+                trace: false,
+                screen: false,
+                seed: None,
+                storage: None,
+                keys: None,
+                step_limit: None,
+                budget: false,
+                lenient: false,
+                vm: false,
+                breakpoints: vec![],
+                inputs: strings(&["b.txt"]),
+            })
+        );
+
+        let args = parse_args(&strings(&["run", "--frames", "30", "--state=fight.toml", "--trace", "--screen", "--seed", "42", "--storage", "s.json", "--keys=menu.keys", "--step-limit", "500", "--budget", "--lenient", "--vm", "--break", "3", "--break=Enemy:9", "c.txt"])).unwrap();
+
+        assert_eq!(args.frames, 30);
+        assert!(args.trace);
+        assert!(args.screen);
+        assert_eq!(args.seed, Some(42));
+        assert_eq!(args.storage.as_deref(), Some("s.json"));
+        assert_eq!(args.keys.as_deref(), Some("menu.keys"));
+        assert_eq!(args.state.as_deref(), Some("fight.toml"));
+        assert_eq!(args.step_limit, Some(500));
+        assert!(args.budget);
+        assert!(args.lenient);
+        assert!(args.vm);
+        assert_eq!(args.breakpoints, vec![(None, 3), (Some("Enemy".to_string()), 9)]);
+    }
+
+    #[test]
+    fn test_parse_args_defaults_to_help() {
+        assert_eq!(parse_args(&[]).unwrap().command, Command::Help);
+        assert_eq!(parse_args(&strings(&["lex", "--help"])).unwrap().command, Command::Help);
+    }
+
+    #[test]
+    fn test_parse_args_errors() {
+        assert!(parse_args(&strings(&["compile"])).is_err());
+        assert!(parse_args(&strings(&["lex", "--format", "xml"])).is_err());
+        assert!(parse_args(&strings(&["lex", "--format"])).is_err());
+        assert!(parse_args(&strings(&["lex", "--verbose"])).is_err());
+        assert!(parse_args(&strings(&["lex", "--color", "sometimes"])).is_err());
+        assert!(parse_args(&strings(&["run", "--frames", "-1"])).is_err());
+        assert!(parse_args(&strings(&["run", "--seed", "x"])).is_err());
+        assert!(parse_args(&strings(&["run", "--step-limit", "lots"])).is_err());
+        assert!(parse_args(&strings(&["fmt", "--indent", "two"])).is_err());
+    }
+
+    #[test]
+    fn test_expand_inputs() {
+        assert_eq!(expand_inputs(&[]), Ok(strings(&[STDIN])));
+        assert_eq!(expand_inputs(&strings(&["a.txt"])), Ok(strings(&["a.txt"])));
+
+        let directory = std::env::temp_dir().join(format!("stonescript-inputs-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+
+        fs::create_dir_all(directory.join("nested.txt")).unwrap();
+
+        for name in ["b.txt", "a.txt", "c.md"] {
+            fs::write(directory.join(name), "").unwrap();
+        }
+
+        let root = directory.to_string_lossy();
+        let files = expand_inputs(&[format!("{}/*.txt", root)]);
+
+        assert_eq!(files, Ok(vec![format!("{}/a.txt", root), format!("{}/b.txt", root)]));
+        assert!(expand_inputs(&[format!("{}/*.missing", root)]).is_err());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,325 @@
+use stonescript::interpreter::state::{StateValue, FIELDS};
+use stonescript::{Expression, Interpreter, Renderer, RuntimeValue, UnaryOperator};
+
+
+const SOURCE: &str = "<repl>";
+
+const HELP: &str = "\
+Enter Stonescript to run it once. Lines opening a block (`?`, `:?`, `:`,
+`for`, `func`) start a multi-line entry that ends with an empty line.
+
+Commands:
+  :state                 Print the game state
+  :state <name>          Print one game state value
+  :state <name> <value>  Change a game state value
+  :tick [n]              Run everything entered so far for n frames (default: 1)
+  :screen                Print the virtual screen
+  :vars                  Print global variables
+  :history               Print previous entries, `!n` runs entry n again
+  :help                  Print this message
+  :quit                  Leave the session
+";
+
+
+pub struct Repl {
+    pub interpreter: Interpreter,
+    renderer: Renderer,
+    color: bool,
+    pending: Vec<String>,
+    script: Vec<String>,
+    history: Vec<String>,
+    done: bool,
+}
+
+
+impl Repl {
+    pub fn new(interpreter: Interpreter, renderer: Renderer, color: bool) -> Self {
+        Repl {
+            interpreter,
+            renderer,
+            color,
+            pending: vec![],
+            script: vec![],
+            history: vec![],
+            done: false,
+        }
+    }
+
+    pub fn prompt(&self) -> &'static str {
+        match self.pending.is_empty() {
+            true => "> ",
+            false => "... ",
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    pub fn feed(&mut self, line: &str) -> String {
+        if !self.pending.is_empty() {
+            if !line.trim().is_empty() {
+                self.pending.push(line.to_string());
+                return String::new();
+            }
+
+            let source = std::mem::take(&mut self.pending).join("\n");
+
+            return self.submit(&source);
+        }
+
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() {
+            return String::new();
+        }
+
+        if let Some(command) = trimmed.strip_prefix(':').filter(|command| command.starts_with(char::is_alphabetic)) {
+            return self.command(command);
+        }
+
+        if let Some(number) = trimmed.strip_prefix('!') {
+            return match number.parse::<usize>().ok().and_then(|number| self.history.get(number.wrapping_sub(1))) {
+                Some(source) => {
+                    let source = source.clone();
+                    format!("{}\n{}", source, self.submit(&source))
+                },
+                None => format!("no history entry `{}`\n", number),
+            };
+        }
+
+        if opens_block(trimmed) {
+            self.pending.push(line.to_string());
+            return String::new();
+        }
+
+        self.submit(line)
+    }
+
+    fn submit(&mut self, source: &str) -> String {
+        self.history.push(source.to_string());
+
+        let program = match stonescript::parse(source) {
+            Ok(program) => program,
+            Err(diagnostic) => return self.renderer.render(&diagnostic, SOURCE, source),
+        };
+
+        let result = match echoed(&program) {
+            Some(expression) => self.interpreter.eval(expression),
+            None => self.interpreter.run(&program).map(|_| RuntimeValue::Null),
+        };
+
+        let mut output = self.take_output();
+
+        match result {
+            Ok(RuntimeValue::Null) => {},
+            Ok(RuntimeValue::String(value)) => output.push_str(&format!("\"{}\"\n", value)),
+            Ok(value) => output.push_str(&format!("{}\n", value)),
+            Err(error) => {
+                output.push_str(&self.renderer.render(&error.to_diagnostic(), SOURCE, source));
+                return output;
+            },
+        }
+
+        self.script.push(source.to_string());
+
+        output
+    }
+
+    fn command(&mut self, command: &str) -> String {
+        let (name, rest) = command.split_once(char::is_whitespace).unwrap_or((command, ""));
+        let rest = rest.trim();
+
+        match name {
+            "state" => self.state(rest),
+            "tick" => match rest {
+                "" => self.tick(1),
+                count => match count.parse() {
+                    Ok(count) => self.tick(count),
+                    Err(_) => format!("invalid frame count `{}`\n", count),
+                },
+            },
+            "screen" => match self.color {
+                true => self.interpreter.screen.to_ansi(),
+                false => self.interpreter.screen.to_plain(),
+            },
+            "vars" => {
+                let mut globals = self.interpreter.environment.globals().iter().collect::<Vec<_>>();
+                globals.sort_by_key(|(name, _)| name.as_str());
+
+                globals
+                    .into_iter()
+                    .map(|(name, value)| format!("{} = {}\n", name, value))
+                    .collect()
+            },
+            "history" => self.history
+                .iter()
+                .enumerate()
+                .map(|(index, source)| format!("{:>3}  {}\n", index + 1, source.replace('\n', "\n     ")))
+                .collect(),
+            "help" => HELP.to_string(),
+            "quit" | "q" | "exit" => {
+                self.done = true;
+                String::new()
+            },
+            _ => format!("unknown command `:{}`, try `:help`\n", name),
+        }
+    }
+
+    fn state(&mut self, arguments: &str) -> String {
+        let state = &mut self.interpreter.state;
+
+        match arguments.split_once(char::is_whitespace) {
+            Some((name, value)) => match state.assign(name, StateValue::parse(value)) {
+                Ok(()) => String::new(),
+                Err(diagnostic) => self.renderer.render(&diagnostic, SOURCE, ""),
+            },
+            None if !arguments.is_empty() => match state.get(arguments) {
+                Some(value) => format!("{}\n", value),
+                None => format!("`{}` is not set\n", arguments),
+            },
+            None => FIELDS
+                .iter()
+                .map(|name| (name.to_string(), state.get(name).unwrap_or_default()))
+                .chain(state.variables.keys().map(|name| (name.clone(), state.get(name).unwrap_or_default())))
+                .map(|(name, value)| format!("{} = {}\n", name, value))
+                .collect(),
+        }
+    }
+
+    fn tick(&mut self, count: u64) -> String {
+        let source = self.script.join("\n");
+
+        let program = match stonescript::parse(&source) {
+            Ok(program) => program,
+            Err(diagnostic) => return self.renderer.render(&diagnostic, SOURCE, &source),
+        };
+
+        let mut output = String::new();
+
+        for _ in 0..count {
+            let frame = self.interpreter.frame();
+            let result = self.interpreter.tick(&program);
+
+            output.push_str(&format!("-- frame {}\n", frame));
+            output.push_str(&self.take_output());
+
+            if let Err(error) = result {
+                output.push_str(&self.renderer.render(&error.to_diagnostic(), SOURCE, &source));
+                break;
+            }
+        }
+
+        output
+    }
+
+    fn take_output(&mut self) -> String {
+        self.interpreter
+            .take_output()
+            .into_iter()
+            .map(|line| format!("{}\n", line))
+            .collect()
+    }
+}
+
+
+fn opens_block(line: &str) -> bool {
+    line.starts_with('?')
+        || line.starts_with(':')
+        || line.starts_with("for ")
+        || line.starts_with("func ")
+}
+
+
+fn echoed(program: &Expression) -> Option<&Expression> {
+    let Expression::Block { statements } = program else {
+        return None;
+    };
+
+    match statements.as_slice() {
+        [expression @ (
+            Expression::Value(_)
+            | Expression::Identifier(_)
+            | Expression::Binary { .. }
+            | Expression::Call { .. }
+            | Expression::Member { .. }
+            | Expression::Index { .. }
+            | Expression::Array { .. }
+            | Expression::New { .. }
+            | Expression::This
+        )] => Some(expression),
+        [expression @ Expression::Unary { operator, .. }] if !matches!(operator, UnaryOperator::Increment | UnaryOperator::Decrement) => {
+            Some(expression)
+        },
+        _ => None,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repl() -> Repl {
+        Repl::new(Interpreter::new(), Renderer::plain(), false)
+    }
+
+    fn feed(repl: &mut Repl, lines: &[&str]) -> String {
+        lines.iter().map(|line| repl.feed(line)).collect()
+    }
+
+    #[test]
+    fn test_persistent_variables() {
+        let mut repl = repl();
+
+        assert_eq!(feed(&mut repl, &["var x = 2", "x = x * 21", "x", "\"a\" + x", ">@x@"]), "42\n\"a42\"\n42\n");
+        assert_eq!(repl.feed(":vars"), "x = 42\n");
+        assert!(repl.feed(":history").starts_with("  1  var x = 2\n  2  x = x * 21\n"));
+    }
+
+    #[test]
+    fn test_multiline_entry() {
+        let mut repl = repl();
+
+        assert_eq!(repl.feed("func Twice(n)"), "");
+        assert_eq!(repl.prompt(), "... ");
+        assert_eq!(repl.feed("  return n * 2"), "");
+        assert_eq!(repl.feed(""), "");
+        assert_eq!(repl.prompt(), "> ");
+        assert_eq!(repl.feed("Twice(4)"), "8\n");
+
+        assert_eq!(feed(&mut repl, &["?Twice(1) > 5", "  >big", ":", "  >small", ""]), "small\n");
+    }
+
+    #[test]
+    fn test_state_and_ticks() {
+        let mut repl = repl();
+
+        assert_eq!(repl.feed(":state hp 7"), "");
+        assert_eq!(repl.feed(":state hp"), "7\n");
+        assert!(repl.feed(":state hp lots").starts_with("error: `hp` must be an int"));
+        assert!(repl.feed(":state").starts_with("hp = 7\nmaxhp = 0\n"));
+
+        assert_eq!(feed(&mut repl, &["var n = 0", "n++", ">n=@n@"]), "n=1\n");
+        assert_eq!(repl.feed(":tick 2"), "-- frame 0\nn=2\n-- frame 1\nn=3\n");
+        assert_eq!(repl.feed(":state time"), "2\n");
+    }
+
+    #[test]
+    fn test_errors_and_commands() {
+        let mut repl = repl();
+
+        assert!(repl.feed("var x = 1 / 0").starts_with("error: division by zero"));
+        assert!(repl.feed("var = 1").starts_with("error:"));
+        assert_eq!(repl.feed(":tick"), "-- frame 0\n");
+        assert_eq!(repl.feed("!1").lines().next(), Some("var x = 1 / 0"));
+        assert_eq!(repl.feed("!9"), "no history entry `9`\n");
+        assert_eq!(repl.feed(":nope"), "unknown command `:nope`, try `:help`\n");
+        assert_eq!(repl.feed("draw.Bg(0, 0, \"#f00\")"), "");
+        assert_eq!(repl.feed(":screen").lines().count(), repl.interpreter.screen.to_plain().lines().count());
+
+        repl.feed(":quit");
+
+        assert!(repl.is_done());
+    }
+}
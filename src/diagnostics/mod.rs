@@ -0,0 +1,227 @@
+pub mod render;
+
+use std::fmt;
+
+pub use render::Renderer;
+
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    pub length: usize,
+}
+
+impl Span {
+    pub fn new(line: usize, column: usize, length: usize) -> Span {
+        Span { line, column, length }
+    }
+
+    pub fn to(&self, end: Span) -> Span {
+        if end.line != self.line || end.column < self.column {
+            return *self;
+        }
+
+        Span::new(self.line, self.column, end.column + end.length - self.column)
+    }
+}
+
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => f.write_str("error"),
+            Severity::Warning => f.write_str("warning"),
+            Severity::Note => f.write_str("note"),
+        }
+    }
+}
+
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+    pub primary: bool,
+}
+
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub help: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: impl Into<String>) -> Diagnostic {
+        Diagnostic {
+            severity,
+            message: message.into(),
+            labels: vec![],
+            notes: vec![],
+            help: vec![],
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Diagnostic {
+        Diagnostic::new(Severity::Error, message)
+    }
+
+    pub fn warning(message: impl Into<String>) -> Diagnostic {
+        Diagnostic::new(Severity::Warning, message)
+    }
+
+    pub fn with_span(self, span: Span) -> Diagnostic {
+        self.with_primary_label(span, "")
+    }
+
+    pub fn with_primary_label(mut self, span: Span, message: impl Into<String>) -> Diagnostic {
+        self.labels.push(Label { span, message: message.into(), primary: true });
+        self
+    }
+
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Diagnostic {
+        self.labels.push(Label { span, message: message.into(), primary: false });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Diagnostic {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Diagnostic {
+        self.help.push(help.into());
+        self
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        self.labels
+            .iter()
+            .find(|label| label.primary)
+            .or(self.labels.first())
+            .map(|label| label.span)
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.span() {
+            Some(span) => write!(f, "{}:{}: {}: {}", span.line, span.column, self.severity, self.message),
+            None => write!(f, "{}: {}", self.severity, self.message),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_span_to() {
+        assert_eq!(Span::new(2, 3, 1).to(Span::new(2, 7, 2)), Span::new(2, 3, 6));
+        assert_eq!(Span::new(2, 3, 1).to(Span::new(3, 1, 2)), Span::new(2, 3, 1));
+    }
+
+    #[test]
+    fn test_display() {
+        let diagnostic = Diagnostic::error("expected `)`").with_span(Span::new(3, 5, 1));
+
+        assert_eq!(diagnostic.to_string(), "3:5: error: expected `)`");
+        assert_eq!(Diagnostic::warning("unused").to_string(), "warning: unused");
+    }
+
+    #[test]
+    fn test_render_plain() {
+        let source = "var a = 1\nfunc Test(\n  a\n";
+
+        let diagnostic = Diagnostic::error("expected parameter name or `)`, found end of line")
+            .with_primary_label(Span::new(2, 11, 1), "expected parameter name or `)`")
+            .with_label(Span::new(2, 10, 1), "parameter list starts here")
+            .with_note("parameters are separated by commas")
+            .with_help("close the parameter list with `)`");
+
+        assert_eq!(
+            Renderer::plain().render(&diagnostic, "script.txt", source),
+            "\
+error: expected parameter name or `)`, found end of line
+ --> script.txt:2:11
+  |
+2 | func Test(
+  |          - parameter list starts here
+  |           ^ expected parameter name or `)`
+  |
+  = note: parameters are separated by commas
+  = help: close the parameter list with `)`
+"
+        );
+    }
+
+    #[test]
+    fn test_render_multiple_lines() {
+        let source = "a\nb\nc\nd\n";
+
+        let diagnostic = Diagnostic::warning("shadowed")
+            .with_label(Span::new(1, 1, 1), "first")
+            .with_primary_label(Span::new(4, 1, 1), "second");
+
+        assert_eq!(
+            Renderer::plain().render(&diagnostic, "x", source),
+            "\
+warning: shadowed
+ --> x:4:1
+  |
+1 | a
+  | - first
+...
+4 | d
+  | ^ second
+"
+        );
+    }
+
+    #[test]
+    fn test_render_tabs() {
+        let diagnostic = Diagnostic::error("expected `)`").with_primary_label(Span::new(2, 4, 1), "here");
+
+        assert_eq!(
+            Renderer::plain().render(&diagnostic, "x", "?a\n\t\tb(\n"),
+            "error: expected `)`\n --> x:2:4\n  |\n2 | \t\tb(\n  | \t\t ^ here\n"
+        );
+    }
+
+    #[test]
+    fn test_render_without_span() {
+        let diagnostic = Diagnostic::error("file is not formatted");
+
+        assert_eq!(
+            Renderer::plain().render(&diagnostic, "x", ""),
+            "error: file is not formatted\n --> x\n"
+        );
+    }
+
+    #[test]
+    fn test_render_colored() {
+        let diagnostic = Diagnostic::error("boom").with_span(Span::new(1, 1, 2));
+        let rendered = Renderer::colored().render(&diagnostic, "x", "ab");
+
+        assert!(rendered.starts_with("\x1b[1;31merror\x1b[0m\x1b[1m: boom\x1b[0m\n"));
+        assert!(rendered.contains("\x1b[1;31m^^\x1b[0m"));
+    }
+}
@@ -0,0 +1,148 @@
+use super::{Diagnostic, Label, Severity};
+
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const GREEN: &str = "\x1b[1;32m";
+const BLUE: &str = "\x1b[1;34m";
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Renderer {
+    pub color: bool,
+}
+
+impl Renderer {
+    pub fn plain() -> Renderer {
+        Renderer { color: false }
+    }
+
+    pub fn colored() -> Renderer {
+        Renderer { color: true }
+    }
+
+    fn paint(&self, style: &str, text: &str) -> String {
+        if self.color && !text.is_empty() {
+            format!("{}{}{}", style, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn severity_style(severity: Severity) -> &'static str {
+        match severity {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+            Severity::Note => GREEN,
+        }
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic, file: &str, source: &str) -> String {
+        let style = Renderer::severity_style(diagnostic.severity);
+        let mut output = String::new();
+
+        output.push_str(&self.paint(style, &diagnostic.severity.to_string()));
+        output.push_str(&self.paint(BOLD, &format!(": {}", diagnostic.message)));
+        output.push('\n');
+
+        let mut labels = diagnostic.labels.iter().collect::<Vec<&Label>>();
+        labels.sort_by_key(|label| (label.span.line, label.span.column));
+
+        let gutter = labels
+            .iter()
+            .map(|label| label.span.line.to_string().len())
+            .max()
+            .unwrap_or(1);
+
+        let padding = " ".repeat(gutter);
+        let bar = self.paint(BLUE, "|");
+
+        match diagnostic.span() {
+            Some(span) => output.push_str(&format!(
+                "{}{} {}:{}:{}\n",
+                padding,
+                self.paint(BLUE, "-->"),
+                file,
+                span.line,
+                span.column,
+            )),
+            None => output.push_str(&format!("{}{} {}\n", padding, self.paint(BLUE, "-->"), file)),
+        }
+
+        if !labels.is_empty() {
+            let lines = source.lines().collect::<Vec<&str>>();
+            let mut previous_line: Option<usize> = None;
+
+            output.push_str(&format!("{} {}\n", padding, bar));
+
+            for label in labels {
+                let line = label.span.line;
+
+                let Some(text) = line.checked_sub(1).and_then(|index| lines.get(index)) else {
+                    continue;
+                };
+
+                if previous_line != Some(line) {
+                    if previous_line.is_some_and(|previous| line > previous + 1) {
+                        output.push_str(&format!("{}\n", self.paint(BLUE, "...")));
+                    }
+
+                    output.push_str(&format!(
+                        "{} {} {}\n",
+                        self.paint(BLUE, &format!("{:>width$}", line, width = gutter)),
+                        bar,
+                        text,
+                    ));
+                }
+
+                let (marker, marker_style) = if label.primary {
+                    ("^", style)
+                } else {
+                    ("-", BLUE)
+                };
+
+                let underline = format!(
+                    "{}{}",
+                    marker.repeat(label.span.length.max(1)),
+                    if label.message.is_empty() { String::new() } else { format!(" {}", label.message) },
+                );
+
+                output.push_str(&format!(
+                    "{} {} {}{}\n",
+                    padding,
+                    bar,
+                    indent(text, label.span.column),
+                    self.paint(marker_style, &underline),
+                ));
+
+                previous_line = Some(line);
+            }
+        }
+
+        if !diagnostic.notes.is_empty() || !diagnostic.help.is_empty() {
+            output.push_str(&format!("{} {}\n", padding, bar));
+        }
+
+        for note in &diagnostic.notes {
+            output.push_str(&format!("{} {} {}: {}\n", padding, self.paint(BLUE, "="), self.paint(BOLD, "note"), note));
+        }
+
+        for help in &diagnostic.help {
+            output.push_str(&format!("{} {} {}: {}\n", padding, self.paint(BLUE, "="), self.paint(BOLD, "help"), help));
+        }
+
+        output
+    }
+}
+
+
+// Pads up to `column`, keeping the source line's tabs so the marker lines up however tabs are shown.
+fn indent(text: &str, column: usize) -> String {
+    text.chars()
+        .chain(std::iter::repeat(' '))
+        .take(column.saturating_sub(1))
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect()
+}
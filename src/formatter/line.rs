@@ -0,0 +1,250 @@
+use crate::lexer::tokens::{Token, TokenType};
+use crate::lexer::Lexer;
+
+
+// A token with the source text it was lexed from and the whitespace before it.
+#[derive(Debug, Clone)]
+pub struct Piece<'a> {
+    pub token_type: TokenType,
+    pub text: &'a str,
+    pub gap: &'a str,
+}
+
+
+// The tokens between two line ends. Tokens such as ascii blocks, block
+// comments and strings can span several lines of source.
+#[derive(Debug, Clone)]
+pub struct SourceLine<'a> {
+    pub indent: usize,
+    pub pieces: Vec<Piece<'a>>,
+}
+
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LineKind {
+    Blank,
+    Comment,
+    BlockComment,
+    Code,
+}
+
+
+impl SourceLine<'_> {
+    pub fn kind(&self) -> LineKind {
+        match self.pieces.first().map(|piece| &piece.token_type) {
+            None => LineKind::Blank,
+            Some(TokenType::Comment(_)) => LineKind::Comment,
+            Some(TokenType::CommentBlock(_)) if self.pieces.len() == 1 => LineKind::BlockComment,
+            Some(_) => LineKind::Code,
+        }
+    }
+
+    pub fn starts_with(&self, word: &str) -> bool {
+        matches!(self.pieces.first().map(|piece| &piece.token_type), Some(TokenType::Identifier(name)) if name == word)
+    }
+
+    // Whether the line ends in a token that is still open at the end of
+    // the source, such as an unterminated string. Anything added after it
+    // would become part of the token, and a line end after a trailing
+    // `ascii` would start an ascii block.
+    pub fn is_open(&self) -> bool {
+        let Some(last) = self.pieces.last() else {
+            return false;
+        };
+
+        match &last.token_type {
+            TokenType::String(_) => last.text.len() < 2 || !last.text.ends_with('"'),
+            TokenType::CommentBlock(_) => last.text.len() < 4 || !last.text.ends_with("*/"),
+            TokenType::AsciiBlock(_) => !last.text.ends_with("asciiend"),
+            TokenType::Identifier(name) => name == "ascii",
+            TokenType::Text(text) => {
+                let ascii = text
+                    .strip_suffix("ascii")
+                    .is_some_and(|rest| !rest.ends_with(|c: char| c.is_alphanumeric()));
+
+                ascii || (last.text.contains('\n') && !last.text.ends_with("asciiend"))
+            },
+            _ => false,
+        }
+    }
+}
+
+
+pub fn split_lines(source: &str) -> Vec<SourceLine<'_>> {
+    let tokens = Lexer::new(source).collect::<Vec<Token>>();
+    let starts = line_starts(source);
+
+    let offsets = tokens
+        .iter()
+        .map(|token| offset(source, &starts, token))
+        .chain(std::iter::once(source.len()))
+        .collect::<Vec<usize>>();
+
+    let mut lines = vec![SourceLine { indent: 0, pieces: vec![] }];
+    let mut gap = "";
+
+    for (index, token) in tokens.into_iter().enumerate() {
+        let raw = &source[offsets[index]..offsets[index + 1]];
+        let last = index + 2 == offsets.len();
+
+        // Text runs up to the line end, and a token cut off by the end of
+        // the source keeps everything it took, so neither is trimmed.
+        let text = match &token.token_type {
+            TokenType::Text(_) => raw,
+            TokenType::String(_) | TokenType::CommentBlock(_) | TokenType::AsciiBlock(_) if last => raw,
+            _ => raw.trim_end_matches([' ', '\t', '\r']),
+        };
+
+        let line = lines.last_mut().unwrap();
+
+        match token.token_type {
+            TokenType::EndLine => lines.push(SourceLine { indent: 0, pieces: vec![] }),
+            TokenType::NewLineIndent(indent) if line.pieces.is_empty() => line.indent = indent as usize,
+            token_type => line.pieces.push(Piece { token_type, text, gap }),
+        }
+
+        gap = &raw[text.len()..];
+    }
+
+    lines
+}
+
+
+fn line_starts(source: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+        .collect()
+}
+
+
+fn offset(source: &str, starts: &[usize], token: &Token) -> usize {
+    let start = starts[token.line - 1];
+
+    source[start..]
+        .char_indices()
+        .nth(token.column - 1)
+        .map_or(source.len(), |(index, _)| start + index)
+}
+
+
+// The pieces as they were written, for lines whose spacing cannot change.
+pub fn original(pieces: &[Piece]) -> String {
+    pieces
+        .iter()
+        .enumerate()
+        .map(|(index, piece)| match index {
+            0 => piece.text.to_string(),
+            _ => format!("{}{}", piece.gap, piece.text),
+        })
+        .collect()
+}
+
+
+// Lexes a rendered line on its own to make sure spacing did not change
+// how it splits into tokens.
+pub fn same_tokens(rendered: &str, pieces: &[Piece]) -> bool {
+    let tokens = Lexer::new(rendered).map(|token| token.token_type);
+
+    tokens.eq(pieces.iter().map(|piece| piece.token_type.clone()))
+}
+
+
+fn is_operand_end(piece: Option<&Piece>) -> bool {
+    match piece.map(|piece| &piece.token_type) {
+        Some(TokenType::Identifier(word)) => word != "return",
+        Some(TokenType::String(_) | TokenType::ParenthesisClose | TokenType::SquareBracketClose) => true,
+        Some(TokenType::AsciiBlock(_) | TokenType::CommentBlock(_) | TokenType::Unknown(_)) => true,
+        Some(TokenType::Increment | TokenType::Decrement) => true,
+        _ => false,
+    }
+}
+
+
+fn is_operator(token_type: &TokenType) -> bool {
+    !token_type.symbol().is_empty() && !matches!(
+        token_type,
+        TokenType::ParenthesisOpen | TokenType::ParenthesisClose | TokenType::SquareBracketOpen | TokenType::SquareBracketClose | TokenType::Comma
+    )
+}
+
+
+pub fn normalize_spacing(pieces: &[Piece]) -> String {
+    let mut output = String::new();
+    let mut previous: Option<&Piece> = None;
+    let mut space = false;
+    let mut in_path = false;
+
+    for (index, piece) in pieces.iter().enumerate() {
+        let spaced = !piece.gap.is_empty();
+        let after_return = matches!(previous.map(|piece| &piece.token_type), Some(TokenType::Identifier(word)) if word == "return");
+
+        match &piece.token_type {
+            TokenType::Comma => {
+                output.push_str(piece.text);
+                space = true;
+            },
+            TokenType::ParenthesisClose | TokenType::SquareBracketClose => {
+                output.push_str(piece.text);
+                space = false;
+            },
+            TokenType::Increment | TokenType::Decrement => {
+                if space || (spaced && after_return) {
+                    output.push(' ');
+                }
+
+                output.push_str(piece.text);
+                space = false;
+            },
+            TokenType::NotEqual | TokenType::Subtract | TokenType::Add if !is_operand_end(previous) => {
+                if space || (spaced && after_return) {
+                    output.push(' ');
+                }
+
+                output.push_str(piece.text);
+                space = false;
+            },
+            TokenType::Range | TokenType::Dot => {
+                output.push_str(piece.text);
+                space = false;
+            },
+            TokenType::If | TokenType::Else | TokenType::ElseIf | TokenType::Greater if index == 0 => {
+                output.push_str(piece.text);
+            },
+            TokenType::Text(_) => {
+                output.push_str(piece.gap);
+                output.push_str(piece.text);
+            },
+            TokenType::Divide if in_path => output.push_str(piece.text),
+            token_type if is_operator(token_type) => {
+                if !output.is_empty() {
+                    output.push(' ');
+                }
+
+                output.push_str(piece.text);
+                space = true;
+            },
+            _ => {
+                let glued = previous.is_none_or(|piece| {
+                    is_operator(&piece.token_type) || matches!(piece.token_type, TokenType::ParenthesisOpen | TokenType::SquareBracketOpen)
+                });
+
+                if space || (spaced && !glued) {
+                    output.push(' ');
+                }
+
+                output.push_str(piece.text);
+                space = false;
+            },
+        }
+
+        in_path = match &piece.token_type {
+            TokenType::Identifier(word) => word == "new" || in_path,
+            TokenType::Divide => in_path,
+            _ => false,
+        };
+
+        previous = Some(piece);
+    }
+
+    output
+}
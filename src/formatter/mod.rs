@@ -0,0 +1,361 @@
+pub mod line;
+
+use crate::diagnostics::Diagnostic;
+use crate::lexer::tokens::TokenType;
+use crate::parser::without_spans;
+
+use line::{normalize_spacing, original, same_tokens, split_lines, LineKind, SourceLine};
+
+
+#[derive(Debug, Clone)]
+pub struct FormatOptions {
+    pub indent_width: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions { indent_width: 2 }
+    }
+}
+
+
+struct FormattedLine {
+    kind: LineKind,
+    level: usize,
+    text: String,
+    comment: Option<String>,
+    function: bool,
+}
+
+
+pub fn format(source: &str, options: &FormatOptions) -> Result<String, Diagnostic> {
+    let program = crate::parse(source)?;
+    let source_lines = split_lines(source);
+    let mut lines = collect_lines(&source_lines, options);
+
+    align_comments(&mut lines);
+
+    let open = source_lines.last().is_some_and(SourceLine::is_open);
+    let formatted = join_lines(&lines, open);
+
+    // Formatting only moves whitespace between tokens, so a change in the
+    // parsed program is a bug in the formatter and the input is left alone.
+    let reparsed = crate::parse(&formatted).map(without_spans);
+
+    match reparsed == Ok(without_spans(program)) {
+        true => Ok(formatted),
+        false => Err(
+            Diagnostic::error("formatting would change the meaning of this script")
+                .with_note("the file was left unchanged")
+        ),
+    }
+}
+
+
+pub fn check(source: &str, options: &FormatOptions) -> Result<bool, Diagnostic> {
+    Ok(format(source, options)? == source)
+}
+
+
+fn code_level(stack: &mut Vec<usize>, width: usize) -> usize {
+    while stack.len() > 1 && *stack.last().unwrap() > width {
+        stack.pop();
+    }
+
+    if *stack.last().unwrap() < width {
+        stack.push(width);
+    }
+
+    stack.len() - 1
+}
+
+
+fn comment_level(stack: &[usize], width: usize) -> usize {
+    if *stack.last().unwrap() < width {
+        return stack.len();
+    }
+
+    stack.iter().filter(|w| **w <= width).count().max(1) - 1
+}
+
+
+fn collect_lines(source_lines: &[SourceLine], options: &FormatOptions) -> Vec<FormattedLine> {
+    let mut lines = vec![];
+    let mut stack = vec![0];
+
+    for line in source_lines {
+        let kind = line.kind();
+
+        let level = match kind {
+            LineKind::Blank => 0,
+            LineKind::Comment | LineKind::BlockComment => comment_level(&stack, line.indent),
+            LineKind::Code => code_level(&mut stack, line.indent),
+        };
+
+        let indent = " ".repeat(level * options.indent_width);
+
+        let (text, comment) = match kind {
+            LineKind::Blank => (String::new(), None),
+            LineKind::Comment | LineKind::BlockComment => (format!("{}{}", indent, line.pieces[0].text), None),
+            LineKind::Code => {
+                let (code, comment) = match line.pieces.split_last() {
+                    Some((last, code)) if matches!(last.token_type, TokenType::Comment(_)) => (code, Some(last.text.to_string())),
+                    _ => (&line.pieces[..], None),
+                };
+
+                let mut text = normalize_spacing(code);
+
+                if !same_tokens(&text, code) {
+                    text = original(code);
+                }
+
+                (format!("{}{}", indent, text), comment)
+            },
+        };
+
+        lines.push(FormattedLine {
+            function: kind == LineKind::Code && level == 0 && line.starts_with("func"),
+            kind,
+            level,
+            text,
+            comment,
+        });
+    }
+
+    lines
+}
+
+
+fn align_comments(lines: &mut [FormattedLine]) {
+    let mut start = 0;
+
+    while start < lines.len() {
+        let has_comment = |line: &FormattedLine| line.kind == LineKind::Code && line.comment.is_some();
+
+        if !has_comment(&lines[start]) {
+            start += 1;
+            continue;
+        }
+
+        let mut end = start;
+
+        while end < lines.len() && has_comment(&lines[end]) {
+            end += 1;
+        }
+
+        let column = lines[start..end]
+            .iter()
+            .map(|line| width(&line.text))
+            .max()
+            .unwrap_or(0);
+
+        for line in &mut lines[start..end] {
+            let padding = column - width(&line.text) + 1;
+            let comment = line.comment.take().unwrap();
+
+            line.text = format!("{}{}{}", line.text, " ".repeat(padding), comment);
+        }
+
+        start = end;
+    }
+}
+
+
+// The width of the last line, as ascii blocks and block comments can span several.
+fn width(text: &str) -> usize {
+    text.rsplit('\n').next().unwrap_or("").chars().count()
+}
+
+
+fn join_lines(lines: &[FormattedLine], open: bool) -> String {
+    let mut blank_before = vec![false; lines.len()];
+
+    for (index, line) in lines.iter().enumerate() {
+        if !line.function {
+            continue;
+        }
+
+        let mut anchor = index;
+
+        while anchor > 0 && lines[anchor - 1].kind == LineKind::Comment && lines[anchor - 1].level == 0 {
+            anchor -= 1;
+        }
+
+        blank_before[anchor] = true;
+
+        let end = lines[index + 1..]
+            .iter()
+            .position(|line| line.level == 0 && line.kind != LineKind::Blank);
+
+        if let Some(end) = end {
+            blank_before[index + 1 + end] = true;
+        }
+    }
+
+    let mut output: Vec<&str> = vec![];
+    let mut pending_blank = false;
+
+    for (index, line) in lines.iter().enumerate() {
+        if line.kind == LineKind::Blank {
+            pending_blank = true;
+            continue;
+        }
+
+        if (pending_blank || blank_before[index]) && !output.is_empty() {
+            output.push("");
+        }
+
+        pending_blank = false;
+        output.push(&line.text);
+    }
+
+    if output.is_empty() {
+        return String::new();
+    }
+
+    match open {
+        true => output.join("\n"),
+        false => output.join("\n") + "\n",
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format_default(source: &str) -> String {
+        format(source, &FormatOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn test_indentation() {
+        assert_eq!(
+            format_default("?a\n    b\n    ?c\n        d\n:\n    e\n"),
+            "?a\n  b\n  ?c\n    d\n:\n  e\n"
+        );
+    }
+
+    #[test]
+    fn test_indentation_width() {
+        let options = FormatOptions { indent_width: 4 };
+
+        assert_eq!(format("?a\n b\n", &options).unwrap(), "?a\n    b\n");
+    }
+
+    #[test]
+    fn test_operator_spacing() {
+        assert_eq!(format_default("var x=1+2*3\n"), "var x = 1 + 2 * 3\n");
+        assert_eq!(format_default("?hp<10&foe!boss\n"), "?hp < 10 & foe ! boss\n");
+        assert_eq!(format_default("?  !foe\n"), "?!foe\n");
+        assert_eq!(format_default("x = -1\n"), "x = -1\n");
+        assert_eq!(format_default("i ++\n"), "i++\n");
+        assert_eq!(format_default("var s = f( a,b )\n"), "var s = f(a, b)\n");
+        assert_eq!(format_default("var p = foe.distance/2.5\n"), "var p = foe.distance / 2.5\n");
+        assert_eq!(format_default("for i=0..10\n"), "for i = 0..10\n");
+        assert_eq!(format_default("var c = new Components/Counter\n"), "var c = new Components/Counter\n");
+        assert_eq!(format_default("var s = \"a+b  c\"+x\n"), "var s = \"a+b  c\" + x\n");
+    }
+
+    #[test]
+    fn test_print_and_commands_untouched() {
+        let source = "?a\n    >o1,2,#red,Hello   world!  \n    equip vigor crossbow *8 +10\n";
+
+        assert_eq!(
+            format_default(source),
+            "?a\n  >o1,2,#red,Hello   world!  \n  equip vigor crossbow *8 +10\n"
+        );
+    }
+
+    #[test]
+    fn test_ascii_untouched() {
+        let source = "?a\n    >o1,1,ascii\n  /\\\n\n ( o )  \nasciiend\n    b=1\n";
+
+        assert_eq!(
+            format_default(source),
+            "?a\n  >o1,1,ascii\n  /\\\n\n ( o )  \nasciiend\n  b = 1\n"
+        );
+    }
+
+    #[test]
+    fn test_blank_lines_between_functions() {
+        let source = "\n\nvar a=1\nfunc A()\n  return 1\nfunc B()\n\n\n  return 2\n// entry\nfunc C()\n  return 3\nA()\n\n";
+
+        assert_eq!(
+            format_default(source),
+            "var a = 1\n\nfunc A()\n  return 1\n\nfunc B()\n\n  return 2\n\n// entry\nfunc C()\n  return 3\n\nA()\n"
+        );
+    }
+
+    #[test]
+    fn test_comment_alignment() {
+        let source = "var a=1 // first\nvar longer = 2 // second\n// standalone\nvar b = 3    // third\n";
+
+        assert_eq!(
+            format_default(source),
+            "var a = 1      // first\nvar longer = 2 // second\n// standalone\nvar b = 3 // third\n"
+        );
+    }
+
+    #[test]
+    fn test_block_comment_untouched() {
+        let source = "/* keep\n     this   as is\n*/\nvar a=1\n";
+
+        assert_eq!(format_default(source), "/* keep\n     this   as is\n*/\nvar a = 1\n");
+    }
+
+    #[test]
+    fn test_idempotent() {
+        let source = "// header\nvar   count=0\nfunc Tick( n )\n    count+=n\n    ?count>=10&!done // limit\n        >@count@ reached\n        count=0  // reset\n    :?count<0\n        count = -count\nfunc Draw()\n    >o1,1,ascii\n##\n asciiend\nasciiend\nTick(1)\n";
+
+        let once = format_default(source);
+        let twice = format_default(&once);
+
+        assert!(once.ends_with("  >o1,1,ascii\n##\n asciiend\nasciiend\n\nTick(1)\n"));
+        assert_eq!(once, twice);
+        assert!(check(&once, &FormatOptions::default()).unwrap());
+        assert!(!check(source, &FormatOptions::default()).unwrap());
+    }
+
+    #[test]
+    fn test_same_program() {
+        let sources = [
+            "var s = \"abc\nx=1",
+            "var s = \"abc\nx=1\n",
+            "var s = \"a  \"",
+            "var x = a.ascii",
+            ">o1,1,ascii",
+            ">o1,1,ascii\n  x=1  \n",
+            "var art = ascii\n ( o )\n",
+            "/* never\n  closed  ",
+            "?a\r\n\t>hi \r\n\tb=1\r\n",
+            "var a=1 /* x */ +2 // c\nvar b = [ 1,2 ]\n",
+            "var n = 1 . 5\n",
+            "equip  vigor crossbow\n>  text\n",
+        ];
+
+        for source in sources {
+            let once = format_default(source);
+
+            assert_eq!(
+                without_spans(crate::parse(&once).unwrap()),
+                without_spans(crate::parse(source).unwrap()),
+                "{:?} became {:?}", source, once
+            );
+            assert_eq!(format_default(&once), once, "{:?}", source);
+        }
+
+        assert_eq!(format_default("var s = \"abc\nx=1"), "var s = \"abc\nx=1");
+        assert_eq!(format_default(">o1,1,ascii\n  x=1  \n"), ">o1,1,ascii\n  x=1  \n");
+    }
+
+    #[test]
+    fn test_invalid_input_refused() {
+        let options = FormatOptions::default();
+
+        assert!(format("var x = (1\n", &options).is_err());
+        assert!(format("var x = a.ascii\nfoo\n", &options).is_err());
+        assert!(format("?a\n    b()\n  c()\n", &options).is_err());
+        assert!(check("var x = (1\n", &options).is_err());
+    }
+}
@@ -0,0 +1,888 @@
+use std::collections::HashMap;
+
+use crate::diagnostics::{Diagnostic, Span};
+use crate::parser::structs::{BinaryOperator, Expression, UnaryOperator};
+
+use super::{binary_chain, dotted_name};
+use super::trace::GameCommand;
+use super::value::RuntimeValue;
+
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Variable {
+    pub local: Option<u32>,
+    pub name: u32,
+}
+
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Slot {
+    Local(u32),
+    Global(u32),
+}
+
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Instruction {
+    Constant(u32),
+    Null,
+    Pop,
+    Dup,
+    Span(u32),
+    Step,
+    Load(Variable),
+    Resolve { variable: Variable, found: u32 },
+    JumpIfDeclared { variable: Variable, target: u32 },
+    Declare(Slot),
+    Store(Variable),
+    Binary(BinaryOperator),
+    Compound(BinaryOperator),
+    Not,
+    Negate,
+    Adjust(UnaryOperator),
+    And(u32),
+    Or(u32),
+    Truthy,
+    Jump(u32),
+    JumpIfFalse(u32),
+    Index,
+    SetIndex,
+    Field { name: u32, fallback: Option<u32> },
+    SetField(u32),
+    Array(u32),
+    Call { name: u32, arguments: u32 },
+    CallDotted { call: u32, arguments: u32, done: u32 },
+    Method { name: u32, arguments: u32, call: Option<u32> },
+    Define(u32),
+    DefineMethod(u32),
+    New(u32),
+    This,
+    Fail(u32),
+    ToInteger,
+    Range,
+    LoopEnter(u32),
+    Current(Slot),
+    Iterate,
+    RangeNext(u32),
+    Each,
+    EachNext { slot: Slot, exit: u32 },
+    LoopExit(u32),
+    Print(u32),
+    Command { name: u32, span: u32, parts: u32 },
+    Return,
+    Halt,
+}
+
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DottedCall {
+    pub function: u32,
+    pub path: Variable,
+}
+
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Statement {
+    pub start: u32,
+    pub end: u32,
+    pub depth: u32,
+    pub loops: u32,
+}
+
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Chunk {
+    pub name: u32,
+    pub parameters: Vec<u32>,
+    pub locals: u32,
+    pub code: Vec<Instruction>,
+    pub statements: Vec<Statement>,
+}
+
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Program {
+    pub chunks: Vec<Chunk>,
+    pub constants: Vec<RuntimeValue>,
+    pub names: Vec<String>,
+    pub spans: Vec<Span>,
+    pub calls: Vec<DottedCall>,
+}
+
+
+impl Program {
+    pub fn name(&self, index: u32) -> &str {
+        &self.names[index as usize]
+    }
+
+    pub fn instructions(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.code.len()).sum()
+    }
+}
+
+
+pub fn compile(program: &Expression) -> Result<Program, Diagnostic> {
+    let mut compiler = Compiler::default();
+
+    compiler.program.chunks.push(Chunk::default());
+    compiler.statement(program)?;
+    compiler.emit(Instruction::Halt);
+
+    let main = std::mem::take(&mut compiler.chunk);
+    let name = compiler.name("");
+
+    compiler.program.chunks[0] = main.finish(name, vec![]);
+
+    Ok(compiler.program)
+}
+
+
+// Object scripts are loaded when `new` first runs, so their chunks are
+// appended to an already compiled program.
+pub fn compile_object(program: Program, path: &str, script: &Expression) -> Result<(Program, u32), Diagnostic> {
+    let mut compiler = Compiler::resume(program);
+    let chunk = compiler.chunk(&format!("new {}", path), &[], script, true)?;
+
+    Ok((compiler.program, chunk))
+}
+
+
+#[derive(Default)]
+struct Loop {
+    breaks: Vec<usize>,
+    continues: Vec<usize>,
+}
+
+
+#[derive(Default)]
+struct Builder {
+    code: Vec<Instruction>,
+    statements: Vec<Statement>,
+    locals: Option<HashMap<String, u32>>,
+    loops: Vec<Loop>,
+    depth: u32,
+    object: bool,
+}
+
+
+impl Builder {
+    fn finish(self, name: u32, parameters: Vec<u32>) -> Chunk {
+        Chunk {
+            name,
+            parameters,
+            locals: self.locals.map_or(0, |locals| locals.len() as u32),
+            code: self.code,
+            statements: self.statements,
+        }
+    }
+}
+
+
+#[derive(Default)]
+struct Compiler {
+    program: Program,
+    names: HashMap<String, u32>,
+    constants: HashMap<String, u32>,
+    chunk: Builder,
+}
+
+
+impl Compiler {
+    fn resume(program: Program) -> Self {
+        let names = program.names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (name.clone(), index as u32))
+            .collect();
+
+        let constants = program.constants
+            .iter()
+            .enumerate()
+            .map(|(index, value)| (format!("{:?}", value), index as u32))
+            .collect();
+
+        Compiler { program, names, constants, chunk: Builder::default() }
+    }
+
+    fn emit(&mut self, instruction: Instruction) -> usize {
+        self.chunk.code.push(instruction);
+        self.chunk.code.len() - 1
+    }
+
+    fn here(&self) -> u32 {
+        self.chunk.code.len() as u32
+    }
+
+    fn patch(&mut self, at: usize) {
+        let here = self.here();
+
+        match &mut self.chunk.code[at] {
+            Instruction::Jump(target)
+            | Instruction::JumpIfFalse(target)
+            | Instruction::And(target)
+            | Instruction::Or(target)
+            | Instruction::Resolve { found: target, .. }
+            | Instruction::JumpIfDeclared { target, .. }
+            | Instruction::CallDotted { done: target, .. }
+            | Instruction::EachNext { exit: target, .. } => *target = here,
+            instruction => unreachable!("cannot patch {:?}", instruction),
+        }
+    }
+
+    fn name(&mut self, name: &str) -> u32 {
+        if let Some(index) = self.names.get(name) {
+            return *index;
+        }
+
+        let index = self.program.names.len() as u32;
+
+        self.program.names.push(name.to_string());
+        self.names.insert(name.to_string(), index);
+
+        index
+    }
+
+    fn constant(&mut self, value: RuntimeValue) -> u32 {
+        let key = format!("{:?}", value);
+
+        if let Some(index) = self.constants.get(&key) {
+            return *index;
+        }
+
+        let index = self.program.constants.len() as u32;
+
+        self.program.constants.push(value);
+        self.constants.insert(key, index);
+
+        index
+    }
+
+    fn span(&mut self, span: Span) -> u32 {
+        self.program.spans.push(span);
+        self.program.spans.len() as u32 - 1
+    }
+
+    fn variable(&mut self, name: &str) -> Variable {
+        let local = self.chunk.locals.as_ref().and_then(|locals| locals.get(name)).copied();
+
+        Variable { local, name: self.name(name) }
+    }
+
+    fn slot(&mut self, name: &str) -> Slot {
+        match &mut self.chunk.locals {
+            Some(locals) => {
+                let next = locals.len() as u32;
+                Slot::Local(*locals.entry(name.to_string()).or_insert(next))
+            },
+            None => Slot::Global(self.name(name)),
+        }
+    }
+
+    fn fail(&mut self, message: &str) {
+        let message = self.constant(RuntimeValue::from(message));
+        self.emit(Instruction::Fail(message));
+    }
+
+    fn escape(&mut self) {
+        match self.chunk.locals.is_some() {
+            true => {
+                self.emit(Instruction::Null);
+                self.emit(Instruction::Return);
+            },
+            false => {
+                self.emit(Instruction::Halt);
+            },
+        }
+    }
+
+    fn statement(&mut self, expression: &Expression) -> Result<(), Diagnostic> {
+        if let Some(span) = expression.span() {
+            let span = self.span(span);
+            self.emit(Instruction::Span(span));
+        }
+
+        self.emit(Instruction::Step);
+
+        match expression {
+            Expression::Block { statements } => self.block(statements)?,
+            Expression::Assignment { name, value, .. } if self.chunk.object => {
+                self.expression(value)?;
+
+                let field = self.name(name);
+
+                self.emit(Instruction::This);
+                self.emit(Instruction::SetField(field));
+            },
+            Expression::Assignment { name, value, .. } if self.chunk.locals.is_some() => {
+                self.expression(value)?;
+
+                let slot = self.slot(name);
+                self.emit(Instruction::Declare(slot));
+            },
+            Expression::Assignment { name, value, .. } => {
+                let name = self.name(name);
+                let skip = self.emit(Instruction::JumpIfDeclared { variable: Variable { local: None, name }, target: 0 });
+
+                self.expression(value)?;
+                self.emit(Instruction::Declare(Slot::Global(name)));
+                self.patch(skip);
+            },
+            Expression::Reassignment { target, operator, value, .. } => {
+                self.expression(value)?;
+
+                if let Some(operator) = operator {
+                    self.expression(target)?;
+                    self.emit(Instruction::Compound(*operator));
+                }
+
+                self.store(target)?;
+            },
+            Expression::Condition { condition, then_branch, else_branch, .. } => {
+                self.expression(condition)?;
+
+                let otherwise = self.emit(Instruction::JumpIfFalse(0));
+
+                self.statement(then_branch)?;
+
+                match else_branch {
+                    Some(else_branch) => {
+                        let end = self.emit(Instruction::Jump(0));
+
+                        self.patch(otherwise);
+                        self.statement(else_branch)?;
+                        self.patch(end);
+                    },
+                    None => self.patch(otherwise),
+                }
+            },
+            Expression::Function { name, parameters, body } => {
+                let chunk = self.function(name, parameters, body)?;
+                self.define(chunk);
+            },
+            Expression::ForRange { variable, start, end, body, span } => {
+                self.expression(start)?;
+                self.emit(Instruction::ToInteger);
+                self.expression(end)?;
+                self.emit(Instruction::ToInteger);
+                self.emit(Instruction::Range);
+
+                let span = self.span(*span);
+                let slot = self.slot(variable);
+
+                self.emit(Instruction::LoopEnter(span));
+                self.chunk.depth += 3;
+
+                let top = self.here();
+
+                self.emit(Instruction::Current(slot));
+                self.emit(Instruction::Iterate);
+
+                let exits = self.body(body, span)?;
+
+                self.emit(Instruction::RangeNext(top));
+
+                for exit in exits {
+                    self.patch(exit);
+                }
+
+                self.emit(Instruction::LoopExit(3));
+                self.chunk.depth -= 3;
+            },
+            Expression::ForEach { variable, iterable, body, span } => {
+                self.expression(iterable)?;
+                self.emit(Instruction::Each);
+
+                let span = self.span(*span);
+                let slot = self.slot(variable);
+
+                self.emit(Instruction::LoopEnter(span));
+                self.chunk.depth += 2;
+
+                let top = self.here();
+                let next = self.emit(Instruction::EachNext { slot, exit: 0 });
+
+                self.emit(Instruction::Iterate);
+
+                let exits = self.body(body, span)?;
+
+                self.emit(Instruction::Jump(top));
+
+                for exit in exits.into_iter().chain([next]) {
+                    self.patch(exit);
+                }
+
+                self.emit(Instruction::LoopExit(2));
+                self.chunk.depth -= 2;
+            },
+            Expression::Return { value, .. } => {
+                match value {
+                    Some(value) => self.expression(value)?,
+                    None => {
+                        self.emit(Instruction::Null);
+                    },
+                }
+
+                match self.chunk.locals.is_some() {
+                    true => self.emit(Instruction::Return),
+                    false => self.emit(Instruction::Halt),
+                };
+            },
+            Expression::Break | Expression::Continue => {
+                let continues = matches!(expression, Expression::Continue);
+
+                match self.chunk.loops.is_empty() {
+                    true => self.escape(),
+                    false => {
+                        let jump = self.emit(Instruction::Jump(0));
+                        let current = self.chunk.loops.last_mut().unwrap();
+
+                        match continues {
+                            true => current.continues.push(jump),
+                            false => current.breaks.push(jump),
+                        }
+                    },
+                }
+            },
+            Expression::Print { text, .. } => {
+                let parts = self.interpolation(text)?;
+                self.emit(Instruction::Print(parts));
+            },
+            Expression::Command { name, arguments, span } if GameCommand::is_game_command(name) => {
+                let parts = self.interpolation(arguments)?;
+                let name = self.name(name);
+                let span = self.span(*span);
+
+                self.emit(Instruction::Command { name, span, parts });
+            },
+            Expression::Command { .. } | Expression::Nope => {},
+            _ => {
+                self.expression(expression)?;
+                self.emit(Instruction::Pop);
+            },
+        }
+
+        Ok(())
+    }
+
+    fn block(&mut self, statements: &[Expression]) -> Result<(), Diagnostic> {
+        let mut functions = vec![];
+
+        for statement in statements {
+            functions.push(match statement {
+                Expression::Function { name, parameters, body } => {
+                    let chunk = self.function(name, parameters, body)?;
+                    self.define(chunk);
+                    Some(chunk)
+                },
+                _ => None,
+            });
+        }
+
+        for (statement, function) in statements.iter().zip(functions) {
+            let start = self.here();
+
+            match function {
+                Some(chunk) => {
+                    self.emit(Instruction::Step);
+                    self.define(chunk);
+                },
+                None => self.statement(statement)?,
+            }
+
+            self.chunk.statements.push(Statement {
+                start,
+                end: self.here(),
+                depth: self.chunk.depth,
+                loops: self.chunk.loops.len() as u32,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn body(&mut self, body: &Expression, span: u32) -> Result<Vec<usize>, Diagnostic> {
+        self.chunk.loops.push(Loop::default());
+        self.statement(body)?;
+
+        let current = self.chunk.loops.pop().unwrap_or_default();
+
+        for jump in current.continues {
+            self.patch(jump);
+        }
+
+        self.emit(Instruction::Span(span));
+
+        Ok(current.breaks)
+    }
+
+    // Functions declared while an object script runs belong to the object.
+    fn define(&mut self, chunk: u32) {
+        match self.chunk.object {
+            true => self.emit(Instruction::DefineMethod(chunk)),
+            false => self.emit(Instruction::Define(chunk)),
+        };
+    }
+
+    fn function(&mut self, name: &str, parameters: &[String], body: &Expression) -> Result<u32, Diagnostic> {
+        self.chunk(name, parameters, body, false)
+    }
+
+    fn chunk(&mut self, name: &str, parameters: &[String], body: &Expression, object: bool) -> Result<u32, Diagnostic> {
+        let index = self.program.chunks.len() as u32;
+
+        self.program.chunks.push(Chunk::default());
+
+        let mut locals = HashMap::new();
+
+        for local in parameters.iter().cloned().chain(declarations(body)) {
+            let next = locals.len() as u32;
+            locals.entry(local).or_insert(next);
+        }
+
+        let parameters = parameters.iter().map(|parameter| locals[parameter]).collect();
+        let outer = std::mem::replace(&mut self.chunk, Builder { locals: Some(locals), object, ..Builder::default() });
+
+        self.statement(body)?;
+        self.escape();
+
+        let chunk = std::mem::replace(&mut self.chunk, outer);
+        let name = self.name(name);
+
+        self.program.chunks[index as usize] = chunk.finish(name, parameters);
+
+        Ok(index)
+    }
+
+    fn expression(&mut self, expression: &Expression) -> Result<(), Diagnostic> {
+        match expression {
+            Expression::Value(value) => {
+                let constant = self.constant(RuntimeValue::from(value));
+                self.emit(Instruction::Constant(constant));
+            },
+            Expression::Identifier(name) => {
+                let variable = self.variable(name);
+                self.emit(Instruction::Load(variable));
+            },
+            Expression::Binary { .. } => {
+                let (first, rest) = binary_chain(expression);
+
+                self.expression(first)?;
+
+                for (operator, right) in rest {
+                    let skip = match operator {
+                        BinaryOperator::And => Some(self.emit(Instruction::And(0))),
+                        BinaryOperator::Or => Some(self.emit(Instruction::Or(0))),
+                        _ => None,
+                    };
+
+                    self.expression(right)?;
+
+                    match skip {
+                        Some(skip) => {
+                            self.emit(Instruction::Truthy);
+                            self.patch(skip);
+                        },
+                        None => {
+                            self.emit(Instruction::Binary(*operator));
+                        },
+                    }
+                }
+            },
+            Expression::Unary { operator: UnaryOperator::Negate, right } => {
+                self.expression(right)?;
+                self.emit(Instruction::Not);
+            },
+            Expression::Unary { operator: UnaryOperator::Minus, right } => {
+                self.expression(right)?;
+                self.emit(Instruction::Negate);
+            },
+            Expression::Unary { operator, right } => {
+                self.expression(right)?;
+                self.emit(Instruction::Adjust(*operator));
+                self.emit(Instruction::Dup);
+                self.store(right)?;
+            },
+            Expression::Call { callee, arguments, span } => self.call(callee, arguments, *span)?,
+            Expression::Member { target, name } => self.member(target, name)?,
+            Expression::Index { target, index } => {
+                self.expression(target)?;
+                self.expression(index)?;
+                self.emit(Instruction::Index);
+            },
+            Expression::Array { elements } => {
+                for element in elements {
+                    self.expression(element)?;
+                }
+
+                self.emit(Instruction::Array(elements.len() as u32));
+            },
+            Expression::New { path, span } => {
+                let span = self.span(*span);
+                let path = self.name(path);
+
+                self.emit(Instruction::Span(span));
+                self.emit(Instruction::New(path));
+            },
+            Expression::This => {
+                self.emit(Instruction::This);
+            },
+            Expression::Nope => {
+                self.emit(Instruction::Null);
+            },
+            _ => {
+                self.statement(expression)?;
+                self.emit(Instruction::Null);
+            },
+        }
+
+        Ok(())
+    }
+
+    fn store(&mut self, target: &Expression) -> Result<(), Diagnostic> {
+        match target {
+            Expression::Identifier(name) => {
+                let variable = self.variable(name);
+                self.emit(Instruction::Store(variable));
+            },
+            Expression::Index { target, index } => {
+                self.expression(target)?;
+                self.expression(index)?;
+                self.emit(Instruction::SetIndex);
+            },
+            Expression::Member { target: object, name: field } => {
+                let field = self.name(field);
+
+                match dotted_name(target) {
+                    Some(full) => {
+                        let variable = self.variable(&full);
+                        let assign = self.emit(Instruction::JumpIfDeclared { variable, target: 0 });
+
+                        self.expression(object)?;
+                        self.emit(Instruction::SetField(field));
+
+                        let done = self.emit(Instruction::Jump(0));
+
+                        self.patch(assign);
+                        self.emit(Instruction::Store(variable));
+                        self.patch(done);
+                    },
+                    None => {
+                        self.expression(object)?;
+                        self.emit(Instruction::SetField(field));
+                    },
+                }
+            },
+            _ => self.fail("invalid assignment target"),
+        }
+
+        Ok(())
+    }
+
+    fn member(&mut self, target: &Expression, name: &str) -> Result<(), Diagnostic> {
+        let field = self.name(name);
+        let mut done = vec![];
+
+        if let Some(path) = dotted_name(target) {
+            let full = self.variable(&format!("{}.{}", path, name));
+            let path = self.variable(&path);
+
+            done.push(self.emit(Instruction::Resolve { variable: full, found: 0 }));
+
+            let strict = self.emit(Instruction::JumpIfDeclared { variable: path, target: 0 });
+
+            self.expression(target)?;
+            self.emit(Instruction::Field { name: field, fallback: Some(full.name) });
+
+            done.push(self.emit(Instruction::Jump(0)));
+            self.patch(strict);
+        }
+
+        self.expression(target)?;
+        self.emit(Instruction::Field { name: field, fallback: None });
+
+        for jump in done {
+            self.patch(jump);
+        }
+
+        Ok(())
+    }
+
+    fn call(&mut self, callee: &Expression, arguments: &[Expression], span: Span) -> Result<(), Diagnostic> {
+        let span = self.span(span);
+
+        self.emit(Instruction::Span(span));
+
+        for argument in arguments {
+            self.expression(argument)?;
+        }
+
+        self.emit(Instruction::Span(span));
+
+        let count = arguments.len() as u32;
+
+        match callee {
+            Expression::Identifier(name) => {
+                let name = self.name(name);
+                self.emit(Instruction::Call { name, arguments: count });
+            },
+            Expression::Member { target, name } => {
+                let call = match dotted_name(target) {
+                    Some(path) => {
+                        let function = self.name(&format!("{}.{}", path, name));
+                        let path = self.variable(&path);
+
+                        self.program.calls.push(DottedCall { function, path });
+
+                        Some(self.program.calls.len() as u32 - 1)
+                    },
+                    None => None,
+                };
+
+                let done = call.map(|call| self.emit(Instruction::CallDotted { call, arguments: count, done: 0 }));
+
+                self.expression(target)?;
+
+                let name = self.name(name);
+                self.emit(Instruction::Method { name, arguments: count, call });
+
+                if let Some(done) = done {
+                    self.patch(done);
+                }
+            },
+            _ => self.fail("expression is not callable"),
+        }
+
+        Ok(())
+    }
+
+    fn interpolation(&mut self, text: &str) -> Result<u32, Diagnostic> {
+        let mut parts = 0;
+        let mut literal = String::new();
+        let mut rest = text;
+
+        while let Some(start) = rest.find('@') {
+            let end = match rest[start + 1..].find('@') {
+                Some(end) => start + 1 + end,
+                None => break,
+            };
+
+            literal.push_str(&rest[..start]);
+
+            match crate::parse(&rest[start + 1..end]) {
+                Ok(Expression::Block { statements }) if statements.len() == 1 => {
+                    parts += self.literal(&mut literal);
+                    self.expression(&statements[0])?;
+                    parts += 1;
+                },
+                _ => literal.push_str(&rest[start..=end]),
+            }
+
+            rest = &rest[end + 1..];
+        }
+
+        literal.push_str(rest);
+
+        Ok(parts + self.literal(&mut literal))
+    }
+
+    fn literal(&mut self, text: &mut String) -> u32 {
+        if text.is_empty() {
+            return 0;
+        }
+
+        let constant = self.constant(RuntimeValue::String(std::mem::take(text)));
+        self.emit(Instruction::Constant(constant));
+
+        1
+    }
+}
+
+
+fn declarations(expression: &Expression) -> Vec<String> {
+    let mut names = vec![];
+    let mut pending = vec![expression];
+
+    while let Some(expression) = pending.pop() {
+        match expression {
+            Expression::Assignment { name, .. } => names.push(name.clone()),
+            Expression::ForRange { variable, body, .. } | Expression::ForEach { variable, body, .. } => {
+                names.push(variable.clone());
+                pending.push(body);
+            },
+            Expression::Condition { then_branch, else_branch, .. } => {
+                pending.extend(else_branch.as_deref());
+                pending.push(then_branch);
+            },
+            Expression::Block { statements } => pending.extend(statements.iter().rev()),
+            _ => {},
+        }
+    }
+
+    names
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compiled(source: &str) -> Program {
+        compile(&crate::parse(source).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_constant_pool_and_slots() {
+        let program = compiled("\
+var a = 5
+var b = \"hp\" + 5
+func Add(x, y)
+  var sum = x + y
+  return sum + a
+a = Add(a, 5)
+");
+
+        assert_eq!(program.constants, vec![RuntimeValue::Integer(5), RuntimeValue::from("hp")]);
+        assert_eq!(program.chunks.len(), 2);
+
+        let add = &program.chunks[1];
+
+        assert_eq!(program.name(add.name), "Add");
+        assert_eq!((add.parameters.clone(), add.locals), (vec![0, 1], 3));
+        assert!(add.code.contains(&Instruction::Declare(Slot::Local(2))));
+        assert!(add.code.iter().any(|instruction| matches!(
+            instruction, Instruction::Load(Variable { local: None, name }) if program.name(*name) == "a"
+        )));
+        assert_eq!(program.chunks[0].statements.len(), 4);
+    }
+
+    #[test]
+    fn test_interpolation_is_compiled() {
+        let program = compiled(">hp @hp@/@maxhp@ @not valid@");
+
+        let parts = program.chunks[0].code
+            .iter()
+            .find_map(|instruction| match instruction {
+                Instruction::Print(parts) => Some(*parts),
+                _ => None,
+            });
+
+        assert_eq!(parts, Some(5));
+        assert!(program.constants.contains(&RuntimeValue::from(" @not valid@")));
+    }
+
+    #[test]
+    fn test_object_scripts() {
+        let program = compiled("var c = new Counter\n>@c.count@");
+
+        assert!(program.chunks[0].code.contains(&Instruction::New(program.names.iter().position(|name| name == "Counter").unwrap() as u32)));
+
+        let script = crate::parse("var count = 0\nfunc Bump()\n  count++\n  return this").unwrap();
+        let (program, chunk) = compile_object(program, "Counter", &script).unwrap();
+        let code = &program.chunks[chunk as usize].code;
+
+        assert_eq!(program.name(program.chunks[chunk as usize].name), "new Counter");
+        assert!(code.iter().any(|instruction| matches!(instruction, Instruction::DefineMethod(_))));
+        assert!(code.iter().any(|instruction| matches!(instruction, Instruction::SetField(_))));
+        assert!(program.chunks.iter().any(|chunk| chunk.code.contains(&Instruction::This)));
+    }
+}
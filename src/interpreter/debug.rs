@@ -0,0 +1,436 @@
+use std::collections::BTreeSet;
+
+use crate::diagnostics::{Diagnostic, Span};
+use crate::parser::structs::Expression;
+
+use super::error::StackFrame;
+use super::value::RuntimeValue;
+use super::Interpreter;
+
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PauseReason {
+    Breakpoint,
+    Step,
+    Frame,
+}
+
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Pause {
+    pub reason: PauseReason,
+    pub script: Option<String>,
+    pub span: Span,
+    pub depth: usize,
+    pub frame: u64,
+}
+
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DebugCommand {
+    Continue,
+    StepIn,
+    StepOver,
+    StepOut,
+    NextFrame,
+    Stop,
+}
+
+
+pub trait Debugger {
+    fn paused(&mut self, interpreter: &mut Interpreter, pause: &Pause) -> DebugCommand;
+}
+
+
+#[derive(Debug, Clone, Copy)]
+enum Stepping {
+    In,
+    Over(usize),
+    Out(usize),
+}
+
+
+#[derive(Default)]
+pub(super) struct DebugState {
+    debugger: Option<Box<dyn Debugger>>,
+    breakpoints: BTreeSet<(Option<String>, usize)>,
+    watches: Vec<(String, Expression)>,
+    stepping: Option<Stepping>,
+    break_on_frame: bool,
+}
+
+
+impl Interpreter {
+    pub fn set_debugger(&mut self, debugger: Box<dyn Debugger>) {
+        self.debug.debugger = Some(debugger);
+    }
+
+    pub fn take_debugger(&mut self) -> Option<Box<dyn Debugger>> {
+        self.debug.debugger.take()
+    }
+
+    pub fn add_breakpoint(&mut self, script: Option<&str>, line: usize) {
+        self.debug.breakpoints.insert((script.map(str::to_string), line));
+    }
+
+    pub fn remove_breakpoint(&mut self, script: Option<&str>, line: usize) -> bool {
+        self.debug.breakpoints.remove(&(script.map(str::to_string), line))
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = (Option<&str>, usize)> + '_ {
+        self.debug.breakpoints.iter().map(|(script, line)| (script.as_deref(), *line))
+    }
+
+    pub fn break_on_next_frame(&mut self) {
+        self.debug.break_on_frame = true;
+    }
+
+    pub fn add_watch(&mut self, source: &str) -> Result<(), Diagnostic> {
+        let expression = parse_expression(source)?;
+
+        self.debug.watches.push((source.to_string(), expression));
+
+        Ok(())
+    }
+
+    pub fn remove_watch(&mut self, source: &str) -> bool {
+        let count = self.debug.watches.len();
+
+        self.debug.watches.retain(|(watch, _)| watch != source);
+
+        self.debug.watches.len() != count
+    }
+
+    pub fn watches(&mut self) -> Vec<(String, Result<RuntimeValue, Diagnostic>)> {
+        let watches = self.debug.watches.clone();
+
+        watches
+            .into_iter()
+            .map(|(source, expression)| {
+                let value = self.inspect_expression(&expression);
+                (source, value)
+            })
+            .collect()
+    }
+
+    pub fn inspect(&mut self, source: &str) -> Result<RuntimeValue, Diagnostic> {
+        let expression = parse_expression(source)?;
+
+        self.inspect_expression(&expression)
+    }
+
+    fn inspect_expression(&mut self, expression: &Expression) -> Result<RuntimeValue, Diagnostic> {
+        let (span, steps, fatal) = (self.span, self.steps, self.fatal);
+        let (calls, failure, random) = (self.calls.clone(), self.failure.take(), self.random.clone());
+        let debugger = self.debug.debugger.take();
+
+        let value = self.evaluate(expression);
+
+        self.debug.debugger = debugger;
+        self.span = span;
+        self.steps = steps;
+        self.fatal = fatal;
+        self.calls = calls;
+        self.failure = failure;
+        self.random = random;
+
+        value
+    }
+
+    pub fn locals(&self) -> Vec<(String, RuntimeValue)> {
+        sorted(self.environment.locals().into_iter().flatten())
+    }
+
+    pub fn globals(&self) -> Vec<(String, RuntimeValue)> {
+        sorted(self.environment.globals())
+    }
+
+    pub fn object_fields(&self) -> Vec<(String, RuntimeValue)> {
+        match &self.this {
+            Some(object) => sorted(&object.borrow().fields),
+            None => vec![],
+        }
+    }
+
+    pub fn call_stack(&self) -> &[StackFrame] {
+        &self.calls
+    }
+
+    pub fn current_script(&self) -> Option<&str> {
+        self.current_script.as_deref()
+    }
+
+    pub(super) fn debug_statement(&mut self, span: Span) -> Result<(), Diagnostic> {
+        if self.debug.debugger.is_none() {
+            return Ok(());
+        }
+
+        let depth = self.calls.len();
+
+        let reason = match self.debug.stepping {
+            Some(Stepping::In) => Some(PauseReason::Step),
+            Some(Stepping::Over(from)) if depth <= from => Some(PauseReason::Step),
+            Some(Stepping::Out(from)) if depth < from => Some(PauseReason::Step),
+            _ => None,
+        };
+
+        match reason {
+            Some(reason) => self.pause(reason, span),
+            None if self.at_breakpoint(span.line) => self.pause(PauseReason::Breakpoint, span),
+            None => Ok(()),
+        }
+    }
+
+    fn at_breakpoint(&self, line: usize) -> bool {
+        self.debug.breakpoints
+            .iter()
+            .any(|(script, breakpoint)| *breakpoint == line && script.as_deref() == self.current_script.as_deref())
+    }
+
+    pub(super) fn debug_frame(&mut self) -> Result<(), Diagnostic> {
+        if !self.debug.break_on_frame || self.debug.debugger.is_none() {
+            return Ok(());
+        }
+
+        self.debug.break_on_frame = false;
+        self.pause(PauseReason::Frame, Span::default())
+    }
+
+    fn pause(&mut self, reason: PauseReason, span: Span) -> Result<(), Diagnostic> {
+        let Some(mut debugger) = self.debug.debugger.take() else {
+            return Ok(());
+        };
+
+        let depth = self.calls.len();
+        let script = self.current_script.as_deref().map(str::to_string);
+        let pause = Pause { reason, script, span, depth, frame: self.frame };
+
+        let command = debugger.paused(self, &pause);
+
+        self.debug.debugger = Some(debugger);
+        self.span = span;
+
+        self.debug.stepping = match command {
+            DebugCommand::Continue => None,
+            DebugCommand::StepIn => Some(Stepping::In),
+            DebugCommand::StepOver => Some(Stepping::Over(depth)),
+            DebugCommand::StepOut => Some(Stepping::Out(depth)),
+            DebugCommand::NextFrame => {
+                self.debug.break_on_frame = true;
+                None
+            },
+            DebugCommand::Stop => {
+                self.fatal = true;
+                return Err(self.error("stopped by the debugger"));
+            },
+        };
+
+        Ok(())
+    }
+}
+
+
+fn parse_expression(source: &str) -> Result<Expression, Diagnostic> {
+    match crate::parse(source)? {
+        Expression::Block { mut statements } if statements.len() == 1 => Ok(statements.remove(0)),
+        _ => Err(Diagnostic::error(format!("`{}` is not a single expression", source))),
+    }
+}
+
+
+fn sorted<'a>(values: impl IntoIterator<Item = (&'a String, &'a RuntimeValue)>) -> Vec<(String, RuntimeValue)> {
+    let mut values = values
+        .into_iter()
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect::<Vec<_>>();
+
+    values.sort_by(|(left, _), (right, _)| left.cmp(right));
+    values
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+
+    use crate::interpreter::loader::MemoryLoader;
+
+    use super::*;
+
+    struct Scripted {
+        commands: VecDeque<DebugCommand>,
+        log: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Debugger for Scripted {
+        fn paused(&mut self, interpreter: &mut Interpreter, pause: &Pause) -> DebugCommand {
+            let locals = interpreter.locals()
+                .into_iter()
+                .chain(interpreter.object_fields())
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect::<Vec<_>>();
+
+            let watches = interpreter.watches()
+                .into_iter()
+                .map(|(source, value)| format!("{}={}", source, value.map_or_else(|error| error.message, |value| value.to_string())))
+                .collect::<Vec<_>>();
+
+            self.log.borrow_mut().push(format!(
+                "{:?} {}:{} [{}] [{}]", pause.reason, pause.span.line, pause.depth, locals.join(" "), watches.join(" ")
+            ));
+
+            self.commands.pop_front().unwrap_or(DebugCommand::Continue)
+        }
+    }
+
+    fn debugger(interpreter: &mut Interpreter, commands: &[DebugCommand]) -> Rc<RefCell<Vec<String>>> {
+        let log = Rc::new(RefCell::new(vec![]));
+
+        interpreter.set_debugger(Box::new(Scripted { commands: commands.iter().copied().collect(), log: log.clone() }));
+
+        log
+    }
+
+    #[test]
+    fn test_breakpoints_and_stepping() {
+        let program = crate::parse("\
+func Add(a, b)
+  var sum = a + b
+  return sum
+var total = 0
+total = Add(1, 2)
+total = Add(total, 3)
+>@total@
+").unwrap();
+
+        let mut interpreter = Interpreter::new();
+        let log = debugger(&mut interpreter, &[
+            DebugCommand::StepIn,
+            DebugCommand::StepOver,
+            DebugCommand::StepOut,
+            DebugCommand::StepOver,
+        ]);
+
+        interpreter.add_breakpoint(None, 5);
+        interpreter.add_watch("total * 10").unwrap();
+        interpreter.run(&program).unwrap();
+
+        assert_eq!(*log.borrow(), vec![
+            "Breakpoint 5:0 [] [total * 10=0]",
+            "Step 2:1 [a=1 b=2] [total * 10=0]",
+            "Step 3:1 [a=1 b=2 sum=3] [total * 10=0]",
+            "Step 6:0 [] [total * 10=30]",
+            "Step 7:0 [] [total * 10=60]",
+        ]);
+        assert_eq!(interpreter.take_output(), vec!["6"]);
+        assert_eq!(interpreter.globals(), vec![("total".to_string(), RuntimeValue::Integer(6))]);
+        assert!(interpreter.remove_watch("total * 10"));
+        assert!(interpreter.add_watch("var x = 1\nx").is_err());
+    }
+
+    #[test]
+    fn test_frame_stepping() {
+        let program = crate::parse("var n = 0\nn++\n>@n@").unwrap();
+
+        let mut interpreter = Interpreter::new();
+        let log = debugger(&mut interpreter, &[DebugCommand::NextFrame, DebugCommand::Stop]);
+
+        interpreter.break_on_next_frame();
+        interpreter.tick(&program).unwrap();
+
+        let error = interpreter.tick(&program).unwrap_err();
+
+        assert_eq!(error.message(), "stopped by the debugger");
+        assert_eq!(*log.borrow(), vec!["Frame 0:0 [] []", "Frame 0:0 [] []"]);
+        assert_eq!(interpreter.take_output(), Vec::<String>::new());
+        assert_eq!(interpreter.globals(), vec![("n".to_string(), RuntimeValue::Integer(1))]);
+    }
+
+    #[test]
+    fn test_object_inspection() {
+        let program = crate::parse("var c = new Counter\nc.Bump(5)").unwrap();
+
+        let mut interpreter = Interpreter::new();
+        let log = Rc::new(RefCell::new(vec![]));
+
+        interpreter.loader = Box::new(MemoryLoader::new().with_file("Counter", "var count = 1\nfunc Bump(by)\n  count += by\n"));
+        interpreter.add_breakpoint(Some("Counter"), 3);
+        interpreter.set_debugger(Box::new(Inspect(log.clone())));
+        interpreter.run(&program).unwrap();
+
+        assert_eq!(*log.borrow(), vec!["Counter.Bump@2 by=5 count=1"]);
+    }
+
+    #[test]
+    fn test_breakpoints_per_script() {
+        let program = crate::parse("var c = new Counter\nc.Bump(5)").unwrap();
+
+        let mut interpreter = Interpreter::new();
+        let log = Rc::new(RefCell::new(vec![]));
+
+        interpreter.loader = Box::new(MemoryLoader::new().with_file("Counter", "var count = 1\nfunc Bump(by)\n  count += by\n"));
+        interpreter.add_breakpoint(None, 2);
+        interpreter.set_debugger(Box::new(Locations(log.clone(), VecDeque::from([DebugCommand::StepIn]))));
+        interpreter.run(&program).unwrap();
+
+        assert_eq!(*log.borrow(), vec!["Breakpoint main:2", "Step Counter:3"]);
+        assert_eq!(interpreter.current_script(), None);
+    }
+
+    struct Locations(Rc<RefCell<Vec<String>>>, VecDeque<DebugCommand>);
+
+    impl Debugger for Locations {
+        fn paused(&mut self, _: &mut Interpreter, pause: &Pause) -> DebugCommand {
+            self.0.borrow_mut().push(format!("{:?} {}:{}", pause.reason, pause.script.as_deref().unwrap_or("main"), pause.span.line));
+            self.1.pop_front().unwrap_or(DebugCommand::Continue)
+        }
+    }
+
+    #[test]
+    fn test_watches_have_no_side_effects() {
+        let program = crate::parse("func Fail()\n  return 1 / 0\nvar roll = rng\nvar broken = 1 / 0").unwrap();
+
+        let expected = {
+            let mut interpreter = Interpreter::new();
+            let _ = interpreter.run(&program);
+            interpreter.globals()
+        };
+
+        let mut interpreter = Interpreter::new();
+        let log = debugger(&mut interpreter, &[]);
+
+        interpreter.add_breakpoint(None, 3);
+        interpreter.add_watch("Fail()").unwrap();
+        interpreter.add_watch("rng").unwrap();
+
+        let error = interpreter.run(&program).unwrap_err();
+
+        assert_eq!(log.borrow().len(), 1);
+        assert_eq!(error.diagnostic.span().unwrap().line, 4);
+        assert!(error.stack.is_empty());
+        assert_eq!(interpreter.globals(), expected);
+    }
+
+    struct Inspect(Rc<RefCell<Vec<String>>>);
+
+    impl Debugger for Inspect {
+        fn paused(&mut self, interpreter: &mut Interpreter, _: &Pause) -> DebugCommand {
+            let stack = interpreter.call_stack()
+                .iter()
+                .map(|frame| format!("{}@{}", frame.function, frame.span.line))
+                .collect::<Vec<_>>();
+
+            let values = interpreter.locals()
+                .into_iter()
+                .chain(interpreter.object_fields())
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect::<Vec<_>>();
+
+            self.0.borrow_mut().push(format!("{} {}", stack.join(" "), values.join(" ")));
+
+            DebugCommand::Continue
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use super::value::RuntimeValue;
+
+
+#[derive(Debug, Default)]
+pub struct Environment {
+    globals: HashMap<String, RuntimeValue>,
+    frames: Vec<HashMap<String, RuntimeValue>>,
+}
+
+
+impl Environment {
+    pub fn new() -> Self {
+        Environment::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&RuntimeValue> {
+        self.frames
+            .last()
+            .and_then(|frame| frame.get(name))
+            .or_else(|| self.globals.get(name))
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn declare(&mut self, name: &str, value: RuntimeValue) {
+        match self.frames.last_mut() {
+            Some(frame) => frame.insert(name.to_string(), value),
+            None => self.globals.insert(name.to_string(), value),
+        };
+    }
+
+    pub fn assign(&mut self, name: &str, value: RuntimeValue) -> bool {
+        if let Some(slot) = self.frames.last_mut().and_then(|frame| frame.get_mut(name)) {
+            *slot = value;
+            return true;
+        }
+
+        match self.globals.get_mut(name) {
+            Some(slot) => {
+                *slot = value;
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn push_frame(&mut self) {
+        self.frames.push(HashMap::new());
+    }
+
+    pub fn pop_frame(&mut self) {
+        self.frames.pop();
+    }
+
+    pub fn depth(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn globals(&self) -> &HashMap<String, RuntimeValue> {
+        &self.globals
+    }
+
+    pub fn locals(&self) -> Option<&HashMap<String, RuntimeValue>> {
+        self.frames.last()
+    }
+
+    pub fn clear(&mut self) {
+        self.globals.clear();
+        self.frames.clear();
+    }
+}
@@ -0,0 +1,73 @@
+use std::fmt;
+
+use crate::diagnostics::{Diagnostic, Span};
+
+
+const SHOWN_FRAMES: usize = 8;
+
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct StackFrame {
+    pub function: String,
+    pub span: Span,
+}
+
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RuntimeError {
+    pub diagnostic: Box<Diagnostic>,
+    pub stack: Vec<StackFrame>,
+}
+
+
+impl RuntimeError {
+    pub fn new(diagnostic: Diagnostic, stack: Vec<StackFrame>) -> Self {
+        RuntimeError { diagnostic: Box::new(diagnostic), stack }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.diagnostic.message
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let diagnostic = self.stack
+            .iter()
+            .rev()
+            .take(SHOWN_FRAMES)
+            .fold(*self.diagnostic.clone(), |diagnostic, frame| {
+                diagnostic.with_label(frame.span, format!("in `{}`, called here", frame.function))
+            });
+
+        match self.stack.len() > SHOWN_FRAMES {
+            true => diagnostic.with_note(format!("{} more calls are not shown", self.stack.len() - SHOWN_FRAMES)),
+            false => diagnostic,
+        }
+    }
+}
+
+
+impl From<RuntimeError> for Diagnostic {
+    fn from(error: RuntimeError) -> Self {
+        error.to_diagnostic()
+    }
+}
+
+
+impl fmt::Display for StackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "in `{}` called at {}:{}", self.function, self.span.line, self.span.column)
+    }
+}
+
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.diagnostic)?;
+
+        for frame in self.stack.iter().rev() {
+            write!(f, "\n  {}", frame)?;
+        }
+
+        Ok(())
+    }
+}
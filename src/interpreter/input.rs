@@ -0,0 +1,177 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::diagnostics::Diagnostic;
+
+
+pub const KEYS: [&str; 10] = [
+    "left", "right", "up", "down", "primary", "back", "ability1", "ability2", "bumpL", "bumpR",
+];
+
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum InputEvent {
+    Press(String),
+    Release(String),
+    Tap(String),
+}
+
+
+#[derive(Debug, Clone, Default)]
+pub struct Input {
+    frame: u64,
+    held: BTreeSet<String>,
+    previous: BTreeSet<String>,
+    timeline: BTreeMap<u64, Vec<InputEvent>>,
+}
+
+
+impl Input {
+    pub fn new() -> Self {
+        Input::default()
+    }
+
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn schedule(&mut self, frame: u64, event: InputEvent) {
+        self.timeline.entry(frame).or_default().push(event);
+    }
+
+    pub fn press(&mut self, frame: u64, key: &str) {
+        self.schedule(frame, InputEvent::Press(key.to_string()));
+    }
+
+    pub fn release(&mut self, frame: u64, key: &str) {
+        self.schedule(frame, InputEvent::Release(key.to_string()));
+    }
+
+    pub fn tap(&mut self, frame: u64, key: &str) {
+        self.schedule(frame, InputEvent::Tap(key.to_string()));
+    }
+
+    pub fn load_timeline(&mut self, source: &str) -> Result<(), Diagnostic> {
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let content = line.split('#').next().unwrap_or("").trim();
+
+            if content.is_empty() {
+                continue;
+            }
+
+            let error = |message: String| {
+                Diagnostic::error(message).with_note(format!("in line {} of the key timeline: `{}`", line_number, line))
+            };
+
+            let (frame, events) = content
+                .split_once(':')
+                .ok_or_else(|| error("expected `frame: events`".to_string()))?;
+
+            let frame = frame
+                .trim()
+                .parse()
+                .map_err(|_| error(format!("invalid frame `{}`", frame.trim())))?;
+
+            for event in events.split_whitespace() {
+                let (key, event) = match (event.strip_prefix('+'), event.strip_prefix('-')) {
+                    (Some(key), _) => (key, InputEvent::Press(key.to_string())),
+                    (_, Some(key)) => (key, InputEvent::Release(key.to_string())),
+                    _ => (event, InputEvent::Tap(event.to_string())),
+                };
+
+                if !KEYS.contains(&key) {
+                    return Err(error(format!("unknown key `{}`", key)).with_help(format!("keys are {}", KEYS.join(", "))));
+                }
+
+                self.schedule(frame, event);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn begin_frame(&mut self) {
+        for event in self.timeline.remove(&self.frame).unwrap_or_default() {
+            match event {
+                InputEvent::Press(key) => {
+                    self.held.insert(key);
+                },
+                InputEvent::Release(key) => {
+                    self.held.remove(&key);
+                },
+                InputEvent::Tap(key) => {
+                    self.held.insert(key.clone());
+                    self.release(self.frame + 1, &key);
+                },
+            }
+        }
+    }
+
+    pub fn end_frame(&mut self) {
+        self.previous = self.held.clone();
+        self.frame += 1;
+    }
+
+    pub fn is_held(&self, key: &str) -> bool {
+        self.held.contains(key)
+    }
+
+    pub fn is_down(&self, key: &str) -> bool {
+        self.held.contains(key) && !self.previous.contains(key)
+    }
+
+    pub fn is_up(&self, key: &str) -> bool {
+        !self.held.contains(key) && self.previous.contains(key)
+    }
+
+    pub fn key(&self) -> String {
+        if let Some(key) = self.held.difference(&self.previous).next() {
+            return format!("{}Begin", key);
+        }
+
+        match self.previous.difference(&self.held).next() {
+            Some(key) => format!("{}End", key),
+            None => String::new(),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeline() {
+        let mut input = Input::new();
+
+        input.load_timeline("# menu test\n0: +left\n2: -left ability1\n").unwrap();
+
+        let mut frames = vec![];
+
+        for _ in 0..4 {
+            input.begin_frame();
+            frames.push((input.key(), input.is_held("left"), input.is_down("ability1"), input.is_up("ability1")));
+            input.end_frame();
+        }
+
+        assert_eq!(frames, vec![
+            ("leftBegin".to_string(), true, false, false),
+            ("".to_string(), true, false, false),
+            ("ability1Begin".to_string(), false, true, false),
+            ("ability1End".to_string(), false, false, true),
+        ]);
+    }
+
+    #[test]
+    fn test_timeline_errors() {
+        let error = Input::new().load_timeline("0: +left\nsoon: up").unwrap_err();
+
+        assert_eq!(error.message, "invalid frame `soon`");
+        assert_eq!(error.notes, vec!["in line 2 of the key timeline: `soon: up`"]);
+
+        let error = Input::new().load_timeline("1: jump").unwrap_err();
+
+        assert_eq!(error.message, "unknown key `jump`");
+    }
+}
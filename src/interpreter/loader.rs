@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::diagnostics::Diagnostic;
+
+
+pub trait Loader {
+    fn load(&self, path: &str) -> Result<String, Diagnostic>;
+}
+
+
+#[derive(Debug, Clone, Default)]
+pub struct MemoryLoader {
+    pub files: HashMap<String, String>,
+}
+
+
+impl MemoryLoader {
+    pub fn new() -> Self {
+        MemoryLoader::default()
+    }
+
+    pub fn with_file(mut self, path: &str, source: &str) -> Self {
+        self.files.insert(path.to_string(), source.to_string());
+        self
+    }
+}
+
+
+impl Loader for MemoryLoader {
+    fn load(&self, path: &str) -> Result<String, Diagnostic> {
+        self.files
+            .get(path)
+            .cloned()
+            .ok_or_else(|| Diagnostic::error(format!("cannot find script `{}`", path)))
+    }
+}
+
+
+#[derive(Debug, Clone, Default)]
+pub struct FileLoader {
+    root: PathBuf,
+}
+
+
+impl FileLoader {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        FileLoader { root: root.into() }
+    }
+}
+
+
+impl Loader for FileLoader {
+    fn load(&self, path: &str) -> Result<String, Diagnostic> {
+        let file = self.root.join(format!("{}.txt", path));
+
+        std::fs::read_to_string(&file).map_err(|error| {
+            Diagnostic::error(format!("cannot find script `{}`", path))
+                .with_note(format!("tried to read {}: {}", file.display(), error))
+        })
+    }
+}
@@ -0,0 +1,83 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use crate::diagnostics::Diagnostic;
+
+use super::value::RuntimeValue;
+
+
+fn index_argument(method: &str, arguments: &[RuntimeValue], position: usize, length: usize) -> Result<usize, Diagnostic> {
+    match arguments.get(position) {
+        Some(RuntimeValue::Integer(index)) if *index >= 0 && (*index as usize) < length => Ok(*index as usize),
+        Some(RuntimeValue::Integer(index)) => Err(Diagnostic::error(format!(
+            "index {} is out of range for `{}` on an array of length {}", index, method, length
+        ))),
+        Some(value) => Err(Diagnostic::error(format!(
+            "`{}` expects an int index, found {}", method, value.type_name()
+        ))),
+        None => Err(Diagnostic::error(format!("`{}` expects an index argument", method))),
+    }
+}
+
+
+fn value_argument<'a>(method: &str, arguments: &'a [RuntimeValue], position: usize) -> Result<&'a RuntimeValue, Diagnostic> {
+    arguments
+        .get(position)
+        .ok_or_else(|| Diagnostic::error(format!("`{}` expects {} argument(s)", method, position + 1)))
+}
+
+
+pub fn call_array_method(
+    array: &Rc<RefCell<Vec<RuntimeValue>>>,
+    method: &str,
+    arguments: &[RuntimeValue],
+) -> Result<RuntimeValue, Diagnostic> {
+    match method {
+        "Count" => Ok(RuntimeValue::Integer(array.borrow().len() as i32)),
+        "Contains" => {
+            let value = value_argument(method, arguments, 0)?;
+            Ok(RuntimeValue::Boolean(array.borrow().iter().any(|item| item.loose_eq(value))))
+        },
+        "IndexOf" => {
+            let value = value_argument(method, arguments, 0)?;
+            let index = array.borrow().iter().position(|item| item.loose_eq(value));
+            Ok(RuntimeValue::Integer(index.map_or(-1, |index| index as i32)))
+        },
+        _ => call_mutating_method(&mut array.borrow_mut(), method, arguments),
+    }
+}
+
+
+fn call_mutating_method(values: &mut Vec<RuntimeValue>, method: &str, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    match method {
+        "Add" => {
+            values.push(value_argument(method, arguments, 0)?.clone());
+            Ok(RuntimeValue::Null)
+        },
+        "Insert" => {
+            let length = values.len() + 1;
+            let index = index_argument(method, arguments, 0, length)?;
+            values.insert(index, value_argument(method, arguments, 1)?.clone());
+            Ok(RuntimeValue::Null)
+        },
+        "RemoveAt" => {
+            let index = index_argument(method, arguments, 0, values.len())?;
+            Ok(values.remove(index))
+        },
+        "Emplace" => {
+            let index = index_argument(method, arguments, 0, values.len())?;
+            values[index] = value_argument(method, arguments, 1)?.clone();
+            Ok(RuntimeValue::Null)
+        },
+        "Clear" => {
+            values.clear();
+            Ok(RuntimeValue::Null)
+        },
+        "Sort" => {
+            values.sort_by(|left, right| left.compare(right).unwrap_or(Ordering::Equal));
+            Ok(RuntimeValue::Null)
+        },
+        _ => Err(Diagnostic::error(format!("array has no method `{}`", method))),
+    }
+}
@@ -0,0 +1,1798 @@
+pub mod value;
+pub mod error;
+pub mod environment;
+pub mod state;
+pub mod trace;
+pub mod screen;
+pub mod random;
+pub mod storage;
+pub mod ui;
+pub mod input;
+pub mod object;
+pub mod loader;
+pub mod debug;
+pub mod bytecode;
+pub mod vm;
+pub mod natives;
+mod methods;
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::diagnostics::{Diagnostic, Severity, Span};
+use crate::parser::structs::{BinaryOperator, Expression, UnaryOperator};
+
+use debug::DebugState;
+use environment::Environment;
+use error::{RuntimeError, StackFrame};
+use input::Input;
+use loader::{Loader, MemoryLoader};
+use object::{Object, ObjectRef};
+use methods::call_array_method;
+use random::Random;
+use screen::Screen;
+use storage::{MemoryStorage, Storage};
+use ui::{ComponentId, Ui};
+use state::GameState;
+use trace::{CommandEvent, GameCommand};
+use value::RuntimeValue;
+
+
+const MAX_CALL_DEPTH: usize = 200;
+
+// A script call takes 10-20 KB of native stack in debug builds, so calls
+// move to a fresh stack segment when the current one runs low.
+const STACK_RED_ZONE: usize = 256 * 1024;
+const STACK_SEGMENT: usize = 4 * 1024 * 1024;
+
+pub const DEFAULT_STEP_LIMIT: u64 = 100_000;
+
+
+pub type NativeFunction = fn(&mut Interpreter, &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic>;
+
+
+#[derive(Debug)]
+pub struct Function {
+    pub name: String,
+    pub parameters: Vec<String>,
+    pub body: Expression,
+    pub script: Option<Rc<str>>,
+}
+
+
+#[derive(Debug)]
+struct LoopCounter {
+    span: Span,
+    iterations: u64,
+}
+
+
+#[derive(Debug)]
+enum Flow {
+    Normal,
+    Return(RuntimeValue),
+    Break,
+    Continue,
+}
+
+
+pub struct Interpreter {
+    pub environment: Environment,
+    pub state: GameState,
+    pub screen: Screen,
+    pub random: Random,
+    pub storage: Box<dyn Storage>,
+    pub ui: Ui,
+    pub input: Input,
+    pub loader: Box<dyn Loader>,
+    functions: HashMap<String, Rc<Function>>,
+    natives: HashMap<&'static str, NativeFunction>,
+    constants: HashMap<&'static str, RuntimeValue>,
+    output: Vec<String>,
+    trace: Vec<CommandEvent>,
+    span: Span,
+    frame: u64,
+    location: Option<String>,
+    scripts: HashMap<String, Rc<Expression>>,
+    current_script: Option<Rc<str>>,
+    this: Option<ObjectRef>,
+    constructing: Option<usize>,
+    steps: u64,
+    step_limit: u64,
+    loops: Vec<LoopCounter>,
+    calls: Vec<StackFrame>,
+    failure: Option<Vec<StackFrame>>,
+    lenient: bool,
+    fatal: bool,
+    warnings: Vec<RuntimeError>,
+    debug: DebugState,
+}
+
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Interpreter::new()
+    }
+}
+
+
+impl Interpreter {
+    pub fn new() -> Self {
+        let mut interpreter = Interpreter {
+            environment: Environment::new(),
+            state: GameState::new(),
+            screen: Screen::default(),
+            random: Random::default(),
+            storage: Box::new(MemoryStorage::new()),
+            ui: Ui::new(),
+            input: Input::new(),
+            loader: Box::new(MemoryLoader::new()),
+            functions: HashMap::new(),
+            natives: HashMap::new(),
+            constants: HashMap::new(),
+            output: vec![],
+            trace: vec![],
+            span: Span::default(),
+            frame: 0,
+            location: None,
+            scripts: HashMap::new(),
+            current_script: None,
+            this: None,
+            constructing: None,
+            steps: 0,
+            step_limit: DEFAULT_STEP_LIMIT,
+            loops: vec![],
+            calls: vec![],
+            failure: None,
+            lenient: false,
+            fatal: false,
+            warnings: vec![],
+            debug: DebugState::default(),
+        };
+
+        natives::register_defaults(&mut interpreter);
+
+        interpreter
+    }
+
+    pub fn register_native(&mut self, name: &'static str, function: NativeFunction) {
+        self.natives.insert(name, function);
+    }
+
+    pub fn register_constant(&mut self, name: &'static str, value: RuntimeValue) {
+        self.constants.insert(name, value);
+    }
+
+    pub fn output(&self) -> &[String] {
+        &self.output
+    }
+
+    pub fn take_output(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.output)
+    }
+
+    pub fn trace(&self) -> &[CommandEvent] {
+        &self.trace
+    }
+
+    pub fn take_trace(&mut self) -> Vec<CommandEvent> {
+        std::mem::take(&mut self.trace)
+    }
+
+    pub fn warnings(&self) -> &[RuntimeError] {
+        &self.warnings
+    }
+
+    pub fn take_warnings(&mut self) -> Vec<RuntimeError> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    pub fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+
+    pub fn function(&self, name: &str) -> Option<&Rc<Function>> {
+        self.functions.get(name)
+    }
+
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    pub fn step_limit(&self) -> u64 {
+        self.step_limit
+    }
+
+    pub fn set_step_limit(&mut self, limit: u64) {
+        self.step_limit = limit;
+    }
+
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
+
+    pub fn set_location(&mut self, location: &str) {
+        self.state.loc.id = location.to_string();
+        self.sync_location();
+    }
+
+    fn sync_location(&mut self) {
+        if self.location.as_deref() == Some(self.state.loc.id.as_str()) {
+            return;
+        }
+
+        if self.location.is_some() {
+            self.reset();
+        }
+
+        self.location = Some(self.state.loc.id.clone());
+    }
+
+    pub fn reset(&mut self) {
+        self.environment.clear();
+        self.output.clear();
+        self.screen.clear();
+        self.ui.clear();
+        self.frame = 0;
+        self.state.time = 0;
+    }
+
+    pub fn run(&mut self, program: &Expression) -> Result<(), RuntimeError> {
+        self.eval(program)?;
+
+        Ok(())
+    }
+
+    pub fn eval(&mut self, expression: &Expression) -> Result<RuntimeValue, RuntimeError> {
+        self.steps = 0;
+        self.loops.clear();
+        self.fatal = false;
+        self.failure = None;
+
+        self.evaluate(expression).map_err(|diagnostic| self.fail(diagnostic))
+    }
+
+    pub fn tick(&mut self, program: &Expression) -> Result<(), RuntimeError> {
+        self.begin_tick();
+
+        let result = match self.debug_frame() {
+            Ok(()) => self.run(program),
+            Err(diagnostic) => Err(self.fail(diagnostic)),
+        };
+
+        self.end_tick();
+
+        result
+    }
+
+    fn begin_tick(&mut self) {
+        self.sync_location();
+        self.output.clear();
+        self.warnings.clear();
+        self.screen.clear();
+        self.input.begin_frame();
+    }
+
+    fn end_tick(&mut self) {
+        self.ui.render(&mut self.screen);
+        self.input.end_frame();
+        self.frame += 1;
+        self.state.advance();
+    }
+
+    pub fn click(&mut self, id: ComponentId) -> Result<(), RuntimeError> {
+        if let Some(callback) = self.ui.pressed_callback(id) {
+            self.call_function(&callback, &[]).map_err(|diagnostic| self.fail(diagnostic))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn click_at(&mut self, x: i32, y: i32) -> Result<bool, RuntimeError> {
+        match self.ui.button_at(x, y) {
+            Some(id) => {
+                self.click(id)?;
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    // `evaluate` and `execute` recurse once per nested expression, so arms with
+    // many temporaries live in their own methods to keep these frames small.
+    pub fn evaluate(&mut self, expression: &Expression) -> Result<RuntimeValue, Diagnostic> {
+        match expression {
+            Expression::Value(value) => Ok(RuntimeValue::from(value)),
+            Expression::Identifier(name) => Ok(self.lookup(name)),
+            Expression::Binary { left, .. } if matches!(**left, Expression::Binary { .. }) => self.evaluate_chain(expression),
+            Expression::Binary { left, operator, right } => self.evaluate_binary(left, operator, right),
+            Expression::Unary { operator, right } => self.evaluate_unary(operator, right),
+            Expression::Call { callee, arguments, span } => {
+                self.span = *span;
+                self.call(callee, arguments, *span)
+            },
+            Expression::Member { target, name } => self.member(target, name),
+            Expression::Nope => Ok(RuntimeValue::Null),
+            expression => self.evaluate_compound(expression),
+        }
+    }
+
+    fn evaluate_compound(&mut self, expression: &Expression) -> Result<RuntimeValue, Diagnostic> {
+        match expression {
+            Expression::Index { target, index } => {
+                let target = self.evaluate(target)?;
+                let index = self.evaluate(index)?;
+
+                self.index(&target, &index)
+            },
+            Expression::Array { elements } => {
+                let values = elements
+                    .iter()
+                    .map(|element| self.evaluate(element))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok(RuntimeValue::array(values))
+            },
+            Expression::New { path, span } => {
+                self.span = *span;
+                self.instantiate(path)
+            },
+            Expression::This => match &self.this {
+                Some(object) => Ok(RuntimeValue::Object(object.clone())),
+                None => Err(self.error("`this` can only be used inside an object")),
+            },
+            _ => {
+                self.execute(expression)?;
+                Ok(RuntimeValue::Null)
+            },
+        }
+    }
+
+    fn evaluate_binary(&mut self, left: &Expression, operator: &BinaryOperator, right: &Expression) -> Result<RuntimeValue, Diagnostic> {
+        match operator {
+            BinaryOperator::And | BinaryOperator::Or => self.evaluate_logical(left, operator, right),
+            operator => {
+                let left = self.evaluate(left)?;
+                let right = self.evaluate(right)?;
+
+                self.binary(operator, left, right)
+            },
+        }
+    }
+
+    // Chains nest to the left, so they are folded in a loop rather than by recursing down `left`.
+    fn evaluate_chain(&mut self, expression: &Expression) -> Result<RuntimeValue, Diagnostic> {
+        let (first, rest) = binary_chain(expression);
+        let mut value = self.evaluate(first)?;
+
+        for (operator, right) in rest {
+            value = match operator {
+                BinaryOperator::And if !value.is_truthy() => RuntimeValue::Boolean(false),
+                BinaryOperator::Or if value.is_truthy() => RuntimeValue::Boolean(true),
+                BinaryOperator::And | BinaryOperator::Or => RuntimeValue::Boolean(self.evaluate(right)?.is_truthy()),
+                operator => {
+                    let right = self.evaluate(right)?;
+                    self.binary(operator, value, right)?
+                },
+            };
+        }
+
+        Ok(value)
+    }
+
+    fn evaluate_logical(&mut self, left: &Expression, operator: &BinaryOperator, right: &Expression) -> Result<RuntimeValue, Diagnostic> {
+        let left = self.evaluate(left)?.is_truthy();
+
+        match (operator, left) {
+            (BinaryOperator::And, false) => Ok(RuntimeValue::Boolean(false)),
+            (BinaryOperator::Or, true) => Ok(RuntimeValue::Boolean(true)),
+            _ => Ok(RuntimeValue::Boolean(self.evaluate(right)?.is_truthy())),
+        }
+    }
+
+    fn evaluate_unary(&mut self, operator: &UnaryOperator, right: &Expression) -> Result<RuntimeValue, Diagnostic> {
+        match operator {
+            UnaryOperator::Negate => {
+                Ok(RuntimeValue::Boolean(!self.evaluate(right)?.is_truthy()))
+            },
+            UnaryOperator::Minus => {
+                match self.evaluate(right)? {
+                    RuntimeValue::Integer(value) => Ok(RuntimeValue::Integer(value.wrapping_neg())),
+                    RuntimeValue::Float(value) => Ok(RuntimeValue::Float(-value)),
+                    value => Err(self.error(format!("cannot negate {}", value.type_name()))),
+                }
+            },
+            operator => {
+                let delta = match operator {
+                    UnaryOperator::Increment => 1,
+                    _ => -1,
+                };
+
+                let value = match self.evaluate(right)? {
+                    RuntimeValue::Integer(value) => RuntimeValue::Integer(value.wrapping_add(delta)),
+                    RuntimeValue::Float(value) => RuntimeValue::Float(value + delta as f64),
+                    value => return Err(self.error(format!("cannot apply `{}` to {}", operator, value.type_name()))),
+                };
+
+                self.store(right, value.clone())?;
+
+                Ok(value)
+            },
+        }
+    }
+
+    fn execute(&mut self, expression: &Expression) -> Result<Flow, Diagnostic> {
+        self.enter(expression)?;
+
+        match expression {
+            Expression::Block { statements } => self.execute_block(statements),
+            Expression::Condition { condition, then_branch, else_branch, .. } => {
+                self.execute_condition(condition, then_branch, else_branch.as_deref())
+            },
+            Expression::Return { value: Some(value), .. } => self.evaluate(value).map(Flow::Return),
+            Expression::Return { value: None, .. } => Ok(Flow::Return(RuntimeValue::Null)),
+            Expression::Break => Ok(Flow::Break),
+            Expression::Continue => Ok(Flow::Continue),
+            _ => self.execute_statement(expression),
+        }
+    }
+
+    fn enter(&mut self, expression: &Expression) -> Result<(), Diagnostic> {
+        if let Some(span) = expression.span() {
+            self.span = span;
+        }
+
+        self.step()?;
+
+        match expression.span() {
+            Some(span) => self.debug_statement(span),
+            None => Ok(()),
+        }
+    }
+
+    fn execute_condition(&mut self, condition: &Expression, then_branch: &Expression, else_branch: Option<&Expression>) -> Result<Flow, Diagnostic> {
+        if self.evaluate(condition)?.is_truthy() {
+            self.execute(then_branch)
+        } else if let Some(else_branch) = else_branch {
+            self.execute(else_branch)
+        } else {
+            Ok(Flow::Normal)
+        }
+    }
+
+    fn execute_block(&mut self, statements: &[Expression]) -> Result<Flow, Diagnostic> {
+        for statement in statements {
+            if let Expression::Function { name, parameters, body } = statement {
+                self.define_function(name, parameters, body);
+            }
+        }
+
+        for statement in statements {
+            let flow = self.execute(statement);
+
+            match self.recover(flow)? {
+                Flow::Normal => {},
+                flow => return Ok(flow),
+            }
+        }
+
+        Ok(Flow::Normal)
+    }
+
+    fn recover(&mut self, flow: Result<Flow, Diagnostic>) -> Result<Flow, Diagnostic> {
+        match flow {
+            Err(diagnostic) if self.lenient && !self.fatal => {
+                self.warn(diagnostic);
+                Ok(Flow::Normal)
+            },
+            flow => flow,
+        }
+    }
+
+    fn execute_statement(&mut self, expression: &Expression) -> Result<Flow, Diagnostic> {
+        match expression {
+            Expression::Assignment { .. } | Expression::Reassignment { .. } => self.assign(expression),
+            Expression::Print { .. } | Expression::Command { .. } => self.emit(expression),
+            Expression::Function { name, parameters, body } => {
+                self.define_function(name, parameters, body);
+
+                Ok(Flow::Normal)
+            },
+            Expression::ForRange { .. } | Expression::ForEach { .. } => self.execute_loop(expression),
+            Expression::Nope => Ok(Flow::Normal),
+            _ => {
+                self.evaluate(expression)?;
+
+                Ok(Flow::Normal)
+            },
+        }
+    }
+
+    fn execute_loop(&mut self, expression: &Expression) -> Result<Flow, Diagnostic> {
+        match expression {
+            Expression::ForRange { variable, start, end, body, span } => {
+                let start = self.integer(start)?;
+                let end = self.integer(end)?;
+
+                self.loops.push(LoopCounter { span: *span, iterations: 0 });
+                let flow = self.for_range(variable, start, end, body, *span);
+                self.loops.pop();
+
+                flow
+            },
+            Expression::ForEach { variable, iterable, body, span } => {
+                let values = self.iterable(iterable)?;
+
+                self.loops.push(LoopCounter { span: *span, iterations: 0 });
+                let flow = self.for_each(variable, values, body, *span);
+                self.loops.pop();
+
+                flow
+            },
+            _ => Ok(Flow::Normal),
+        }
+    }
+
+    fn assign(&mut self, expression: &Expression) -> Result<Flow, Diagnostic> {
+        match expression {
+            Expression::Assignment { name, value, .. } if self.is_constructing() => {
+                let value = self.evaluate(value)?;
+
+                if let Some(object) = &self.this {
+                    object.borrow_mut().fields.insert(name.clone(), value);
+                }
+
+                Ok(Flow::Normal)
+            },
+            Expression::Assignment { name, .. } if self.environment.depth() == 0 && self.environment.contains(name) => {
+                Ok(Flow::Normal)
+            },
+            Expression::Assignment { name, value, .. } => {
+                let value = self.evaluate(value)?;
+                self.environment.declare(name, value);
+
+                Ok(Flow::Normal)
+            },
+            Expression::Reassignment { target, operator, value, .. } => {
+                let mut value = self.evaluate(value)?;
+
+                if let Some(operator) = operator {
+                    let current = self.evaluate(target)?;
+                    value = self.binary(operator, current, value)?;
+                }
+
+                self.store(target, value)?;
+
+                Ok(Flow::Normal)
+            },
+            _ => Ok(Flow::Normal),
+        }
+    }
+
+    fn emit(&mut self, expression: &Expression) -> Result<Flow, Diagnostic> {
+        match expression {
+            Expression::Print { text, .. } => {
+                let text = self.interpolate(text)?;
+
+                let shown = self.screen.render_print(&text).to_string();
+
+                self.output.push(shown);
+
+                Ok(Flow::Normal)
+            },
+            Expression::Command { name, arguments, span } if GameCommand::is_game_command(name) => {
+                let arguments = self.interpolate(arguments)?;
+                let command = GameCommand::parse(name, &arguments).map_err(|diagnostic| self.locate(diagnostic))?;
+
+                self.trace.push(CommandEvent { frame: self.frame, command, span: *span });
+
+                Ok(Flow::Normal)
+            },
+            _ => Ok(Flow::Normal),
+        }
+    }
+
+    fn for_range(&mut self, variable: &str, start: i32, end: i32, body: &Expression, span: Span) -> Result<Flow, Diagnostic> {
+        let step = if start <= end { 1 } else { -1 };
+
+        let mut current = start;
+
+        loop {
+            self.environment.declare(variable, RuntimeValue::Integer(current));
+
+            match self.iterate(body)? {
+                Flow::Break => break,
+                flow @ Flow::Return(_) => return Ok(flow),
+                Flow::Normal | Flow::Continue => {},
+            }
+
+            self.span = span;
+
+            if current == end {
+                break;
+            }
+
+            current += step;
+        }
+
+        Ok(Flow::Normal)
+    }
+
+    fn for_each(&mut self, variable: &str, values: Vec<RuntimeValue>, body: &Expression, span: Span) -> Result<Flow, Diagnostic> {
+        for value in values {
+            self.environment.declare(variable, value);
+
+            match self.iterate(body)? {
+                Flow::Break => break,
+                flow @ Flow::Return(_) => return Ok(flow),
+                Flow::Normal | Flow::Continue => {},
+            }
+
+            self.span = span;
+        }
+
+        Ok(Flow::Normal)
+    }
+
+    fn iterable(&mut self, iterable: &Expression) -> Result<Vec<RuntimeValue>, Diagnostic> {
+        match self.evaluate(iterable)? {
+            RuntimeValue::Array(values) => Ok(values.borrow().clone()),
+            value => Err(self.error(format!("cannot iterate over {}", value.type_name()))),
+        }
+    }
+
+    fn iterate(&mut self, body: &Expression) -> Result<Flow, Diagnostic> {
+        if let Some(counter) = self.loops.last_mut() {
+            counter.iterations += 1;
+        }
+
+        self.execute(body)
+    }
+
+    fn step(&mut self) -> Result<(), Diagnostic> {
+        self.steps += 1;
+
+        if self.steps <= self.step_limit {
+            return Ok(());
+        }
+
+        self.fatal = true;
+
+        let diagnostic = Diagnostic::error(format!("frame exceeded the step limit of {}", self.step_limit));
+
+        let diagnostic = match self.loops.iter().max_by_key(|counter| counter.iterations) {
+            Some(counter) => diagnostic
+                .with_primary_label(counter.span, format!("this loop ran {} times", counter.iterations))
+                .with_label(self.span, "stopped here"),
+            None => diagnostic.with_primary_label(self.span, "stopped here"),
+        };
+
+        Err(diagnostic.with_help("spread the work over several frames or raise the limit"))
+    }
+
+    fn define_function(&mut self, name: &str, parameters: &[String], body: &Expression) {
+        let function = Function {
+            name: name.to_string(),
+            parameters: parameters.to_vec(),
+            body: body.clone(),
+            script: self.current_script.clone(),
+        };
+
+        match &self.this {
+            Some(object) if self.is_constructing() => {
+                object.borrow_mut().functions.insert(name.to_string(), Rc::new(function));
+            },
+            _ => {
+                self.functions.insert(name.to_string(), Rc::new(function));
+            },
+        }
+    }
+
+    fn is_constructing(&self) -> bool {
+        self.constructing == Some(self.environment.depth())
+    }
+
+    fn script(&mut self, path: &str) -> Result<Rc<Expression>, Diagnostic> {
+        if let Some(program) = self.scripts.get(path) {
+            return Ok(program.clone());
+        }
+
+        let source = self.loader.load(path).map_err(|diagnostic| self.locate(diagnostic))?;
+
+        let program = crate::parse(&source).map_err(|diagnostic| {
+            self.error(format!("cannot parse `{}`", path))
+                .with_note(format!("{} at line {}", diagnostic.message, diagnostic.labels.first().map_or(0, |label| label.span.line)))
+        })?;
+
+        let program = Rc::new(program);
+
+        self.scripts.insert(path.to_string(), program.clone());
+
+        Ok(program)
+    }
+
+    fn instantiate(&mut self, path: &str) -> Result<RuntimeValue, Diagnostic> {
+        let program = self.script(path)?;
+
+        if self.environment.depth() >= MAX_CALL_DEPTH {
+            self.fatal = true;
+            return Err(self.error(format!("call stack overflow in `new {}`", path)));
+        }
+
+        let object = Object::new(path);
+        let span = self.span;
+
+        self.environment.push_frame();
+
+        let this = self.this.replace(object.clone());
+        let constructing = self.constructing.replace(self.environment.depth());
+        let script = self.current_script.replace(Rc::from(path));
+
+        let flow = self.within(format!("new {}", path), |interpreter| interpreter.execute(&program));
+
+        self.this = this;
+        self.constructing = constructing;
+        self.current_script = script;
+        self.environment.pop_frame();
+        self.span = span;
+
+        flow?;
+
+        Ok(RuntimeValue::Object(object))
+    }
+
+    fn within<T>(&mut self, function: String, run: impl FnOnce(&mut Interpreter) -> Result<T, Diagnostic>) -> Result<T, Diagnostic> {
+        self.calls.push(StackFrame { function, span: self.span });
+
+        let result = stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, || run(self));
+
+        if result.is_err() && self.failure.is_none() {
+            self.failure = Some(self.calls.clone());
+        }
+
+        self.calls.pop();
+
+        result
+    }
+
+    fn fail(&mut self, diagnostic: Diagnostic) -> RuntimeError {
+        let stack = self.failure.take().unwrap_or_else(|| self.calls.clone());
+
+        RuntimeError::new(diagnostic, stack)
+    }
+
+    fn warn(&mut self, diagnostic: Diagnostic) {
+        let mut warning = self.fail(diagnostic);
+
+        warning.diagnostic.severity = Severity::Warning;
+
+        self.warnings.push(warning);
+    }
+
+    fn error(&self, message: impl Into<String>) -> Diagnostic {
+        Diagnostic::error(message).with_primary_label(self.span, "while evaluating this")
+    }
+
+    fn locate(&self, diagnostic: Diagnostic) -> Diagnostic {
+        match diagnostic.labels.is_empty() {
+            true => diagnostic.with_primary_label(self.span, "while evaluating this"),
+            false => diagnostic,
+        }
+    }
+
+    fn resolve(&mut self, name: &str) -> Option<RuntimeValue> {
+        if let Some(value) = self.environment.locals().and_then(|locals| locals.get(name)) {
+            return Some(value.clone());
+        }
+
+        if let Some(value) = self.this.as_ref().and_then(|object| object.borrow().field(name)) {
+            return Some(value);
+        }
+
+        if let Some(value) = self.environment.globals().get(name) {
+            return Some(value.clone());
+        }
+
+        match name {
+            "rng" => Some(RuntimeValue::Integer(self.random.rng())),
+            "rngf" => Some(RuntimeValue::Float(self.random.rngf())),
+            "key" => Some(RuntimeValue::String(self.input.key())),
+            _ => self.constants.get(name).cloned().or_else(|| self.state.get(name)),
+        }
+    }
+
+    fn lookup(&mut self, name: &str) -> RuntimeValue {
+        self.resolve(name).unwrap_or_else(|| RuntimeValue::String(name.to_string()))
+    }
+
+    fn integer(&mut self, expression: &Expression) -> Result<i32, Diagnostic> {
+        let value = self.evaluate(expression)?;
+
+        self.to_integer(value)
+    }
+
+    fn to_integer(&self, value: RuntimeValue) -> Result<i32, Diagnostic> {
+        match value {
+            RuntimeValue::Integer(value) => Ok(value),
+            RuntimeValue::Float(value) => Ok(value as i32),
+            value => Err(self.error(format!("expected int, found {}", value.type_name()))),
+        }
+    }
+
+    fn store(&mut self, target: &Expression, value: RuntimeValue) -> Result<(), Diagnostic> {
+        match target {
+            Expression::Identifier(name) => {
+                let local = self.environment.locals().is_some_and(|locals| locals.contains_key(name));
+
+                if let Some(object) = self.this.as_ref().filter(|_| !local) {
+                    if let Some(slot) = object.borrow_mut().fields.get_mut(name) {
+                        *slot = value;
+                        return Ok(());
+                    }
+                }
+
+                if !self.environment.assign(name, value) {
+                    return Err(
+                        self.error(format!("cannot assign to undeclared variable `{}`", name))
+                            .with_help(format!("declare it first with `var {}`", name))
+                    );
+                }
+
+                Ok(())
+            },
+            Expression::Index { target, index } => {
+                let target = self.evaluate(target)?;
+                let index = self.evaluate(index)?;
+
+                self.set_index(&target, &index, value)
+            },
+            Expression::Member { target: object, name: field } => {
+                if let Some(name) = dotted_name(target).filter(|name| self.environment.contains(name)) {
+                    self.environment.assign(&name, value);
+                    return Ok(());
+                }
+
+                let object = self.evaluate(object)?;
+
+                self.set_member(object, field, value)
+            },
+            _ => Err(self.error("invalid assignment target")),
+        }
+    }
+
+    fn set_index(&self, target: &RuntimeValue, index: &RuntimeValue, value: RuntimeValue) -> Result<(), Diagnostic> {
+        match (target, index) {
+            (RuntimeValue::Array(values), RuntimeValue::Integer(position)) => {
+                let mut values = values.borrow_mut();
+                let length = values.len();
+
+                match values.get_mut(*position as usize) {
+                    Some(slot) if *position >= 0 => {
+                        *slot = value;
+                        Ok(())
+                    },
+                    _ => Err(self.error(format!("index {} is out of range for an array of length {}", position, length))),
+                }
+            },
+            _ => Err(self.error(format!("cannot index {} with {}", target.type_name(), index.type_name()))),
+        }
+    }
+
+    fn set_member(&mut self, object: RuntimeValue, field: &str, value: RuntimeValue) -> Result<(), Diagnostic> {
+        match object {
+            RuntimeValue::Component(id) => self.ui.set(id, field, value).map_err(|diagnostic| self.locate(diagnostic)),
+            RuntimeValue::Object(object) => {
+                object.borrow_mut().fields.insert(field.to_string(), value);
+                Ok(())
+            },
+            _ => Err(self.error("cannot assign to this member")),
+        }
+    }
+
+    fn member(&mut self, target: &Expression, name: &str) -> Result<RuntimeValue, Diagnostic> {
+        if let Some(path) = dotted_name(target) {
+            let full = format!("{}.{}", path, name);
+
+            if let Some(value) = self.resolve(&full) {
+                return Ok(value);
+            }
+
+            if !self.environment.contains(&path) {
+                let value = self.evaluate(target)?;
+
+                return self.member_of(value, name, Some(full));
+            }
+        }
+
+        let value = self.evaluate(target)?;
+
+        self.member_of(value, name, None)
+    }
+
+    fn member_of(&self, value: RuntimeValue, name: &str, fallback: Option<String>) -> Result<RuntimeValue, Diagnostic> {
+        match value {
+            RuntimeValue::Component(id) => self.ui.get(id, name).map_err(|diagnostic| self.locate(diagnostic)),
+            RuntimeValue::Object(object) => self.field(&object, name),
+            value => match fallback {
+                Some(full) => Ok(RuntimeValue::String(full)),
+                None => Err(self.error(format!("{} has no field `{}`", value.type_name(), name))),
+            },
+        }
+    }
+
+    fn field(&self, object: &ObjectRef, name: &str) -> Result<RuntimeValue, Diagnostic> {
+        let object = object.borrow();
+
+        object.field(name).ok_or_else(|| self.error(format!("`{}` has no field `{}`", object.name, name)))
+    }
+
+    fn index(&self, target: &RuntimeValue, index: &RuntimeValue) -> Result<RuntimeValue, Diagnostic> {
+        match (target, index) {
+            (RuntimeValue::Array(values), RuntimeValue::Integer(position)) => {
+                let values = values.borrow();
+
+                match values.get(*position as usize) {
+                    Some(value) if *position >= 0 => Ok(value.clone()),
+                    _ => Err(self.error(format!("index {} is out of range for an array of length {}", position, values.len()))),
+                }
+            },
+            _ => Err(self.error(format!("cannot index {} with {}", target.type_name(), index.type_name()))),
+        }
+    }
+
+    fn call(&mut self, callee: &Expression, arguments: &[Expression], span: Span) -> Result<RuntimeValue, Diagnostic> {
+        let arguments = arguments
+            .iter()
+            .map(|argument| self.evaluate(argument))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.span = span;
+
+        match callee {
+            Expression::Identifier(name) => self.call_function(name, &arguments),
+            Expression::Member { target, name } => {
+                let function = dotted_name(target)
+                    .filter(|path| !self.environment.contains(path))
+                    .map(|path| format!("{}.{}", path, name));
+
+                if let Some(function) = &function {
+                    if self.functions.contains_key(function) || self.natives.contains_key(function.as_str()) {
+                        return self.call_function(function, &arguments);
+                    }
+                }
+
+                let target = self.evaluate(target)?;
+
+                self.call_member(target, name, &arguments, function)
+            },
+            _ => Err(self.error("expression is not callable")),
+        }
+    }
+
+    fn call_member(&mut self, target: RuntimeValue, name: &str, arguments: &[RuntimeValue], function: Option<String>) -> Result<RuntimeValue, Diagnostic> {
+        match target {
+            RuntimeValue::Array(values) => {
+                call_array_method(&values, name, arguments).map_err(|diagnostic| self.locate(diagnostic))
+            },
+            RuntimeValue::Component(id) => {
+                self.ui.call(id, name, arguments).map_err(|diagnostic| self.locate(diagnostic))
+            },
+            RuntimeValue::Object(object) => self.call_method(&object, name, arguments),
+            value => match function {
+                Some(function) => self.call_function(&function, arguments),
+                None => Err(self.error(format!("{} has no method `{}`", value.type_name(), name))),
+            },
+        }
+    }
+
+    pub fn call_function(&mut self, name: &str, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+        if let Some(object) = self.this.clone() {
+            if let Some(function) = object.borrow().function(name) {
+                return self.call_user_function(&function, arguments, Some(object.clone()));
+            }
+        }
+
+        if let Some(function) = self.functions.get(name).cloned() {
+            return self.call_user_function(&function, arguments, None);
+        }
+
+        if let Some(native) = self.natives.get(name).copied() {
+            return native(self, arguments).map_err(|diagnostic| self.locate(diagnostic));
+        }
+
+        Err(self.error(format!("unknown function `{}`", name)))
+    }
+
+    pub fn call_method(&mut self, object: &ObjectRef, name: &str, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+        let function = object.borrow().function(name);
+
+        match function {
+            Some(function) => self.call_user_function(&function, arguments, Some(object.clone())),
+            None => Err(self.error(format!("`{}` has no method `{}`", object.borrow().name, name))),
+        }
+    }
+
+    fn call_user_function(&mut self, function: &Function, arguments: &[RuntimeValue], this: Option<ObjectRef>) -> Result<RuntimeValue, Diagnostic> {
+        if arguments.len() > function.parameters.len() {
+            return Err(self.error(format!(
+                "function `{}` takes {} argument(s) but {} were supplied",
+                function.name,
+                function.parameters.len(),
+                arguments.len(),
+            )));
+        }
+
+        if self.environment.depth() >= MAX_CALL_DEPTH {
+            self.fatal = true;
+            return Err(self.error(format!("call stack overflow in `{}`", function.name)));
+        }
+
+        let span = self.span;
+
+        self.environment.push_frame();
+
+        for (index, parameter) in function.parameters.iter().enumerate() {
+            let value = arguments.get(index).cloned().unwrap_or_default();
+            self.environment.declare(parameter, value);
+        }
+
+        let name = match &this {
+            Some(object) => format!("{}.{}", object.borrow().name, function.name),
+            None => function.name.clone(),
+        };
+
+        let this = std::mem::replace(&mut self.this, this);
+        let script = std::mem::replace(&mut self.current_script, function.script.clone());
+        let flow = self.within(name, |interpreter| interpreter.execute(&function.body));
+
+        self.this = this;
+        self.current_script = script;
+        self.environment.pop_frame();
+        self.span = span;
+
+        match flow? {
+            Flow::Return(value) => Ok(value),
+            _ => Ok(RuntimeValue::Null),
+        }
+    }
+
+    fn binary(&self, operator: &BinaryOperator, left: RuntimeValue, right: RuntimeValue) -> Result<RuntimeValue, Diagnostic> {
+        use RuntimeValue::{Float, Integer};
+
+        let mismatch = || self.error(format!(
+            "cannot apply `{}` to {} and {}", operator, left.type_name(), right.type_name()
+        ));
+
+        match operator {
+            BinaryOperator::Equal => Ok(left.loose_eq(&right).into()),
+            BinaryOperator::NotEqual => Ok((!left.loose_eq(&right)).into()),
+            BinaryOperator::And => Ok((left.is_truthy() && right.is_truthy()).into()),
+            BinaryOperator::Or => Ok((left.is_truthy() || right.is_truthy()).into()),
+            BinaryOperator::Greater | BinaryOperator::Less | BinaryOperator::GreaterEqual | BinaryOperator::LessEqual => {
+                let ordering = left.compare(&right).ok_or_else(mismatch)?;
+
+                Ok(match operator {
+                    BinaryOperator::Greater => ordering.is_gt(),
+                    BinaryOperator::Less => ordering.is_lt(),
+                    BinaryOperator::GreaterEqual => ordering.is_ge(),
+                    _ => ordering.is_le(),
+                }.into())
+            },
+            BinaryOperator::Add if matches!(left, RuntimeValue::String(_)) || matches!(right, RuntimeValue::String(_)) => {
+                Ok(RuntimeValue::String(format!("{}{}", left, right)))
+            },
+            _ => match (&left, &right) {
+                (Integer(a), Integer(b)) => {
+                    let (a, b) = (*a, *b);
+
+                    if b == 0 && matches!(operator, BinaryOperator::Divide | BinaryOperator::Modulo) {
+                        return Err(self.error("division by zero"));
+                    }
+
+                    Ok(Integer(match operator {
+                        BinaryOperator::Add => a.wrapping_add(b),
+                        BinaryOperator::Subtract => a.wrapping_sub(b),
+                        BinaryOperator::Multiply => a.wrapping_mul(b),
+                        BinaryOperator::Divide => a.wrapping_div(b),
+                        _ => a.wrapping_rem(b),
+                    }))
+                },
+                _ => {
+                    let (a, b) = match (left.as_float(), right.as_float()) {
+                        (Some(a), Some(b)) => (a, b),
+                        _ => return Err(mismatch()),
+                    };
+
+                    Ok(Float(match operator {
+                        BinaryOperator::Add => a + b,
+                        BinaryOperator::Subtract => a - b,
+                        BinaryOperator::Multiply => a * b,
+                        BinaryOperator::Divide => a / b,
+                        _ => a % b,
+                    }))
+                },
+            },
+        }
+    }
+
+    pub fn interpolate(&mut self, text: &str) -> Result<String, Diagnostic> {
+        let mut result = String::new();
+        let mut rest = text;
+
+        while let Some(start) = rest.find('@') {
+            let end = match rest[start + 1..].find('@') {
+                Some(end) => start + 1 + end,
+                None => break,
+            };
+
+            result.push_str(&rest[..start]);
+
+            match crate::parse(&rest[start + 1..end]) {
+                Ok(Expression::Block { statements }) if statements.len() == 1 => {
+                    let value = self.evaluate(&statements[0])?;
+                    result.push_str(&value.to_string());
+                },
+                _ => result.push_str(&rest[start..=end]),
+            }
+
+            rest = &rest[end + 1..];
+        }
+
+        result.push_str(rest);
+
+        Ok(result)
+    }
+}
+
+
+// Splits `a + b - c` into `a` and the operators with their right operands, in evaluation order.
+fn binary_chain(expression: &Expression) -> (&Expression, Vec<(&BinaryOperator, &Expression)>) {
+    let mut rest = vec![];
+    let mut first = expression;
+
+    while let Expression::Binary { left, operator, right } = first {
+        rest.push((operator, right.as_ref()));
+        first = left;
+    }
+
+    rest.reverse();
+
+    (first, rest)
+}
+
+
+fn dotted_name(expression: &Expression) -> Option<String> {
+    match expression {
+        Expression::Identifier(name) => Some(name.clone()),
+        Expression::Member { target, name } => Some(format!("{}.{}", dotted_name(target)?, name)),
+        _ => None,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(source: &str) -> Interpreter {
+        let mut interpreter = Interpreter::new();
+        interpreter.run(&crate::parse(source).unwrap()).unwrap();
+        interpreter
+    }
+
+    fn global(interpreter: &Interpreter, name: &str) -> RuntimeValue {
+        interpreter.environment.get(name).cloned().unwrap()
+    }
+
+    fn run_error(source: &str) -> Diagnostic {
+        *Interpreter::new().run(&crate::parse(source).unwrap()).unwrap_err().diagnostic
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let interpreter = run("var a = 7 / 2\nvar b = 7 / 2.0\nvar c = 1 + 2 * 3 - 4\nvar d = -7 % 3\nvar e = 2147483647 + 1");
+
+        assert_eq!(global(&interpreter, "a"), RuntimeValue::Integer(3));
+        assert_eq!(global(&interpreter, "b"), RuntimeValue::Float(3.5));
+        assert_eq!(global(&interpreter, "c"), RuntimeValue::Integer(3));
+        assert_eq!(global(&interpreter, "d"), RuntimeValue::Integer(-1));
+        assert_eq!(global(&interpreter, "e"), RuntimeValue::Integer(i32::MIN));
+    }
+
+    #[test]
+    fn test_strings_and_comparisons() {
+        let interpreter = run("var s = \"hp: \" + 10 + 0.5\nvar a = 1 = 1.0\nvar b = \"x\" ! \"y\" & 2 >= 3\nvar c = loc = caves");
+
+        assert_eq!(global(&interpreter, "s"), RuntimeValue::from("hp: 100.5"));
+        assert_eq!(global(&interpreter, "a"), RuntimeValue::Boolean(true));
+        assert_eq!(global(&interpreter, "b"), RuntimeValue::Boolean(false));
+        assert_eq!(global(&interpreter, "c"), RuntimeValue::Boolean(false));
+    }
+
+    #[test]
+    fn test_conditions_and_loops() {
+        let source = "\
+var total = 0
+for i = 1..10
+  ?i % 2 = 0
+    continue
+  :? i > 7
+    break
+  total += i
+var down = []
+for i = 3..1
+  down.Add(i)
+";
+
+        let interpreter = run(source);
+
+        assert_eq!(global(&interpreter, "total"), RuntimeValue::Integer(16));
+        assert_eq!(global(&interpreter, "down").to_string(), "[3, 2, 1]");
+    }
+
+    #[test]
+    fn test_functions() {
+        let source = "\
+var calls = 0
+func Fib(n)
+  calls++
+  ?n < 2
+    return n
+  return Fib(n - 1) + Fib(n - 2)
+var result = Fib(10)
+";
+
+        let interpreter = run(source);
+
+        assert_eq!(global(&interpreter, "result"), RuntimeValue::Integer(55));
+        assert_eq!(global(&interpreter, "calls"), RuntimeValue::Integer(177));
+        assert!(interpreter.environment.get("n").is_none());
+    }
+
+    #[test]
+    fn test_arrays() {
+        let source = "\
+var a = [3, 1, 2]
+a.Add(5)
+a.Sort()
+a[0] = a[0] * 10
+var sum = 0
+for v : a
+  sum += v
+var found = a.IndexOf(5)
+var removed = a.RemoveAt(1)
+";
+
+        let interpreter = run(source);
+
+        assert_eq!(global(&interpreter, "a").to_string(), "[10, 3, 5]");
+        assert_eq!(global(&interpreter, "sum"), RuntimeValue::Integer(20));
+        assert_eq!(global(&interpreter, "found"), RuntimeValue::Integer(3));
+        assert_eq!(global(&interpreter, "removed"), RuntimeValue::Integer(2));
+    }
+
+    #[test]
+    fn test_long_chains() {
+        let source = format!("var a = {}\nvar b = {}", vec!["1"; 5000].join(" - "), vec!["a < 0"; 5000].join(" | "));
+        let interpreter = run(&source);
+
+        assert_eq!(global(&interpreter, "a"), RuntimeValue::Integer(-4998));
+        assert_eq!(global(&interpreter, "b"), RuntimeValue::Boolean(true));
+    }
+
+    #[test]
+    fn test_self_containing_arrays() {
+        let source = "\
+var a = [1]
+a.Add(a)
+var b = [1]
+b.Add(b)
+var found = a.Contains([1])
+var same = a = b
+var index = a.IndexOf(b)
+>@a@
+";
+
+        let mut interpreter = run(source);
+
+        assert_eq!(global(&interpreter, "found"), RuntimeValue::Boolean(false));
+        assert_eq!(global(&interpreter, "same"), RuntimeValue::Boolean(true));
+        assert_eq!(global(&interpreter, "index"), RuntimeValue::Integer(1));
+        assert_eq!(interpreter.take_output(), vec!["[1, [...]]"]);
+    }
+
+    #[test]
+    fn test_print_interpolation() {
+        let mut interpreter = run("var name = \"Bob\"\nvar a = [1, 2]\n>Hi @name@, @a[1] + 1@ and @@\n>plain");
+
+        assert_eq!(interpreter.take_output(), vec!["Hi Bob, 3 and @@", "plain"]);
+    }
+
+    #[test]
+    fn test_tick_keeps_variables() {
+        let program = crate::parse("var count = 0\nvar armed = false\ncount++\n?time % 3 = 0\n  armed = true\n>@time@ @count@ @armed@").unwrap();
+        let mut interpreter = Interpreter::new();
+        let mut frames = vec![];
+
+        for _ in 0..4 {
+            interpreter.tick(&program).unwrap();
+            frames.extend(interpreter.take_output());
+        }
+
+        assert_eq!(frames, vec!["0 1 true", "1 2 true", "2 3 true", "3 4 true"]);
+        assert_eq!(interpreter.frame(), 4);
+
+        let program = crate::parse("var seen = 0\n?time > 0 & time % 2 = 0\n  seen++").unwrap();
+        let mut interpreter = Interpreter::new();
+
+        for _ in 0..5 {
+            interpreter.tick(&program).unwrap();
+        }
+
+        assert_eq!(interpreter.environment.get("seen"), Some(&RuntimeValue::Integer(2)));
+    }
+
+    #[test]
+    fn test_location_change_resets() {
+        let program = crate::parse("var visits = 0\nvisits++").unwrap();
+        let mut interpreter = Interpreter::new();
+
+        interpreter.set_location("rocky");
+        interpreter.tick(&program).unwrap();
+        interpreter.tick(&program).unwrap();
+        interpreter.set_location("rocky");
+        interpreter.tick(&program).unwrap();
+
+        assert_eq!(interpreter.environment.get("visits"), Some(&RuntimeValue::Integer(3)));
+        assert_eq!(interpreter.frame(), 3);
+
+        interpreter.set_location("caves");
+
+        assert_eq!(interpreter.frame(), 0);
+        assert!(interpreter.environment.get("visits").is_none());
+
+        interpreter.tick(&program).unwrap();
+
+        assert_eq!(interpreter.environment.get("visits"), Some(&RuntimeValue::Integer(1)));
+        assert_eq!(interpreter.location(), Some("caves"));
+    }
+
+    #[test]
+    fn test_game_state() {
+        let program = crate::parse("\
+?loc = caves & loc.stars >= 5
+  >deep
+?foe.distance < 10 & foe = boo
+  >@foe@ close, @foe.count@ left
+?hp < maxhp / 2
+  >low @hp@/@maxhp@
+").unwrap();
+
+        let mut interpreter = Interpreter::new();
+
+        interpreter.state.hp = 20;
+        interpreter.state.maxhp = 30;
+        interpreter.state.loc.id = "caves".to_string();
+        interpreter.state.loc.stars = 5;
+        interpreter.state.foe.id = "boo".to_string();
+        interpreter.state.foe.distance = 30;
+        interpreter.tick(&program).unwrap();
+
+        assert_eq!(interpreter.take_output(), vec!["deep"]);
+
+        interpreter.state.hp = 10;
+        interpreter.state.foe.distance = 4;
+        interpreter.state.foe.count = 3;
+        interpreter.tick(&program).unwrap();
+
+        assert_eq!(interpreter.take_output(), vec!["deep", "boo close, 3 left", "low 10/30"]);
+        assert_eq!(interpreter.state.time, 2);
+        assert_eq!(interpreter.state.totaltime, 2);
+
+        interpreter.state.loc.id = "rocky".to_string();
+        interpreter.tick(&program).unwrap();
+
+        assert_eq!(interpreter.frame(), 1);
+        assert_eq!(interpreter.state.time, 1);
+        assert_eq!(interpreter.state.totaltime, 3);
+    }
+
+    #[test]
+    fn test_command_trace() {
+        let program = crate::parse("\
+var weapon = \"hammer\"
+?foe = boss & time >= 2
+  equip @weapon@
+  activate potion
+:
+  equipL sword
+?time = 3
+  brew stone + wood
+  loadout 2
+").unwrap();
+
+        let mut interpreter = Interpreter::new();
+
+        for frame in 0..4 {
+            if frame == 2 {
+                interpreter.state.foe.id = "boss".to_string();
+            }
+
+            interpreter.tick(&program).unwrap();
+        }
+
+        let trace = interpreter.take_trace();
+
+        assert_eq!(
+            trace.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "0: equipL sword",
+                "1: equipL sword",
+                "2: equip hammer",
+                "2: activate potion",
+                "3: equip hammer",
+                "3: activate potion",
+                "3: brew stone + wood",
+                "3: loadout 2",
+            ]
+        );
+        assert_eq!(trace[2].command, GameCommand::Equip { hand: None, item: "hammer".to_string() });
+        assert_eq!(trace[2].span, Span::new(3, 3, 14));
+        assert!(interpreter.trace().is_empty());
+
+        let diagnostic = Interpreter::new().run(&crate::parse("loadout x").unwrap()).unwrap_err().diagnostic;
+
+        assert_eq!(diagnostic.message, "`loadout` expects a slot number, found `x`");
+        assert_eq!(diagnostic.span(), Some(Span::new(1, 1, 9)));
+    }
+
+    #[test]
+    fn test_screen() {
+        let program = crate::parse("\
+draw.Box(0, 0, 12, 3, \"#00ff00\")
+>h1,1,#ff0000,hp @hp@
+>status
+>o-1,0,@
+?time > 0
+  draw.Clear()
+  >c0,0,cleared
+").unwrap();
+
+        let mut interpreter = Interpreter::new();
+
+        interpreter.screen = Screen::new(16, 6);
+        interpreter.screen.player = (3, 4);
+        interpreter.state.hp = 9;
+        interpreter.tick(&program).unwrap();
+
+        assert_eq!(interpreter.take_output(), vec!["hp 9", "status", "@"]);
+        assert_eq!(interpreter.screen.to_plain(), "status-----+\n|hp 9      |\n+----------+\n\n  @\n");
+        assert_eq!(interpreter.screen.cell(1, 1).unwrap().color, screen::Color::parse("red"));
+        assert_eq!(interpreter.screen.cell(0, 2).unwrap().color, screen::Color::parse("green"));
+
+        interpreter.tick(&program).unwrap();
+
+        assert_eq!(interpreter.screen.to_plain(), "\n\n\n        cleared\n");
+
+        let diagnostic = Interpreter::new().run(&crate::parse("draw.Bg(0, 0, \"#nope\")").unwrap()).unwrap_err().diagnostic;
+
+        assert_eq!(diagnostic.message, "invalid color `#nope`");
+    }
+
+    #[test]
+    fn test_random() {
+        let program = crate::parse("var rolls = []\nrolls.Add(rng)\nrolls.Add(rngf)\n?rng < 5000\n  >low").unwrap();
+        let mut first = Interpreter::new();
+        let mut second = Interpreter::new();
+
+        first.random.seed(99);
+        second.random.seed(99);
+        first.tick(&program).unwrap();
+        second.tick(&program).unwrap();
+
+        assert_eq!(first.environment.get("rolls").unwrap().to_string(), second.environment.get("rolls").unwrap().to_string());
+
+        let mut interpreter = Interpreter::new();
+
+        interpreter.random.script_rng([4999, 1234, 5000, 7000]);
+        interpreter.random.script_rngf([0.25]);
+        interpreter.tick(&program).unwrap();
+
+        assert_eq!(interpreter.take_output(), vec!["low"]);
+
+        interpreter.tick(&program).unwrap();
+
+        assert!(interpreter.output().is_empty());
+        assert!(interpreter.environment.get("rolls").unwrap().to_string().starts_with("[4999, 0.25, 5000, 0."));
+    }
+
+    #[test]
+    fn test_ui() {
+        let program = crate::parse("\
+var clicks = 0
+var hud
+var button
+func OnPress()
+  clicks++
+  hud.visible = !hud.visible
+?time = 0
+  hud = ui.AddPanel()
+  hud.anchor = top_left
+  hud.dock = top_left
+  hud.w = 14
+  hud.h = 5
+  hud.style = 1
+  hud.margin = 1
+  var label = hud.AddText(\"hp\")
+  label.dock = top_left
+  label.anchor = top_left
+  label.text = \"hp \" + hp
+  button = ui.root.AddButton(\"Go\")
+  button.dock = bottom_right
+  button.anchor = bottom_right
+  button.w = 6
+  button.SetPressed(OnPress)
+").unwrap();
+
+        let mut interpreter = Interpreter::new();
+
+        interpreter.screen = Screen::new(16, 8);
+        interpreter.state.hp = 7;
+        interpreter.tick(&program).unwrap();
+
+        assert_eq!(interpreter.screen.to_plain(), "+------------+\n|hp 7        |\n|            |\n|            |\n+------------+\n          +----+\n          | Go |\n          +----+\n");
+
+        let button = match interpreter.environment.get("button") {
+            Some(RuntimeValue::Component(id)) => *id,
+            value => panic!("expected a component, found {:?}", value),
+        };
+
+        interpreter.click(button).unwrap();
+
+        assert!(!interpreter.click_at(0, 7).unwrap());
+        assert!(interpreter.click_at(12, 6).unwrap());
+        assert_eq!(interpreter.environment.get("clicks"), Some(&RuntimeValue::Integer(2)));
+
+        interpreter.click(button).unwrap();
+        interpreter.tick(&program).unwrap();
+
+        assert_eq!(interpreter.screen.to_plain(), "\n\n\n\n\n          +----+\n          | Go |\n          +----+\n");
+
+        let diagnostic = Interpreter::new().run(&crate::parse("var p = ui.AddPanel()\np.text = 1").unwrap()).unwrap_err().diagnostic;
+
+        assert_eq!(diagnostic.message, "Panel has no property `text`");
+        assert_eq!(diagnostic.span(), Some(Span::new(2, 1, 10)));
+    }
+
+    #[test]
+    fn test_key_input() {
+        let program = crate::parse("\
+var cursor = 0
+?key = upBegin
+  cursor--
+?key.GetKeyDown(down)
+  cursor++
+?key.GetKey(primary)
+  >hold @cursor@
+?key.GetKeyUp(\"primary\")
+  >release
+").unwrap();
+
+        let mut interpreter = Interpreter::new();
+
+        interpreter.input.tap(0, "down");
+        interpreter.input.tap(2, "down");
+        interpreter.input.press(3, "primary");
+        interpreter.input.tap(4, "up");
+        interpreter.input.release(5, "primary");
+
+        let mut output = vec![];
+
+        for _ in 0..6 {
+            interpreter.tick(&program).unwrap();
+            output.extend(interpreter.take_output());
+        }
+
+        assert_eq!(output, vec!["hold 2", "hold 1", "release"]);
+        assert_eq!(interpreter.environment.get("cursor"), Some(&RuntimeValue::Integer(1)));
+    }
+
+    #[test]
+    fn test_function_locals_reinitialize() {
+        let program = crate::parse("func Next()\n  var step = 0\n  step++\n  return step\nvar last = 0\nlast = Next()").unwrap();
+        let mut interpreter = Interpreter::new();
+
+        interpreter.tick(&program).unwrap();
+        interpreter.tick(&program).unwrap();
+
+        assert_eq!(interpreter.environment.get("last"), Some(&RuntimeValue::Integer(1)));
+    }
+
+    #[test]
+    fn test_runtime_errors() {
+        let diagnostic = run_error("var a = 1\nvar b = a / 0");
+
+        assert_eq!(diagnostic.message, "division by zero");
+        assert_eq!(diagnostic.span(), Some(Span::new(2, 1, 13)));
+
+        let diagnostic = run_error("x = 1");
+
+        assert_eq!(diagnostic.message, "cannot assign to undeclared variable `x`");
+
+        let diagnostic = run_error("var a = []\nvar b = a[2]");
+
+        assert_eq!(diagnostic.message, "index 2 is out of range for an array of length 0");
+
+        let diagnostic = run_error("var a = [1] - 1");
+
+        assert_eq!(diagnostic.message, "cannot apply `-` to array and int");
+    }
+
+    #[test]
+    fn test_objects() {
+        let counter = "\
+var count = 0
+var step = 1
+func Increment()
+  count += step
+  return Describe()
+func Describe()
+  return name() + \" \" + this.count
+func name()
+  return \"counter\"
+";
+        let program = crate::parse("\
+var a = new Components/Counter
+var b = new Components/Counter
+a.step = 5
+a.Increment()
+var last = \"\"
+last = b.Increment()
+var same = a = b
+>@a.count@ @b.count@ @last@ @typeof(a)@ @same@
+").unwrap();
+
+        let mut interpreter = Interpreter::new();
+
+        interpreter.loader = Box::new(MemoryLoader::new().with_file("Components/Counter", counter));
+        interpreter.tick(&program).unwrap();
+        interpreter.tick(&program).unwrap();
+
+        assert_eq!(interpreter.take_output(), vec!["10 2 counter 2 object false"]);
+        assert_eq!(global(&interpreter, "a").to_string(), "<object Components/Counter>");
+        assert!(interpreter.function("Increment").is_none());
+    }
+
+    #[test]
+    fn test_object_errors() {
+        let mut interpreter = Interpreter::new();
+
+        interpreter.loader = Box::new(MemoryLoader::new().with_file("Broken", "var x = (1").with_file("Empty", ""));
+
+        let diagnostic = interpreter.run(&crate::parse("var o = new Missing").unwrap()).unwrap_err().diagnostic;
+        assert_eq!(diagnostic.message, "cannot find script `Missing`");
+
+        let diagnostic = interpreter.run(&crate::parse("var o = new Broken").unwrap()).unwrap_err().diagnostic;
+        assert_eq!(diagnostic.message, "cannot parse `Broken`");
+
+        let diagnostic = interpreter.run(&crate::parse("var o = new Empty\no.Go()").unwrap()).unwrap_err().diagnostic;
+        assert_eq!(diagnostic.message, "`Empty` has no method `Go`");
+
+        assert_eq!(run_error("var o = this").message, "`this` can only be used inside an object");
+    }
+
+    #[test]
+    fn test_step_limit() {
+        let program = crate::parse("var total = 0\nfor i = 1..3\n  for j = 1..1000\n    total += j\n>done").unwrap();
+        let mut interpreter = Interpreter::new();
+
+        interpreter.set_step_limit(500);
+
+        let diagnostic = interpreter.tick(&program).unwrap_err().diagnostic;
+
+        assert_eq!(diagnostic.message, "frame exceeded the step limit of 500");
+        assert_eq!(diagnostic.labels[0].span.line, 3);
+        assert!(diagnostic.labels[0].message.starts_with("this loop ran"));
+        assert_eq!(interpreter.steps(), 501);
+
+        interpreter.set_step_limit(DEFAULT_STEP_LIMIT);
+        interpreter.tick(&program).unwrap();
+
+        assert_eq!(interpreter.take_output(), vec!["done"]);
+        assert!(interpreter.steps() > 3000 && interpreter.steps() < interpreter.step_limit());
+        assert!(interpreter.loops.is_empty());
+    }
+
+    #[test]
+    fn test_runtime_error_stack() {
+        let program = crate::parse("\
+func Divide(a, b)
+  return a / b
+func Average(total, count)
+  return Divide(total, count)
+var x = Average(10, 0)
+").unwrap();
+
+        let error = Interpreter::new().run(&program).unwrap_err();
+
+        assert_eq!(error.message(), "division by zero");
+        assert_eq!(error.diagnostic.span().unwrap().line, 2);
+        assert_eq!(
+            error.stack.iter().map(|frame| (frame.function.as_str(), frame.span.line)).collect::<Vec<_>>(),
+            vec![("Average", 5), ("Divide", 4)]
+        );
+
+        let diagnostic = Diagnostic::from(error);
+
+        assert_eq!(diagnostic.labels[1].message, "in `Divide`, called here");
+        assert_eq!(diagnostic.labels[2].message, "in `Average`, called here");
+
+        assert_eq!(run_error("var n = null\nvar y = n.hp").message, "null has no field `hp`");
+        assert_eq!(run_error("Missing()").message, "unknown function `Missing`");
+        assert_eq!(run_error("var a = [1]\nvar b = a[3]").message, "index 3 is out of range for an array of length 1");
+        assert!(Interpreter::new().run(&program).unwrap_err().to_string().ends_with("in `Divide` called at 4:10\n  in `Average` called at 5:9"));
+    }
+
+    #[test]
+    fn test_call_depth_limit() {
+        let source = "\
+func F(n)
+  ?n > 0
+    return 1 + F(n - 1)
+  return 0
+func Walk(n)
+  var total = 0
+  for i = 0 .. 1
+    ?n > 0 & i = 0
+      total = total + Walk(n - 1) + 1
+  return total
+>@F(199)@ @Walk(199)@
+";
+
+        let mut interpreter = run(source);
+
+        assert_eq!(interpreter.take_output(), vec!["199 199"]);
+
+        let error = Interpreter::new().run(&crate::parse("func G(n)\n  return G(n + 1)\nG(0)").unwrap()).unwrap_err();
+
+        assert_eq!(error.message(), "call stack overflow in `G`");
+        assert_eq!(error.stack.len(), MAX_CALL_DEPTH);
+    }
+
+    #[test]
+    fn test_lenient_mode() {
+        let program = crate::parse("\
+func Broken()
+  var a = [1]
+  >@a[5]@
+  >still in Broken
+var x = 1 / 0
+Broken()
+>done
+").unwrap();
+
+        let mut interpreter = Interpreter::new();
+
+        interpreter.set_lenient(true);
+        interpreter.tick(&program).unwrap();
+
+        assert_eq!(interpreter.take_output(), vec!["still in Broken", "done"]);
+
+        let warnings = interpreter.take_warnings();
+
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].message(), "division by zero");
+        assert_eq!(warnings[0].diagnostic.severity, Severity::Warning);
+        assert_eq!(warnings[1].stack.len(), 1);
+
+        interpreter.set_step_limit(20);
+
+        let error = interpreter.tick(&crate::parse("for i = 1..100\n  1 / 0").unwrap()).unwrap_err();
+
+        assert_eq!(error.message(), "frame exceeded the step limit of 20");
+    }
+}
@@ -0,0 +1,144 @@
+use crate::diagnostics::Diagnostic;
+use crate::interpreter::value::{parse_float, parse_int, RuntimeValue};
+use crate::interpreter::Interpreter;
+
+use super::{expect_count, int_argument, string_argument};
+
+
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.register_native("int.Parse", int_parse);
+    interpreter.register_native("int.Clamp", int_clamp);
+    interpreter.register_native("int.Min", |_, arguments| int_pair("int.Min", arguments, i32::min));
+    interpreter.register_native("int.Max", |_, arguments| int_pair("int.Max", arguments, i32::max));
+    interpreter.register_native("float.Parse", float_parse);
+    interpreter.register_native("typeof", type_of);
+    interpreter.register_native("toint", to_int);
+    interpreter.register_native("tofloat", to_float);
+    interpreter.register_native("tostring", to_string);
+}
+
+
+fn int_parse(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("int.Parse", arguments, 1, 1)?;
+
+    let text = string_argument("int.Parse", arguments, 0)?;
+
+    Ok(RuntimeValue::Integer(parse_int(text).unwrap_or(0)))
+}
+
+
+fn float_parse(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("float.Parse", arguments, 1, 1)?;
+
+    let text = string_argument("float.Parse", arguments, 0)?;
+
+    Ok(RuntimeValue::Float(parse_float(text).unwrap_or(0.0)))
+}
+
+
+fn int_clamp(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("int.Clamp", arguments, 3, 3)?;
+
+    let value = int_argument("int.Clamp", arguments, 0)?;
+    let min = int_argument("int.Clamp", arguments, 1)?;
+    let max = int_argument("int.Clamp", arguments, 2)?;
+
+    Ok(RuntimeValue::Integer(value.max(min).min(max)))
+}
+
+
+fn int_pair(name: &str, arguments: &[RuntimeValue], function: fn(i32, i32) -> i32) -> Result<RuntimeValue, Diagnostic> {
+    expect_count(name, arguments, 2, 2)?;
+
+    Ok(RuntimeValue::Integer(function(int_argument(name, arguments, 0)?, int_argument(name, arguments, 1)?)))
+}
+
+
+fn type_of(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("typeof", arguments, 1, 1)?;
+
+    Ok(RuntimeValue::from(arguments[0].type_name()))
+}
+
+
+fn to_int(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("toint", arguments, 1, 1)?;
+
+    let value = match &arguments[0] {
+        RuntimeValue::Integer(value) => *value,
+        RuntimeValue::Float(value) => *value as i32,
+        RuntimeValue::Boolean(value) => *value as i32,
+        RuntimeValue::String(value) => parse_int(value).unwrap_or(0),
+        RuntimeValue::Null | RuntimeValue::Array(_) | RuntimeValue::Component(_) | RuntimeValue::Object(_) => 0,
+    };
+
+    Ok(RuntimeValue::Integer(value))
+}
+
+
+fn to_float(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("tofloat", arguments, 1, 1)?;
+
+    let value = match &arguments[0] {
+        RuntimeValue::Integer(value) => *value as f64,
+        RuntimeValue::Float(value) => *value,
+        RuntimeValue::Boolean(value) => *value as i32 as f64,
+        RuntimeValue::String(value) => parse_float(value).unwrap_or(0.0),
+        RuntimeValue::Null | RuntimeValue::Array(_) | RuntimeValue::Component(_) | RuntimeValue::Object(_) => 0.0,
+    };
+
+    Ok(RuntimeValue::Float(value))
+}
+
+
+fn to_string(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("tostring", arguments, 1, 1)?;
+
+    Ok(RuntimeValue::String(arguments[0].to_string()))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::natives::evaluate;
+    use crate::interpreter::value::RuntimeValue;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(evaluate("int.Parse(\" -42 \")"), RuntimeValue::Integer(-42));
+        assert_eq!(evaluate("int.Parse(\"+7\")"), RuntimeValue::Integer(7));
+        assert_eq!(evaluate("int.Parse(\"2147483647\")"), RuntimeValue::Integer(i32::MAX));
+        assert_eq!(evaluate("int.Parse(\"2147483648\")"), RuntimeValue::Integer(0));
+        assert_eq!(evaluate("int.Parse(\"1.5\")"), RuntimeValue::Integer(0));
+        assert_eq!(evaluate("int.Parse(\"1_000\")"), RuntimeValue::Integer(0));
+        assert_eq!(evaluate("int.Parse(\"\")"), RuntimeValue::Integer(0));
+        assert_eq!(evaluate("float.Parse(\"2.5e1\")"), RuntimeValue::Float(25.0));
+        assert_eq!(evaluate("float.Parse(\"inf\")"), RuntimeValue::Float(0.0));
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(evaluate("int.Clamp(15, 0, 10)"), RuntimeValue::Integer(10));
+        assert_eq!(evaluate("int.Max(3, -4)"), RuntimeValue::Integer(3));
+        assert_eq!(evaluate("typeof(1.5)"), RuntimeValue::from("float"));
+        assert_eq!(evaluate("typeof([])"), RuntimeValue::from("array"));
+        assert_eq!(evaluate("toint(-2.9)"), RuntimeValue::Integer(-2));
+        assert_eq!(evaluate("toint(\"12\") + 1"), RuntimeValue::Integer(13));
+        assert_eq!(evaluate("toint(true)"), RuntimeValue::Integer(1));
+        assert_eq!(evaluate("tofloat(\"x\")"), RuntimeValue::Float(0.0));
+        assert_eq!(evaluate("tostring(5) + 5"), RuntimeValue::from("55"));
+    }
+
+    #[test]
+    fn test_literal_range() {
+        assert_eq!(evaluate("-2147483648"), RuntimeValue::Integer(i32::MIN));
+        assert_eq!(evaluate("int.Parse(\"-2147483648\")"), RuntimeValue::Integer(i32::MIN));
+        assert_eq!(evaluate("3000000000.0"), RuntimeValue::Float(3000000000.0));
+
+        for source in ["3000000000", "-2147483649", "a + 2147483648"] {
+            let diagnostic = crate::parse(source).unwrap_err();
+
+            assert!(diagnostic.message.ends_with("is out of range"), "{}", source);
+        }
+    }
+}
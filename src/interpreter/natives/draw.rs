@@ -0,0 +1,71 @@
+use crate::diagnostics::Diagnostic;
+use crate::interpreter::screen::Color;
+use crate::interpreter::value::RuntimeValue;
+use crate::interpreter::Interpreter;
+
+use super::{expect_count, int_argument, string_argument};
+
+
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.register_native("draw.Bg", background);
+    interpreter.register_native("draw.Box", draw_box);
+    interpreter.register_native("draw.Clear", clear);
+}
+
+
+fn color_argument(name: &str, arguments: &[RuntimeValue], index: usize) -> Result<Color, Diagnostic> {
+    let color = string_argument(name, arguments, index)?;
+
+    Color::parse(color).ok_or_else(|| Diagnostic::error(format!("invalid color `{}`", color)))
+}
+
+
+fn background(interpreter: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    const NAME: &str = "draw.Bg";
+
+    expect_count(NAME, arguments, 3, 5)?;
+
+    let x = int_argument(NAME, arguments, 0)?;
+    let y = int_argument(NAME, arguments, 1)?;
+    let color = color_argument(NAME, arguments, 2)?;
+
+    let (width, height) = match arguments.len() {
+        5 => (int_argument(NAME, arguments, 3)?, int_argument(NAME, arguments, 4)?),
+        4 => (int_argument(NAME, arguments, 3)?, 1),
+        _ => (1, 1),
+    };
+
+    interpreter.screen.fill_background(x, y, width, height, Some(color));
+
+    Ok(RuntimeValue::Null)
+}
+
+
+fn draw_box(interpreter: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    const NAME: &str = "draw.Box";
+
+    expect_count(NAME, arguments, 4, 6)?;
+
+    let x = int_argument(NAME, arguments, 0)?;
+    let y = int_argument(NAME, arguments, 1)?;
+    let width = int_argument(NAME, arguments, 2)?;
+    let height = int_argument(NAME, arguments, 3)?;
+
+    let color = match arguments.len() {
+        4 => None,
+        _ => Some(color_argument(NAME, arguments, 4)?),
+    };
+
+    interpreter.screen.draw_box(x, y, width, height, color);
+
+    Ok(RuntimeValue::Null)
+}
+
+
+fn clear(interpreter: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("draw.Clear", arguments, 0, 0)?;
+
+    interpreter.screen.clear();
+
+    Ok(RuntimeValue::Null)
+}
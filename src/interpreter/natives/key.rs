@@ -0,0 +1,20 @@
+use crate::diagnostics::Diagnostic;
+use crate::interpreter::input::Input;
+use crate::interpreter::value::RuntimeValue;
+use crate::interpreter::Interpreter;
+
+use super::{expect_count, string_argument};
+
+
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.register_native("key.GetKey", |interpreter, arguments| check("key.GetKey", interpreter, arguments, Input::is_held));
+    interpreter.register_native("key.GetKeyDown", |interpreter, arguments| check("key.GetKeyDown", interpreter, arguments, Input::is_down));
+    interpreter.register_native("key.GetKeyUp", |interpreter, arguments| check("key.GetKeyUp", interpreter, arguments, Input::is_up));
+}
+
+
+fn check(name: &str, interpreter: &mut Interpreter, arguments: &[RuntimeValue], state: fn(&Input, &str) -> bool) -> Result<RuntimeValue, Diagnostic> {
+    expect_count(name, arguments, 1, 1)?;
+
+    Ok(RuntimeValue::Boolean(state(&interpreter.input, string_argument(name, arguments, 0)?)))
+}
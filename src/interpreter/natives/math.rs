@@ -0,0 +1,218 @@
+use std::f64::consts;
+
+use crate::diagnostics::Diagnostic;
+use crate::interpreter::value::RuntimeValue;
+use crate::interpreter::Interpreter;
+
+use super::{expect_count, float_argument};
+
+
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.register_constant("math.pi", RuntimeValue::Float(consts::PI));
+    interpreter.register_constant("math.e", RuntimeValue::Float(consts::E));
+
+    interpreter.register_native("math.Abs", abs);
+    interpreter.register_native("math.Sign", sign);
+    interpreter.register_native("math.Min", min);
+    interpreter.register_native("math.Max", max);
+    interpreter.register_native("math.Clamp", clamp);
+    interpreter.register_native("math.Floor", |_, arguments| float_function("math.Floor", arguments, f64::floor));
+    interpreter.register_native("math.Ceil", |_, arguments| float_function("math.Ceil", arguments, f64::ceil));
+    interpreter.register_native("math.Round", |_, arguments| float_function("math.Round", arguments, f64::round_ties_even));
+    interpreter.register_native("math.FloorToInt", |_, arguments| int_function("math.FloorToInt", arguments, f64::floor));
+    interpreter.register_native("math.CeilToInt", |_, arguments| int_function("math.CeilToInt", arguments, f64::ceil));
+    interpreter.register_native("math.RoundToInt", |_, arguments| int_function("math.RoundToInt", arguments, f64::round_ties_even));
+    interpreter.register_native("math.Sqrt", |_, arguments| float_function("math.Sqrt", arguments, f64::sqrt));
+    interpreter.register_native("math.Exp", |_, arguments| float_function("math.Exp", arguments, f64::exp));
+    interpreter.register_native("math.Sin", |_, arguments| float_function("math.Sin", arguments, f64::sin));
+    interpreter.register_native("math.Cos", |_, arguments| float_function("math.Cos", arguments, f64::cos));
+    interpreter.register_native("math.Tan", |_, arguments| float_function("math.Tan", arguments, f64::tan));
+    interpreter.register_native("math.Asin", |_, arguments| float_function("math.Asin", arguments, f64::asin));
+    interpreter.register_native("math.Acos", |_, arguments| float_function("math.Acos", arguments, f64::acos));
+    interpreter.register_native("math.Atan", |_, arguments| float_function("math.Atan", arguments, f64::atan));
+    interpreter.register_native("math.Atan2", atan2);
+    interpreter.register_native("math.Pow", pow);
+    interpreter.register_native("math.Log", log);
+    interpreter.register_native("math.Lerp", lerp);
+}
+
+
+enum Numbers {
+    Integers(Vec<i32>),
+    Floats(Vec<f64>),
+}
+
+
+fn numbers(name: &str, arguments: &[RuntimeValue], count: usize) -> Result<Numbers, Diagnostic> {
+    expect_count(name, arguments, count, count)?;
+
+    let floats = (0..count)
+        .map(|index| float_argument(name, arguments, index))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let integers = arguments
+        .iter()
+        .map(|argument| match argument {
+            RuntimeValue::Integer(value) => Some(*value),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>();
+
+    Ok(match integers {
+        Some(integers) => Numbers::Integers(integers),
+        None => Numbers::Floats(floats),
+    })
+}
+
+
+fn float_function(name: &str, arguments: &[RuntimeValue], function: fn(f64) -> f64) -> Result<RuntimeValue, Diagnostic> {
+    expect_count(name, arguments, 1, 1)?;
+
+    Ok(RuntimeValue::Float(function(float_argument(name, arguments, 0)?)))
+}
+
+
+fn int_function(name: &str, arguments: &[RuntimeValue], function: fn(f64) -> f64) -> Result<RuntimeValue, Diagnostic> {
+    expect_count(name, arguments, 1, 1)?;
+
+    Ok(RuntimeValue::Integer(function(float_argument(name, arguments, 0)?) as i32))
+}
+
+
+fn abs(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    match numbers("math.Abs", arguments, 1)? {
+        Numbers::Integers(values) => Ok(RuntimeValue::Integer(values[0].wrapping_abs())),
+        Numbers::Floats(values) => Ok(RuntimeValue::Float(values[0].abs())),
+    }
+}
+
+
+fn sign(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    match numbers("math.Sign", arguments, 1)? {
+        Numbers::Integers(values) => Ok(RuntimeValue::Integer(if values[0] < 0 { -1 } else { 1 })),
+        Numbers::Floats(values) => Ok(RuntimeValue::Float(if values[0] < 0.0 { -1.0 } else { 1.0 })),
+    }
+}
+
+
+fn min(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    match numbers("math.Min", arguments, 2)? {
+        Numbers::Integers(values) => Ok(RuntimeValue::Integer(values[0].min(values[1]))),
+        Numbers::Floats(values) => Ok(RuntimeValue::Float(values[0].min(values[1]))),
+    }
+}
+
+
+fn max(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    match numbers("math.Max", arguments, 2)? {
+        Numbers::Integers(values) => Ok(RuntimeValue::Integer(values[0].max(values[1]))),
+        Numbers::Floats(values) => Ok(RuntimeValue::Float(values[0].max(values[1]))),
+    }
+}
+
+
+fn clamp(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    match numbers("math.Clamp", arguments, 3)? {
+        Numbers::Integers(values) => Ok(RuntimeValue::Integer(values[0].max(values[1]).min(values[2]))),
+        Numbers::Floats(values) => Ok(RuntimeValue::Float(values[0].max(values[1]).min(values[2]))),
+    }
+}
+
+
+fn atan2(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("math.Atan2", arguments, 2, 2)?;
+
+    let y = float_argument("math.Atan2", arguments, 0)?;
+    let x = float_argument("math.Atan2", arguments, 1)?;
+
+    Ok(RuntimeValue::Float(y.atan2(x)))
+}
+
+
+fn pow(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("math.Pow", arguments, 2, 2)?;
+
+    let base = float_argument("math.Pow", arguments, 0)?;
+    let exponent = float_argument("math.Pow", arguments, 1)?;
+
+    Ok(RuntimeValue::Float(base.powf(exponent)))
+}
+
+
+fn log(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("math.Log", arguments, 1, 2)?;
+
+    let value = float_argument("math.Log", arguments, 0)?;
+
+    match arguments.len() {
+        2 => Ok(RuntimeValue::Float(value.log(float_argument("math.Log", arguments, 1)?))),
+        _ => Ok(RuntimeValue::Float(value.ln())),
+    }
+}
+
+
+fn lerp(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("math.Lerp", arguments, 3, 3)?;
+
+    let from = float_argument("math.Lerp", arguments, 0)?;
+    let to = float_argument("math.Lerp", arguments, 1)?;
+    let amount = float_argument("math.Lerp", arguments, 2)?.clamp(0.0, 1.0);
+
+    Ok(RuntimeValue::Float(from + (to - from) * amount))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::natives::evaluate;
+    use crate::interpreter::value::RuntimeValue;
+    use crate::interpreter::Interpreter;
+
+    #[test]
+    fn test_result_types() {
+        assert_eq!(evaluate("math.Abs(-3)"), RuntimeValue::Integer(3));
+        assert_eq!(evaluate("math.Abs(-2.5)"), RuntimeValue::Float(2.5));
+        assert_eq!(evaluate("math.Min(3, 7)"), RuntimeValue::Integer(3));
+        assert_eq!(evaluate("math.Max(3, 7.5)"), RuntimeValue::Float(7.5));
+        assert_eq!(evaluate("math.Clamp(12, 0, 10)"), RuntimeValue::Integer(10));
+        assert_eq!(evaluate("math.Clamp(-1, 0.5, 10)"), RuntimeValue::Float(0.5));
+        assert_eq!(evaluate("math.Sign(0)"), RuntimeValue::Integer(1));
+        assert_eq!(evaluate("math.Sign(-0.1)"), RuntimeValue::Float(-1.0));
+        assert_eq!(evaluate("math.Sqrt(16)"), RuntimeValue::Float(4.0));
+        assert_eq!(evaluate("math.Pow(2, 10)"), RuntimeValue::Float(1024.0));
+    }
+
+    #[test]
+    fn test_rounding() {
+        assert_eq!(evaluate("math.Floor(-1.5)"), RuntimeValue::Float(-2.0));
+        assert_eq!(evaluate("math.Ceil(1.2)"), RuntimeValue::Float(2.0));
+        assert_eq!(evaluate("math.Round(2.5)"), RuntimeValue::Float(2.0));
+        assert_eq!(evaluate("math.Round(3.5)"), RuntimeValue::Float(4.0));
+        assert_eq!(evaluate("math.RoundToInt(3.5)"), RuntimeValue::Integer(4));
+        assert_eq!(evaluate("math.FloorToInt(7 / 2.0)"), RuntimeValue::Integer(3));
+    }
+
+    #[test]
+    fn test_constants_and_trig() {
+        assert_eq!(evaluate("math.pi"), RuntimeValue::Float(std::f64::consts::PI));
+        assert_eq!(evaluate("math.Log(math.e)"), RuntimeValue::Float(1.0));
+        assert_eq!(evaluate("math.Log(8, 2)"), RuntimeValue::Float(3.0));
+        assert_eq!(evaluate("math.Cos(0)"), RuntimeValue::Float(1.0));
+        assert_eq!(evaluate("math.Atan2(1, 1) * 4"), RuntimeValue::Float(std::f64::consts::PI));
+        assert_eq!(evaluate("math.Lerp(10, 20, 0.25)"), RuntimeValue::Float(12.5));
+        assert_eq!(evaluate("math.Lerp(10, 20, 2)"), RuntimeValue::Float(20.0));
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut interpreter = Interpreter::new();
+
+        let diagnostic = interpreter.run(&crate::parse("var a = math.Abs(\"x\")").unwrap()).unwrap_err().diagnostic;
+
+        assert_eq!(diagnostic.message, "argument 1 of `math.Abs` must be a number, found string");
+
+        let diagnostic = interpreter.run(&crate::parse("var a = math.Min(1)").unwrap()).unwrap_err().diagnostic;
+
+        assert_eq!(diagnostic.message, "`math.Min` takes 2 argument(s) but 1 were supplied");
+    }
+}
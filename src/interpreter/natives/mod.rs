@@ -0,0 +1,80 @@
+pub mod conversion;
+pub mod draw;
+pub mod key;
+pub mod math;
+pub mod storage;
+pub mod string;
+pub mod ui;
+
+use crate::diagnostics::Diagnostic;
+
+use super::value::RuntimeValue;
+use super::Interpreter;
+
+
+pub fn register_defaults(interpreter: &mut Interpreter) {
+    conversion::register(interpreter);
+    draw::register(interpreter);
+    key::register(interpreter);
+    math::register(interpreter);
+    storage::register(interpreter);
+    string::register(interpreter);
+    ui::register(interpreter);
+}
+
+
+pub fn expect_count(name: &str, arguments: &[RuntimeValue], min: usize, max: usize) -> Result<(), Diagnostic> {
+    if (min..=max).contains(&arguments.len()) {
+        return Ok(());
+    }
+
+    let expected = match max {
+        usize::MAX => format!("at least {}", min),
+        max if max == min => min.to_string(),
+        max => format!("{} to {}", min, max),
+    };
+
+    Err(Diagnostic::error(format!(
+        "`{}` takes {} argument(s) but {} were supplied", name, expected, arguments.len()
+    )))
+}
+
+
+fn mismatch(name: &str, index: usize, expected: &str, value: &RuntimeValue) -> Diagnostic {
+    Diagnostic::error(format!(
+        "argument {} of `{}` must be {}, found {}", index + 1, name, expected, value.type_name()
+    ))
+}
+
+
+pub fn int_argument(name: &str, arguments: &[RuntimeValue], index: usize) -> Result<i32, Diagnostic> {
+    match &arguments[index] {
+        RuntimeValue::Integer(value) => Ok(*value),
+        RuntimeValue::Float(value) => Ok(*value as i32),
+        value => Err(mismatch(name, index, "a number", value)),
+    }
+}
+
+
+pub fn float_argument(name: &str, arguments: &[RuntimeValue], index: usize) -> Result<f64, Diagnostic> {
+    match arguments[index].as_float() {
+        Some(value) => Ok(value),
+        None => Err(mismatch(name, index, "a number", &arguments[index])),
+    }
+}
+
+
+pub fn string_argument<'a>(name: &str, arguments: &'a [RuntimeValue], index: usize) -> Result<&'a str, Diagnostic> {
+    match &arguments[index] {
+        RuntimeValue::String(value) => Ok(value),
+        value => Err(mismatch(name, index, "a string", value)),
+    }
+}
+
+
+#[cfg(test)]
+fn evaluate(source: &str) -> RuntimeValue {
+    let mut interpreter = Interpreter::new();
+    interpreter.run(&crate::parse(&format!("var result = {}", source)).unwrap()).unwrap();
+    interpreter.environment.get("result").cloned().unwrap()
+}
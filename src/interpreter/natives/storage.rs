@@ -0,0 +1,181 @@
+use crate::diagnostics::Diagnostic;
+use crate::interpreter::state::StateValue;
+use crate::interpreter::value::RuntimeValue;
+use crate::interpreter::Interpreter;
+
+use super::{expect_count, string_argument};
+
+
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.register_native("storage.Get", get);
+    interpreter.register_native("storage.Set", set);
+    interpreter.register_native("storage.Has", has);
+    interpreter.register_native("storage.Delete", delete);
+    interpreter.register_native("storage.Incr", increment);
+    interpreter.register_native("storage.Keys", keys);
+}
+
+
+fn state_value(value: &RuntimeValue) -> Result<StateValue, Diagnostic> {
+    match value {
+        RuntimeValue::Boolean(value) => Ok(StateValue::Boolean(*value)),
+        RuntimeValue::Integer(value) => Ok(StateValue::Integer(*value)),
+        RuntimeValue::Float(value) if value.is_finite() => Ok(StateValue::Float(*value)),
+        RuntimeValue::Float(value) => Err(
+            Diagnostic::error(format!("cannot store the non-finite float {}", value))
+                .with_help("storage files can only hold finite numbers")
+        ),
+        RuntimeValue::String(value) => Ok(StateValue::String(value.clone())),
+        value => Err(Diagnostic::error(format!("cannot store {} values", value.type_name()))),
+    }
+}
+
+
+fn get(interpreter: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("storage.Get", arguments, 1, 2)?;
+
+    let key = string_argument("storage.Get", arguments, 0)?;
+
+    match interpreter.storage.get(key) {
+        Some(value) => Ok(RuntimeValue::from(&value)),
+        None => Ok(arguments.get(1).cloned().unwrap_or_default()),
+    }
+}
+
+
+fn set(interpreter: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("storage.Set", arguments, 2, 2)?;
+
+    let key = string_argument("storage.Set", arguments, 0)?;
+
+    interpreter.storage.set(key, state_value(&arguments[1])?)?;
+
+    Ok(RuntimeValue::Null)
+}
+
+
+fn has(interpreter: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("storage.Has", arguments, 1, 1)?;
+
+    let key = string_argument("storage.Has", arguments, 0)?;
+
+    Ok(RuntimeValue::Boolean(interpreter.storage.get(key).is_some()))
+}
+
+
+fn delete(interpreter: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("storage.Delete", arguments, 1, 1)?;
+
+    let key = string_argument("storage.Delete", arguments, 0)?;
+
+    interpreter.storage.delete(key)?;
+
+    Ok(RuntimeValue::Null)
+}
+
+
+fn increment(interpreter: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("storage.Incr", arguments, 1, 2)?;
+
+    let key = string_argument("storage.Incr", arguments, 0)?;
+
+    let amount = match arguments.get(1) {
+        None => 1,
+        Some(RuntimeValue::Integer(amount)) => *amount,
+        Some(value) => {
+            return Err(Diagnostic::error(format!(
+                "argument 2 of `storage.Incr` must be an int, found {}", value.type_name()
+            )));
+        },
+    };
+
+    let value = match interpreter.storage.get(key) {
+        None => amount,
+        Some(StateValue::Integer(value)) => value.wrapping_add(amount),
+        Some(value) => {
+            return Err(Diagnostic::error(format!(
+                "cannot increment `{}`, it holds {}", key, RuntimeValue::from(&value).type_name()
+            )));
+        },
+    };
+
+    interpreter.storage.set(key, StateValue::Integer(value))?;
+
+    Ok(RuntimeValue::Integer(value))
+}
+
+
+fn keys(interpreter: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("storage.Keys", arguments, 0, 1)?;
+
+    let prefix = match arguments.len() {
+        1 => string_argument("storage.Keys", arguments, 0)?,
+        _ => "",
+    };
+
+    Ok(RuntimeValue::array(
+        interpreter.storage
+            .keys()
+            .into_iter()
+            .filter(|key| key.starts_with(prefix))
+            .map(RuntimeValue::from)
+            .collect()
+    ))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::state::StateValue;
+    use crate::interpreter::storage::{MemoryStorage, Storage};
+    use crate::interpreter::Interpreter;
+
+    #[test]
+    fn test_sessions() {
+        let program = crate::parse("\
+var runs = storage.Incr(\"stats.runs\")
+?!storage.Has(\"stats.first\")
+  storage.Set(\"stats.first\", loc)
+storage.Set(\"last\", 1.5)
+storage.Delete(\"last\")
+>@runs@ @storage.Get(\"stats.first\")@ @storage.Get(\"last\", \"none\")@ @storage.Keys(\"stats.\")@
+").unwrap();
+
+        let mut storage: Box<dyn Storage> = Box::new(MemoryStorage::new());
+        let mut output = vec![];
+
+        for location in ["rocky", "caves"] {
+            let mut interpreter = Interpreter::new();
+
+            interpreter.storage = storage;
+            interpreter.set_location(location);
+            interpreter.tick(&program).unwrap();
+            output.extend(interpreter.take_output());
+
+            storage = std::mem::replace(&mut interpreter.storage, Box::new(MemoryStorage::new()));
+        }
+
+        assert_eq!(output, vec![
+            "1 rocky none [\"stats.first\", \"stats.runs\"]",
+            "2 rocky none [\"stats.first\", \"stats.runs\"]",
+        ]);
+        assert_eq!(storage.get("stats.runs"), Some(StateValue::Integer(2)));
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut interpreter = Interpreter::new();
+
+        let diagnostic = interpreter.run(&crate::parse("storage.Set(\"a\", [1])").unwrap()).unwrap_err().diagnostic;
+
+        assert_eq!(diagnostic.message, "cannot store array values");
+
+        let diagnostic = interpreter.run(&crate::parse("storage.Set(\"a\", \"x\")\nstorage.Incr(\"a\")").unwrap()).unwrap_err().diagnostic;
+
+        assert_eq!(diagnostic.message, "cannot increment `a`, it holds string");
+
+        let diagnostic = interpreter.run(&crate::parse("storage.Set(\"a\", 1.0 / 0)").unwrap()).unwrap_err().diagnostic;
+
+        assert_eq!(diagnostic.message, "cannot store the non-finite float inf");
+    }
+}
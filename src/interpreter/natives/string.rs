@@ -0,0 +1,301 @@
+use crate::diagnostics::Diagnostic;
+use crate::interpreter::value::RuntimeValue;
+use crate::interpreter::Interpreter;
+
+use super::{expect_count, int_argument, string_argument};
+
+
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.register_native("string.Size", size);
+    interpreter.register_native("string.Sub", sub);
+    interpreter.register_native("string.IndexOf", index_of);
+    interpreter.register_native("string.Contains", contains);
+    interpreter.register_native("string.Split", split);
+    interpreter.register_native("string.Join", join);
+    interpreter.register_native("string.Format", format);
+    interpreter.register_native("string.ToUpper", |_, arguments| map("string.ToUpper", arguments, str::to_uppercase));
+    interpreter.register_native("string.ToLower", |_, arguments| map("string.ToLower", arguments, str::to_lowercase));
+    interpreter.register_native("string.Capitalize", |_, arguments| map("string.Capitalize", arguments, capitalize));
+    interpreter.register_native("string.Equals", equals);
+    interpreter.register_native("string.Break", break_lines);
+}
+
+
+fn map(name: &str, arguments: &[RuntimeValue], function: fn(&str) -> String) -> Result<RuntimeValue, Diagnostic> {
+    expect_count(name, arguments, 1, 1)?;
+
+    Ok(RuntimeValue::String(function(string_argument(name, arguments, 0)?)))
+}
+
+
+fn capitalize(text: &str) -> String {
+    let mut characters = text.chars();
+
+    match characters.next() {
+        Some(first) => first.to_uppercase().chain(characters).collect(),
+        None => String::new(),
+    }
+}
+
+
+fn char_index(text: &str, start: usize, criteria: &str) -> Option<usize> {
+    let offset = text.char_indices().nth(start).map(|(offset, _)| offset)?;
+    let found = text[offset..].find(criteria)?;
+
+    Some(start + text[offset..offset + found].chars().count())
+}
+
+
+fn size(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("string.Size", arguments, 1, 1)?;
+
+    Ok(RuntimeValue::Integer(string_argument("string.Size", arguments, 0)?.chars().count() as i32))
+}
+
+
+fn sub(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    const NAME: &str = "string.Sub";
+
+    expect_count(NAME, arguments, 2, 3)?;
+
+    let text = string_argument(NAME, arguments, 0)?;
+    let start = int_argument(NAME, arguments, 1)?.max(0) as usize;
+
+    let length = match arguments.len() {
+        3 => int_argument(NAME, arguments, 2)?.max(0) as usize,
+        _ => usize::MAX,
+    };
+
+    Ok(RuntimeValue::String(text.chars().skip(start).take(length).collect()))
+}
+
+
+fn index_of(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    const NAME: &str = "string.IndexOf";
+
+    expect_count(NAME, arguments, 2, 3)?;
+
+    let text = string_argument(NAME, arguments, 0)?;
+    let criteria = string_argument(NAME, arguments, 1)?;
+
+    let start = match arguments.len() {
+        3 => int_argument(NAME, arguments, 2)?,
+        _ => 0,
+    };
+
+    let index = match start {
+        start if start < 0 => None,
+        start if start as usize == text.chars().count() && criteria.is_empty() => Some(start as usize),
+        start => char_index(text, start as usize, criteria),
+    };
+
+    Ok(RuntimeValue::Integer(index.map_or(-1, |index| index as i32)))
+}
+
+
+fn contains(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("string.Contains", arguments, 2, 2)?;
+
+    let text = string_argument("string.Contains", arguments, 0)?;
+    let criteria = string_argument("string.Contains", arguments, 1)?;
+
+    Ok(RuntimeValue::Boolean(text.contains(criteria)))
+}
+
+
+fn split(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    const NAME: &str = "string.Split";
+
+    expect_count(NAME, arguments, 1, usize::MAX)?;
+
+    let text = string_argument(NAME, arguments, 0)?;
+
+    let (separators, discard_empty) = match arguments.last() {
+        Some(RuntimeValue::Boolean(discard)) if arguments.len() > 1 => (&arguments[1..arguments.len() - 1], *discard),
+        _ => (&arguments[1..], false),
+    };
+
+    let separators = match separators.is_empty() {
+        true => vec![" "],
+        false => (0..separators.len())
+            .map(|index| string_argument(NAME, separators, index))
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+
+    let mut parts = vec![];
+    let mut rest = text;
+
+    loop {
+        let next = separators
+            .iter()
+            .filter(|separator| !separator.is_empty())
+            .filter_map(|separator| rest.find(separator).map(|offset| (offset, separator.len())))
+            .min();
+
+        match next {
+            Some((offset, length)) => {
+                parts.push(&rest[..offset]);
+                rest = &rest[offset + length..];
+            },
+            None => {
+                parts.push(rest);
+                break;
+            },
+        }
+    }
+
+    Ok(RuntimeValue::array(
+        parts
+            .into_iter()
+            .filter(|part| !(discard_empty && part.is_empty()))
+            .map(RuntimeValue::from)
+            .collect()
+    ))
+}
+
+
+fn join(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("string.Join", arguments, 2, 2)?;
+
+    let separator = string_argument("string.Join", arguments, 0)?;
+
+    match &arguments[1] {
+        RuntimeValue::Array(values) => Ok(RuntimeValue::String(
+            values.borrow().iter().map(ToString::to_string).collect::<Vec<_>>().join(separator)
+        )),
+        value => Err(Diagnostic::error(format!(
+            "argument 2 of `string.Join` must be an array, found {}", value.type_name()
+        ))),
+    }
+}
+
+
+fn format(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("string.Format", arguments, 1, usize::MAX)?;
+
+    let mut rest = string_argument("string.Format", arguments, 0)?;
+    let mut result = String::new();
+
+    while let Some(start) = rest.find('{') {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+
+        result.push_str(&rest[..start]);
+
+        match rest[start + 1..end].trim().parse::<usize>().ok().and_then(|index| arguments.get(index + 1)) {
+            Some(value) => result.push_str(&value.to_string()),
+            None => result.push_str(&rest[start..=end]),
+        }
+
+        rest = &rest[end + 1..];
+    }
+
+    result.push_str(rest);
+
+    Ok(RuntimeValue::String(result))
+}
+
+
+fn equals(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("string.Equals", arguments, 2, 2)?;
+
+    let left = string_argument("string.Equals", arguments, 0)?;
+    let right = string_argument("string.Equals", arguments, 1)?;
+
+    Ok(RuntimeValue::Boolean(left == right))
+}
+
+
+fn break_lines(_: &mut Interpreter, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+    expect_count("string.Break", arguments, 2, 2)?;
+
+    let text = string_argument("string.Break", arguments, 0)?;
+    let width = int_argument("string.Break", arguments, 1)?.max(1) as usize;
+
+    let mut lines = vec![];
+
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        let mut length = 0;
+
+        for word in paragraph.split(' ') {
+            let mut word = word.chars().collect::<Vec<_>>();
+
+            if length > 0 && length + 1 + word.len() > width {
+                lines.push(std::mem::take(&mut line));
+                length = 0;
+            }
+
+            if length > 0 {
+                line.push(' ');
+                length += 1;
+            }
+
+            while length + word.len() > width {
+                let rest = word.split_off(width - length);
+
+                line.extend(word);
+                lines.push(std::mem::take(&mut line));
+                length = 0;
+                word = rest;
+            }
+
+            length += word.len();
+            line.extend(word);
+        }
+
+        lines.push(line);
+    }
+
+    Ok(RuntimeValue::array(lines.into_iter().map(RuntimeValue::from).collect()))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::interpreter::natives::evaluate;
+    use crate::interpreter::value::RuntimeValue;
+
+    #[test]
+    fn test_bounds() {
+        assert_eq!(evaluate("string.Size(\"héllo\")"), RuntimeValue::Integer(5));
+        assert_eq!(evaluate("string.Sub(\"héllo\", 1, 3)"), RuntimeValue::from("éll"));
+        assert_eq!(evaluate("string.Sub(\"héllo\", 3)"), RuntimeValue::from("lo"));
+        assert_eq!(evaluate("string.Sub(\"héllo\", 4, 10)"), RuntimeValue::from("o"));
+        assert_eq!(evaluate("string.Sub(\"héllo\", 9)"), RuntimeValue::from(""));
+        assert_eq!(evaluate("string.Sub(\"héllo\", -2, 2)"), RuntimeValue::from("hé"));
+        assert_eq!(evaluate("string.IndexOf(\"██ hp ██\", \"hp\")"), RuntimeValue::Integer(3));
+        assert_eq!(evaluate("string.IndexOf(\"abcabc\", \"b\", 2)"), RuntimeValue::Integer(4));
+        assert_eq!(evaluate("string.IndexOf(\"abc\", \"b\", 7)"), RuntimeValue::Integer(-1));
+        assert_eq!(evaluate("string.IndexOf(\"abc\", \"z\")"), RuntimeValue::Integer(-1));
+    }
+
+    #[test]
+    fn test_transformations() {
+        assert_eq!(evaluate("string.ToUpper(\"ärmor\")"), RuntimeValue::from("ÄRMOR"));
+        assert_eq!(evaluate("string.ToLower(\"HP\")"), RuntimeValue::from("hp"));
+        assert_eq!(evaluate("string.Capitalize(\"élite foe\")"), RuntimeValue::from("Élite foe"));
+        assert_eq!(evaluate("string.Contains(\"poison\", \"iso\")"), RuntimeValue::Boolean(true));
+        assert_eq!(evaluate("string.Equals(\"Boo\", \"boo\")"), RuntimeValue::Boolean(false));
+        assert_eq!(evaluate("string.Format(\"{0}/{1} {2}\", 5, 9.5)"), RuntimeValue::from("5/9.5 {2}"));
+    }
+
+    #[test]
+    fn test_split_and_join() {
+        assert_eq!(evaluate("string.Split(\"a b  c\")").to_string(), "[\"a\", \"b\", \"\", \"c\"]");
+        assert_eq!(evaluate("string.Split(\"a,b;;c\", \",\", \";\", true)").to_string(), "[\"a\", \"b\", \"c\"]");
+        assert_eq!(evaluate("string.Join(\"-\", [1, \"x\", 2.5])"), RuntimeValue::from("1-x-2.5"));
+    }
+
+    #[test]
+    fn test_break() {
+        assert_eq!(
+            evaluate("string.Break(\"the quick brown fox\", 10)").to_string(),
+            "[\"the quick\", \"brown fox\"]"
+        );
+        assert_eq!(evaluate("string.Break(\"ééééééé\", 3)").to_string(), "[\"ééé\", \"ééé\", \"é\"]");
+        assert_eq!(evaluate("string.Break(\"ab cdefg\", 4)").to_string(), "[\"ab\", \"cdef\", \"g\"]");
+    }
+}
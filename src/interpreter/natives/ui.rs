@@ -0,0 +1,16 @@
+use crate::interpreter::ui::ROOT;
+use crate::interpreter::value::RuntimeValue;
+use crate::interpreter::Interpreter;
+
+
+pub fn register(interpreter: &mut Interpreter) {
+    interpreter.register_constant("ui.root", RuntimeValue::Component(ROOT));
+
+    interpreter.register_native("ui.AddPanel", |interpreter, arguments| interpreter.ui.call(ROOT, "AddPanel", arguments));
+    interpreter.register_native("ui.AddText", |interpreter, arguments| interpreter.ui.call(ROOT, "AddText", arguments));
+    interpreter.register_native("ui.AddButton", |interpreter, arguments| interpreter.ui.call(ROOT, "AddButton", arguments));
+    interpreter.register_native("ui.AddCanvas", |interpreter, arguments| interpreter.ui.call(ROOT, "AddCanvas", arguments));
+    interpreter.register_native("ui.Add", |interpreter, arguments| interpreter.ui.call(ROOT, "Add", arguments));
+    interpreter.register_native("ui.Remove", |interpreter, arguments| interpreter.ui.call(ROOT, "Remove", arguments));
+    interpreter.register_native("ui.Clear", |interpreter, arguments| interpreter.ui.call(ROOT, "Clear", arguments));
+}
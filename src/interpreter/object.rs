@@ -0,0 +1,37 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::value::RuntimeValue;
+use super::Function;
+
+
+pub type ObjectRef = Rc<RefCell<Object>>;
+
+
+#[derive(Debug, Default)]
+pub struct Object {
+    pub name: String,
+    pub fields: HashMap<String, RuntimeValue>,
+    pub functions: HashMap<String, Rc<Function>>,
+    // Chunks of the methods the bytecode VM compiled for this object.
+    pub methods: HashMap<String, u32>,
+}
+
+
+impl Object {
+    pub fn new(name: &str) -> ObjectRef {
+        Rc::new(RefCell::new(Object {
+            name: name.to_string(),
+            ..Object::default()
+        }))
+    }
+
+    pub fn field(&self, name: &str) -> Option<RuntimeValue> {
+        self.fields.get(name).cloned()
+    }
+
+    pub fn function(&self, name: &str) -> Option<Rc<Function>> {
+        self.functions.get(name).cloned()
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::VecDeque;
+
+
+pub const RNG_RANGE: i32 = 10000;
+
+
+#[derive(Debug, Clone, Default)]
+pub struct Random {
+    state: u64,
+    integers: VecDeque<i32>,
+    floats: VecDeque<f64>,
+}
+
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Random {
+            state: seed,
+            integers: VecDeque::new(),
+            floats: VecDeque::new(),
+        }
+    }
+
+    pub fn seed(&mut self, seed: u64) {
+        self.state = seed;
+    }
+
+    pub fn script_rng(&mut self, values: impl IntoIterator<Item = i32>) {
+        self.integers.extend(values);
+    }
+
+    pub fn script_rngf(&mut self, values: impl IntoIterator<Item = f64>) {
+        self.floats.extend(values);
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+
+        let mut value = self.state;
+
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+
+        value ^ (value >> 31)
+    }
+
+    pub fn rng(&mut self) -> i32 {
+        match self.integers.pop_front() {
+            Some(value) => value,
+            None => (((self.next_u64() >> 32) * RNG_RANGE as u64) >> 32) as i32,
+        }
+    }
+
+    pub fn rngf(&mut self) -> f64 {
+        match self.floats.pop_front() {
+            Some(value) => value,
+            None => (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded() {
+        let mut first = Random::new(7);
+        let mut second = Random::new(7);
+
+        let values = (0..100).map(|_| first.rng()).collect::<Vec<_>>();
+
+        assert_eq!(values, (0..100).map(|_| second.rng()).collect::<Vec<_>>());
+        assert!(values.iter().all(|value| (0..RNG_RANGE).contains(value)));
+        assert!((0..100).map(|_| first.rngf()).all(|value| (0.0..1.0).contains(&value)));
+
+        first.seed(7);
+
+        assert_eq!(first.rng(), values[0]);
+    }
+
+    #[test]
+    fn test_scripted() {
+        let mut random = Random::new(1);
+
+        random.script_rng([0, 9999]);
+        random.script_rngf([0.5]);
+
+        assert_eq!(random.rng(), 0);
+        assert_eq!(random.rngf(), 0.5);
+        assert_eq!(random.rng(), 9999);
+        assert_ne!(random.rngf(), 0.5);
+    }
+}
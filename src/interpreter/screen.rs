@@ -0,0 +1,402 @@
+use std::fmt::Write;
+use std::ops::Range;
+
+
+pub const SCREEN_WIDTH: usize = 80;
+
+pub const SCREEN_HEIGHT: usize = 35;
+
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+
+impl Color {
+    pub const fn rgb(red: u8, green: u8, blue: u8) -> Color {
+        Color { red, green, blue }
+    }
+
+    pub fn parse(name: &str) -> Option<Color> {
+        let name = name.trim().trim_start_matches('#');
+
+        let named = match name.to_ascii_lowercase().as_str() {
+            "white" => Some(Color::rgb(255, 255, 255)),
+            "black" => Some(Color::rgb(0, 0, 0)),
+            "red" => Some(Color::rgb(255, 0, 0)),
+            "green" => Some(Color::rgb(0, 255, 0)),
+            "blue" => Some(Color::rgb(0, 0, 255)),
+            "yellow" => Some(Color::rgb(255, 255, 0)),
+            "cyan" => Some(Color::rgb(0, 255, 255)),
+            "magenta" => Some(Color::rgb(255, 0, 255)),
+            "gray" | "grey" => Some(Color::rgb(128, 128, 128)),
+            _ => None,
+        };
+
+        if named.is_some() {
+            return named;
+        }
+
+        if !name.is_ascii() {
+            return None;
+        }
+
+        let digits = match name.len() {
+            3 => name.chars().flat_map(|c| [c, c]).collect::<String>(),
+            6 => name.to_string(),
+            _ => return None,
+        };
+
+        let channel = |index: usize| u8::from_str_radix(&digits[index..index + 2], 16).ok();
+
+        Some(Color::rgb(channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Cell {
+    pub character: char,
+    pub color: Option<Color>,
+    pub background: Option<Color>,
+}
+
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell { character: ' ', color: None, background: None }
+    }
+}
+
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Anchor {
+    Top,
+    Player,
+    Hud,
+    Foe,
+    Center,
+}
+
+
+#[derive(Debug, Clone)]
+pub struct Screen {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+    top_line: usize,
+    pub player: (i32, i32),
+    pub foe: (i32, i32),
+}
+
+
+impl Default for Screen {
+    fn default() -> Self {
+        Screen::new(SCREEN_WIDTH, SCREEN_HEIGHT)
+    }
+}
+
+
+impl Screen {
+    pub fn new(width: usize, height: usize) -> Self {
+        Screen {
+            width,
+            height,
+            cells: vec![Cell::default(); width * height],
+            top_line: 0,
+            player: (width as i32 / 4, height as i32 * 2 / 3),
+            foe: (width as i32 * 3 / 4, height as i32 * 2 / 3),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.fill(Cell::default());
+        self.top_line = 0;
+    }
+
+    pub fn origin(&self, anchor: Anchor) -> (i32, i32) {
+        match anchor {
+            Anchor::Top | Anchor::Hud => (0, 0),
+            Anchor::Player => self.player,
+            Anchor::Foe => self.foe,
+            Anchor::Center => (self.width as i32 / 2, self.height as i32 / 2),
+        }
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return None;
+        }
+
+        Some(y as usize * self.width + x as usize)
+    }
+
+    pub fn cell(&self, x: i32, y: i32) -> Option<&Cell> {
+        self.index(x, y).map(|index| &self.cells[index])
+    }
+
+    pub fn put(&mut self, x: i32, y: i32, character: char, color: Option<Color>) {
+        if let Some(index) = self.index(x, y) {
+            let cell = &mut self.cells[index];
+
+            cell.character = character;
+            cell.color = color;
+        }
+    }
+
+    pub fn write(&mut self, x: i32, y: i32, text: &str, color: Option<Color>) {
+        for (row, line) in text.split('\n').enumerate() {
+            for (column, character) in line.chars().enumerate() {
+                if character != ' ' {
+                    self.put(x.saturating_add(column as i32), y.saturating_add(row as i32), character, color);
+                }
+            }
+        }
+    }
+
+    pub fn print(&mut self, anchor: Anchor, x: i32, y: i32, text: &str, color: Option<Color>) {
+        let (origin_x, origin_y) = self.origin(anchor);
+
+        self.write(origin_x.saturating_add(x), origin_y.saturating_add(y), text, color);
+    }
+
+    pub fn print_top(&mut self, text: &str) {
+        let lines = text.split('\n').count();
+
+        self.write(0, self.top_line as i32, text, None);
+        self.top_line += lines;
+    }
+
+    pub fn render_print<'a>(&mut self, text: &'a str) -> &'a str {
+        match parse_print(text) {
+            Some((anchor, x, y, color, text)) => {
+                self.print(anchor, x, y, text, color);
+                text
+            },
+            None => {
+                self.print_top(text);
+                text
+            },
+        }
+    }
+
+    pub fn fill_background(&mut self, x: i32, y: i32, width: i32, height: i32, color: Option<Color>) {
+        let (x, y) = (x as i64, y as i64);
+
+        for row in visible(y, y + height as i64, self.height) {
+            for column in visible(x, x + width as i64, self.width) {
+                if let Some(index) = self.index(column, row) {
+                    self.cells[index].background = color;
+                }
+            }
+        }
+    }
+
+    pub fn draw_box(&mut self, x: i32, y: i32, width: i32, height: i32, color: Option<Color>) {
+        if width < 2 || height < 2 {
+            return;
+        }
+
+        let (x, y) = (x as i64, y as i64);
+        let (right, bottom) = (x + width as i64 - 1, y + height as i64 - 1);
+        let columns = visible(x + 1, right, self.width);
+
+        for column in columns.clone() {
+            self.put(column, coordinate(y), '-', color);
+            self.put(column, coordinate(bottom), '-', color);
+        }
+
+        for row in visible(y + 1, bottom, self.height) {
+            self.put(coordinate(x), row, '|', color);
+            self.put(coordinate(right), row, '|', color);
+
+            for column in columns.clone() {
+                self.put(column, row, ' ', color);
+            }
+        }
+
+        for (column, row) in [(x, y), (right, y), (x, bottom), (right, bottom)] {
+            self.put(coordinate(column), coordinate(row), '+', color);
+        }
+    }
+
+    fn rows(&self) -> impl Iterator<Item = &[Cell]> {
+        self.cells.chunks(self.width)
+    }
+
+    pub fn to_plain(&self) -> String {
+        let mut lines = self.rows()
+            .map(|row| row.iter().map(|cell| cell.character).collect::<String>().trim_end().to_string())
+            .collect::<Vec<_>>();
+
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+
+    pub fn to_ansi(&self) -> String {
+        let mut output = String::new();
+
+        for row in self.rows() {
+            let mut current = (None, None);
+
+            for cell in row {
+                if (cell.color, cell.background) != current {
+                    output.push_str("\x1b[0m");
+
+                    if let Some(color) = cell.color {
+                        let _ = write!(output, "\x1b[38;2;{};{};{}m", color.red, color.green, color.blue);
+                    }
+
+                    if let Some(color) = cell.background {
+                        let _ = write!(output, "\x1b[48;2;{};{};{}m", color.red, color.green, color.blue);
+                    }
+
+                    current = (cell.color, cell.background);
+                }
+
+                output.push(cell.character);
+            }
+
+            output.push_str("\x1b[0m\n");
+        }
+
+        output
+    }
+}
+
+
+fn visible(start: i64, end: i64, limit: usize) -> Range<i32> {
+    start.clamp(0, limit as i64) as i32..end.clamp(0, limit as i64) as i32
+}
+
+
+fn coordinate(value: i64) -> i32 {
+    i32::try_from(value).unwrap_or(-1)
+}
+
+
+fn parse_print(text: &str) -> Option<(Anchor, i32, i32, Option<Color>, &str)> {
+    let anchor = match text.chars().next()? {
+        'o' => Anchor::Player,
+        'h' => Anchor::Hud,
+        'f' => Anchor::Foe,
+        'c' => Anchor::Center,
+        _ => return None,
+    };
+
+    let mut parts = text[1..].splitn(3, ',');
+    let x = parts.next()?.trim().parse().ok()?;
+    let y = parts.next()?.trim().parse().ok()?;
+    let rest = parts.next().unwrap_or("");
+
+    match rest.strip_prefix('#').and_then(|rest| rest.split_once(',')) {
+        Some((color, text)) => Some((anchor, x, y, Some(Color::parse(color)?), text)),
+        None => Some((anchor, x, y, None, rest)),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_colors() {
+        assert_eq!(Color::parse("#ff8000"), Some(Color::rgb(255, 128, 0)));
+        assert_eq!(Color::parse("#f80"), Some(Color::rgb(255, 136, 0)));
+        assert_eq!(Color::parse("#Red"), Some(Color::rgb(255, 0, 0)));
+        assert_eq!(Color::parse("#12345"), None);
+        assert_eq!(Color::parse("#zzzzzz"), None);
+        assert_eq!(Color::parse("#ééé"), None);
+    }
+
+    #[test]
+    fn test_write_and_clip() {
+        let mut screen = Screen::new(10, 4);
+
+        screen.print_top("hello");
+        screen.print_top("a\nb");
+        screen.write(7, 3, "clipped", Some(Color::rgb(255, 0, 0)));
+        screen.write(-2, 1, "xyz", None);
+
+        assert_eq!(screen.to_plain(), "hello\nz\nb\n       cli\n");
+        assert_eq!(screen.cell(8, 3).unwrap().color, Some(Color::rgb(255, 0, 0)));
+        assert_eq!(screen.cell(10, 3), None);
+    }
+
+    #[test]
+    fn test_ascii_art_is_transparent() {
+        let mut screen = Screen::new(6, 3);
+
+        screen.write(0, 0, "######\n######", None);
+        screen.write(1, 0, "o  o\n    ", None);
+
+        assert_eq!(screen.to_plain(), "#o##o#\n######\n");
+    }
+
+    #[test]
+    fn test_box_and_background() {
+        let mut screen = Screen::new(8, 5);
+        let blue = Some(Color::rgb(0, 0, 255));
+
+        screen.draw_box(1, 1, 5, 3, None);
+        screen.fill_background(0, 0, 2, 1, blue);
+        screen.print(Anchor::Center, -1, 0, "x", None);
+
+        assert_eq!(screen.to_plain(), "\n +---+\n | x |\n +---+\n");
+        assert_eq!(screen.cell(1, 0).unwrap().background, blue);
+        assert_eq!(screen.cell(2, 0).unwrap().background, None);
+    }
+
+    #[test]
+    fn test_extreme_boxes_are_clipped() {
+        let mut screen = Screen::new(6, 4);
+
+        screen.fill_background(4, 2, i32::MAX, 1, Some(Color::rgb(255, 0, 0)));
+        screen.fill_background(i32::MIN, i32::MIN, i32::MAX, i32::MAX, None);
+        screen.draw_box(2, 1, i32::MAX, 3, None);
+        screen.draw_box(-2_000_000_000, -2_000_000_000, 2_000_000_000, 2_000_000_000, None);
+        screen.draw_box(i32::MAX, i32::MAX, i32::MAX, i32::MAX, None);
+        screen.print(Anchor::Center, i32::MAX, 0, "x", None);
+
+        assert_eq!(screen.to_plain(), "\n  +---\n  |\n  +---\n");
+        assert_eq!(screen.cell(5, 2).unwrap().background, Some(Color::rgb(255, 0, 0)));
+        assert_eq!(screen.cell(3, 2).unwrap().background, None);
+    }
+
+    #[test]
+    fn test_render_print() {
+        let mut screen = Screen::new(12, 4);
+
+        screen.render_print("hello");
+        assert_eq!(screen.render_print("h2,2,#f00,hud"), "hud");
+        screen.render_print("c-1,1,mid, text");
+        screen.render_print("cost,1");
+
+        assert_eq!(screen.to_plain(), "hello\ncost,1\n  hud\n     mid, te\n");
+        assert_eq!(screen.cell(2, 2).unwrap().color, Some(Color::rgb(255, 0, 0)));
+    }
+
+    #[test]
+    fn test_ansi() {
+        let mut screen = Screen::new(3, 1);
+
+        screen.write(1, 0, "ab", Some(Color::rgb(1, 2, 3)));
+
+        assert_eq!(screen.to_ansi(), " \x1b[0m\x1b[38;2;1;2;3mab\x1b[0m\n");
+    }
+}
@@ -0,0 +1,369 @@
+use std::collections::BTreeMap;
+
+use crate::diagnostics::Diagnostic;
+
+use super::value::{parse_float, parse_int, RuntimeValue};
+
+
+pub const FIELDS: [&str; 32] = [
+    "hp", "maxhp", "armor", "maxarmor",
+    "loc.id", "loc.name", "loc.stars", "loc.begin", "loc.loop",
+    "foe.id", "foe.name", "foe.distance", "foe.hp", "foe.maxhp", "foe.armor", "foe.maxarmor",
+    "foe.count", "foe.level", "foe.state", "foe.time",
+    "pos.x", "pos.y", "pos.z", "time", "totaltime",
+    "item.left", "item.right", "item.potion",
+    "buffs.count", "buffs.string", "debuffs.count", "debuffs.string",
+];
+
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum StateValue {
+    Boolean(bool),
+    Integer(i32),
+    Float(f64),
+    String(String),
+}
+
+
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Location {
+    pub id: String,
+    pub name: String,
+    pub stars: i32,
+    pub begin: bool,
+    pub r#loop: bool,
+}
+
+
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Foe {
+    pub id: String,
+    pub name: String,
+    pub distance: i32,
+    pub hp: i32,
+    pub maxhp: i32,
+    pub armor: i32,
+    pub maxarmor: i32,
+    pub count: i32,
+    pub level: i32,
+    pub state: i32,
+    pub time: i32,
+}
+
+
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Items {
+    pub left: String,
+    pub right: String,
+    pub potion: String,
+}
+
+
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Effects {
+    pub count: i32,
+    pub string: String,
+}
+
+
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct GameState {
+    pub hp: i32,
+    pub maxhp: i32,
+    pub armor: i32,
+    pub maxarmor: i32,
+    pub loc: Location,
+    pub foe: Foe,
+    pub pos: Position,
+    pub time: i32,
+    pub totaltime: i32,
+    pub item: Items,
+    pub buffs: Effects,
+    pub debuffs: Effects,
+    pub variables: BTreeMap<String, StateValue>,
+}
+
+
+impl StateValue {
+    pub fn parse(text: &str) -> StateValue {
+        let text = text.trim();
+
+        if let Some(value) = parse_int(text) {
+            return StateValue::Integer(value);
+        }
+
+        if let Some(value) = parse_float(text) {
+            return StateValue::Float(value);
+        }
+
+        match text {
+            "true" => StateValue::Boolean(true),
+            "false" => StateValue::Boolean(false),
+            _ => {
+                let unquoted = text.strip_prefix('"').and_then(|text| text.strip_suffix('"'));
+
+                StateValue::String(unquoted.unwrap_or(text).to_string())
+            },
+        }
+    }
+}
+
+
+impl From<&StateValue> for RuntimeValue {
+    fn from(value: &StateValue) -> Self {
+        match value {
+            StateValue::Boolean(value) => RuntimeValue::Boolean(*value),
+            StateValue::Integer(value) => RuntimeValue::Integer(*value),
+            StateValue::Float(value) => RuntimeValue::Float(*value),
+            StateValue::String(value) => RuntimeValue::String(value.clone()),
+        }
+    }
+}
+
+
+impl GameState {
+    pub fn new() -> Self {
+        GameState::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<RuntimeValue> {
+        let value = match name {
+            "hp" => self.hp.into(),
+            "maxhp" => self.maxhp.into(),
+            "armor" => self.armor.into(),
+            "maxarmor" => self.maxarmor.into(),
+            "loc" | "loc.id" => self.loc.id.as_str().into(),
+            "loc.name" => self.loc.name.as_str().into(),
+            "loc.stars" => self.loc.stars.into(),
+            "loc.begin" => self.loc.begin.into(),
+            "loc.loop" => self.loc.r#loop.into(),
+            "foe" | "foe.id" => self.foe.id.as_str().into(),
+            "foe.name" => self.foe.name.as_str().into(),
+            "foe.distance" => self.foe.distance.into(),
+            "foe.hp" => self.foe.hp.into(),
+            "foe.maxhp" => self.foe.maxhp.into(),
+            "foe.armor" => self.foe.armor.into(),
+            "foe.maxarmor" => self.foe.maxarmor.into(),
+            "foe.count" => self.foe.count.into(),
+            "foe.level" => self.foe.level.into(),
+            "foe.state" => self.foe.state.into(),
+            "foe.time" => self.foe.time.into(),
+            "pos.x" => self.pos.x.into(),
+            "pos.y" => self.pos.y.into(),
+            "pos.z" => self.pos.z.into(),
+            "time" => self.time.into(),
+            "totaltime" => self.totaltime.into(),
+            "item.left" => self.item.left.as_str().into(),
+            "item.right" => self.item.right.as_str().into(),
+            "item.potion" => self.item.potion.as_str().into(),
+            "buffs.count" => self.buffs.count.into(),
+            "buffs.string" => self.buffs.string.as_str().into(),
+            "debuffs.count" => self.debuffs.count.into(),
+            "debuffs.string" => self.debuffs.string.as_str().into(),
+            _ => return self.variables.get(name).map(RuntimeValue::from),
+        };
+
+        Some(value)
+    }
+
+    pub fn set(&mut self, name: &str, value: StateValue) {
+        self.variables.insert(name.to_string(), value);
+    }
+
+    pub fn assign(&mut self, name: &str, value: StateValue) -> Result<(), Diagnostic> {
+        let mismatch = |expected: &str| Diagnostic::error(format!(
+            "`{}` must be {}, found {}", name, expected, RuntimeValue::from(&value).type_name()
+        ));
+
+        let integer = || match &value {
+            StateValue::Integer(value) => Ok(*value),
+            _ => Err(mismatch("an int")),
+        };
+
+        let boolean = || match &value {
+            StateValue::Boolean(value) => Ok(*value),
+            _ => Err(mismatch("a bool")),
+        };
+
+        let string = || RuntimeValue::from(&value).to_string();
+
+        match name {
+            "hp" => self.hp = integer()?,
+            "maxhp" => self.maxhp = integer()?,
+            "armor" => self.armor = integer()?,
+            "maxarmor" => self.maxarmor = integer()?,
+            "loc" | "loc.id" => self.loc.id = string(),
+            "loc.name" => self.loc.name = string(),
+            "loc.stars" => self.loc.stars = integer()?,
+            "loc.begin" => self.loc.begin = boolean()?,
+            "loc.loop" => self.loc.r#loop = boolean()?,
+            "foe" | "foe.id" => self.foe.id = string(),
+            "foe.name" => self.foe.name = string(),
+            "foe.distance" => self.foe.distance = integer()?,
+            "foe.hp" => self.foe.hp = integer()?,
+            "foe.maxhp" => self.foe.maxhp = integer()?,
+            "foe.armor" => self.foe.armor = integer()?,
+            "foe.maxarmor" => self.foe.maxarmor = integer()?,
+            "foe.count" => self.foe.count = integer()?,
+            "foe.level" => self.foe.level = integer()?,
+            "foe.state" => self.foe.state = integer()?,
+            "foe.time" => self.foe.time = integer()?,
+            "pos.x" => self.pos.x = integer()?,
+            "pos.y" => self.pos.y = integer()?,
+            "pos.z" => self.pos.z = integer()?,
+            "time" => self.time = integer()?,
+            "totaltime" => self.totaltime = integer()?,
+            "item.left" => self.item.left = string(),
+            "item.right" => self.item.right = string(),
+            "item.potion" => self.item.potion = string(),
+            "buffs.count" => self.buffs.count = integer()?,
+            "buffs.string" => self.buffs.string = string(),
+            "debuffs.count" => self.debuffs.count = integer()?,
+            "debuffs.string" => self.debuffs.string = string(),
+            _ => self.set(name, value),
+        }
+
+        Ok(())
+    }
+
+    pub fn advance(&mut self) {
+        self.time += 1;
+        self.totaltime += 1;
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn from_json(source: &str) -> Result<GameState, Diagnostic> {
+        serde_json::from_str(source)
+            .map_err(|error| Diagnostic::error(format!("invalid game state: {}", error)))
+    }
+
+    #[cfg(feature = "toml")]
+    pub fn from_toml(source: &str) -> Result<GameState, Diagnostic> {
+        toml::from_str(source)
+            .map_err(|error| Diagnostic::error(format!("invalid game state: {}", error.message())))
+    }
+
+    pub fn load(path: &str) -> Result<GameState, Diagnostic> {
+        match path.rsplit_once('.').map(|(_, extension)| extension) {
+            #[cfg(feature = "serde")]
+            Some("json") => GameState::from_json(&read_fixture(path)?),
+            #[cfg(feature = "toml")]
+            Some("toml") => GameState::from_toml(&read_fixture(path)?),
+            _ => Err(
+                Diagnostic::error(format!("unsupported game state file `{}`", path))
+                    .with_help("game state fixtures need the `serde` feature for .json or the `toml` feature for .toml")
+            ),
+        }
+    }
+}
+
+
+#[cfg(feature = "serde")]
+fn read_fixture(path: &str) -> Result<String, Diagnostic> {
+    std::fs::read_to_string(path)
+        .map_err(|error| Diagnostic::error(format!("could not read `{}`: {}", path, error)))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_names() {
+        let mut state = GameState::new();
+
+        state.hp = 7;
+        state.loc.id = "caves".to_string();
+        state.loc.stars = 5;
+        state.foe.distance = 12;
+        state.buffs.string = "strength".to_string();
+        state.set("res.stone", StateValue::Integer(40));
+
+        assert_eq!(state.get("hp"), Some(RuntimeValue::Integer(7)));
+        assert_eq!(state.get("loc"), Some(RuntimeValue::from("caves")));
+        assert_eq!(state.get("loc.stars"), Some(RuntimeValue::Integer(5)));
+        assert_eq!(state.get("foe.distance"), Some(RuntimeValue::Integer(12)));
+        assert_eq!(state.get("buffs.string"), Some(RuntimeValue::from("strength")));
+        assert_eq!(state.get("res.stone"), Some(RuntimeValue::Integer(40)));
+        assert_eq!(state.get("unknown"), None);
+    }
+
+    #[test]
+    fn test_assign() {
+        let mut state = GameState::new();
+
+        state.assign("foe.hp", StateValue::parse("12")).unwrap();
+        state.assign("loc", StateValue::parse("\"deadwood\"")).unwrap();
+        state.assign("loc.begin", StateValue::parse("true")).unwrap();
+        state.assign("res.wood", StateValue::parse("1.5")).unwrap();
+
+        assert_eq!(state.foe.hp, 12);
+        assert_eq!(state.loc.id, "deadwood");
+        assert!(state.loc.begin);
+        assert_eq!(state.get("res.wood"), Some(RuntimeValue::Float(1.5)));
+
+        let error = state.assign("hp", StateValue::parse("lots")).unwrap_err();
+
+        assert_eq!(error.message, "`hp` must be an int, found string");
+
+        for field in FIELDS {
+            assert!(state.get(field).is_some(), "{}", field);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_from_json() {
+        let state = GameState::from_json(r#"{
+            "hp": 20,
+            "loc": { "id": "rocky", "stars": 3 },
+            "foe": { "id": "boo", "distance": 5, "count": 2 },
+            "variables": { "ai.enabled": true, "scale": 0.5 }
+        }"#).unwrap();
+
+        assert_eq!(state.hp, 20);
+        assert_eq!(state.loc.stars, 3);
+        assert_eq!(state.foe.count, 2);
+        assert_eq!(state.maxhp, 0);
+        assert_eq!(state.get("ai.enabled"), Some(RuntimeValue::Boolean(true)));
+        assert_eq!(state.get("scale"), Some(RuntimeValue::Float(0.5)));
+
+        let error = GameState::from_json(r#"{ "hp": "full" }"#).unwrap_err();
+
+        assert!(error.message.starts_with("invalid game state:"));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_from_toml() {
+        let state = GameState::from_toml("hp = 4\ntime = 90\n\n[item]\nleft = \"sword\"\n\n[variables]\n\"res.wood\" = 9\n").unwrap();
+
+        assert_eq!(state.hp, 4);
+        assert_eq!(state.time, 90);
+        assert_eq!(state.get("item.left"), Some(RuntimeValue::from("sword")));
+        assert_eq!(state.get("res.wood"), Some(RuntimeValue::Integer(9)));
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+
+use crate::diagnostics::Diagnostic;
+
+use super::state::StateValue;
+
+
+pub trait Storage {
+    fn get(&self, key: &str) -> Option<StateValue>;
+
+    fn set(&mut self, key: &str, value: StateValue) -> Result<(), Diagnostic>;
+
+    fn delete(&mut self, key: &str) -> Result<bool, Diagnostic>;
+
+    fn keys(&self) -> Vec<String>;
+}
+
+
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    pub values: BTreeMap<String, StateValue>,
+}
+
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        MemoryStorage::default()
+    }
+}
+
+
+impl Storage for MemoryStorage {
+    fn get(&self, key: &str) -> Option<StateValue> {
+        self.values.get(key).cloned()
+    }
+
+    fn set(&mut self, key: &str, value: StateValue) -> Result<(), Diagnostic> {
+        self.values.insert(key.to_string(), value);
+
+        Ok(())
+    }
+
+    fn delete(&mut self, key: &str) -> Result<bool, Diagnostic> {
+        Ok(self.values.remove(key).is_some())
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.values.keys().cloned().collect()
+    }
+}
+
+
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone)]
+pub struct JsonStorage {
+    path: std::path::PathBuf,
+    values: BTreeMap<String, StateValue>,
+}
+
+
+#[cfg(feature = "serde")]
+impl JsonStorage {
+    pub fn open(path: impl Into<std::path::PathBuf>) -> Result<Self, Diagnostic> {
+        let path = path.into();
+
+        let values = match std::fs::read_to_string(&path) {
+            Ok(source) => serde_json::from_str(&source).map_err(|error| {
+                Diagnostic::error(format!("invalid storage file `{}`: {}", path.display(), error))
+            })?,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(error) => {
+                return Err(Diagnostic::error(format!("could not read `{}`: {}", path.display(), error)));
+            },
+        };
+
+        Ok(JsonStorage { path, values })
+    }
+
+    fn save(&self) -> Result<(), Diagnostic> {
+        let json = serde_json::to_string_pretty(&self.values)
+            .map_err(|error| Diagnostic::error(error.to_string()))?;
+
+        std::fs::write(&self.path, json + "\n")
+            .map_err(|error| Diagnostic::error(format!("could not write `{}`: {}", self.path.display(), error)))
+    }
+}
+
+
+#[cfg(feature = "serde")]
+impl Storage for JsonStorage {
+    fn get(&self, key: &str) -> Option<StateValue> {
+        self.values.get(key).cloned()
+    }
+
+    fn set(&mut self, key: &str, value: StateValue) -> Result<(), Diagnostic> {
+        self.values.insert(key.to_string(), value);
+        self.save()
+    }
+
+    fn delete(&mut self, key: &str) -> Result<bool, Diagnostic> {
+        let removed = self.values.remove(key).is_some();
+
+        if removed {
+            self.save()?;
+        }
+
+        Ok(removed)
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.values.keys().cloned().collect()
+    }
+}
+
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_storage() {
+        let path = std::env::temp_dir().join(format!("stonescript-storage-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut storage = JsonStorage::open(&path).unwrap();
+
+        storage.set("runs", StateValue::Integer(3)).unwrap();
+        storage.set("best", StateValue::String("caves".to_string())).unwrap();
+        assert!(storage.delete("best").unwrap());
+        assert!(!storage.delete("best").unwrap());
+
+        let reopened = JsonStorage::open(&path).unwrap();
+
+        assert_eq!(reopened.get("runs"), Some(StateValue::Integer(3)));
+        assert_eq!(reopened.keys(), vec!["runs"]);
+
+        std::fs::write(&path, "[1]").unwrap();
+
+        assert!(JsonStorage::open(&path).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_non_finite_floats() {
+        let path = std::env::temp_dir().join(format!("stonescript-storage-finite-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut interpreter = crate::interpreter::Interpreter::new();
+
+        interpreter.storage = Box::new(JsonStorage::open(&path).unwrap());
+
+        let program = crate::parse("storage.Set(\"x\", 1.5)\nstorage.Set(\"y\", 1.0 / 0)").unwrap();
+
+        assert!(interpreter.run(&program).is_err());
+
+        let reopened = JsonStorage::open(&path).unwrap();
+
+        assert_eq!(reopened.get("x"), Some(StateValue::Float(1.5)));
+        assert_eq!(reopened.get("y"), None);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
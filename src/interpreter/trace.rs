@@ -0,0 +1,153 @@
+use std::fmt;
+
+use crate::diagnostics::{Diagnostic, Span};
+use crate::lexer::{COMMANDS, HOST_COMMANDS};
+
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value", rename_all = "snake_case"))]
+pub enum GameCommand {
+    Equip {
+        hand: Option<Hand>,
+        item: String,
+    },
+    Activate {
+        ability: String,
+    },
+    Loadout {
+        slot: i32,
+    },
+    Brew {
+        ingredients: Vec<String>,
+    },
+    Play {
+        sound: String,
+        pitch: Option<i32>,
+    },
+}
+
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommandEvent {
+    pub frame: u64,
+    pub command: GameCommand,
+    pub span: Span,
+}
+
+
+impl GameCommand {
+    pub fn is_game_command(name: &str) -> bool {
+        COMMANDS.contains(&name) && !HOST_COMMANDS.contains(&name)
+    }
+
+    pub fn parse(name: &str, arguments: &str) -> Result<GameCommand, Diagnostic> {
+        let arguments = arguments.trim();
+
+        if arguments.is_empty() {
+            return Err(Diagnostic::error(format!("`{}` expects an argument", name)));
+        }
+
+        match name {
+            "equip" | "equipL" | "equipR" => Ok(GameCommand::Equip {
+                hand: match name {
+                    "equipL" => Some(Hand::Left),
+                    "equipR" => Some(Hand::Right),
+                    _ => None,
+                },
+                item: arguments.to_string(),
+            }),
+            "activate" => Ok(GameCommand::Activate { ability: arguments.to_string() }),
+            "loadout" => match arguments.parse() {
+                Ok(slot) => Ok(GameCommand::Loadout { slot }),
+                Err(_) => Err(Diagnostic::error(format!("`loadout` expects a slot number, found `{}`", arguments))),
+            },
+            "brew" => Ok(GameCommand::Brew {
+                ingredients: arguments.split('+').map(|ingredient| ingredient.trim().to_string()).collect(),
+            }),
+            "play" => {
+                let (sound, pitch) = match arguments.rsplit_once(' ') {
+                    Some((sound, pitch)) => match pitch.parse() {
+                        Ok(pitch) => (sound.trim_end(), Some(pitch)),
+                        Err(_) => (arguments, None),
+                    },
+                    None => (arguments, None),
+                };
+
+                Ok(GameCommand::Play { sound: sound.to_string(), pitch })
+            },
+            _ => Err(Diagnostic::error(format!("unknown command `{}`", name))),
+        }
+    }
+}
+
+
+impl fmt::Display for GameCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameCommand::Equip { hand: None, item } => write!(f, "equip {}", item),
+            GameCommand::Equip { hand: Some(Hand::Left), item } => write!(f, "equipL {}", item),
+            GameCommand::Equip { hand: Some(Hand::Right), item } => write!(f, "equipR {}", item),
+            GameCommand::Activate { ability } => write!(f, "activate {}", ability),
+            GameCommand::Loadout { slot } => write!(f, "loadout {}", slot),
+            GameCommand::Brew { ingredients } => write!(f, "brew {}", ingredients.join(" + ")),
+            GameCommand::Play { sound, pitch: None } => write!(f, "play {}", sound),
+            GameCommand::Play { sound, pitch: Some(pitch) } => write!(f, "play {} {}", sound, pitch),
+        }
+    }
+}
+
+
+impl fmt::Display for CommandEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.frame, self.command)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            GameCommand::parse("equipR", " vigor shield "),
+            Ok(GameCommand::Equip { hand: Some(Hand::Right), item: "vigor shield".to_string() })
+        );
+        assert_eq!(
+            GameCommand::parse("brew", "stone+ wood"),
+            Ok(GameCommand::Brew { ingredients: vec!["stone".to_string(), "wood".to_string()] })
+        );
+        assert_eq!(
+            GameCommand::parse("play", "buy 200"),
+            Ok(GameCommand::Play { sound: "buy".to_string(), pitch: Some(200) })
+        );
+        assert_eq!(
+            GameCommand::parse("play", "level_up"),
+            Ok(GameCommand::Play { sound: "level_up".to_string(), pitch: None })
+        );
+        assert_eq!(GameCommand::parse("loadout", "2"), Ok(GameCommand::Loadout { slot: 2 }));
+
+        assert!(GameCommand::parse("loadout", "two").is_err());
+        assert!(GameCommand::parse("activate", "").is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        let command = GameCommand::parse("brew", "tar + bronze").unwrap();
+
+        assert_eq!(command.to_string(), "brew tar + bronze");
+        assert_eq!(GameCommand::parse("equipL", "sword").unwrap().to_string(), "equipL sword");
+    }
+}
@@ -0,0 +1,728 @@
+use std::collections::BTreeMap;
+
+use crate::diagnostics::Diagnostic;
+
+use super::natives::{expect_count, int_argument, string_argument};
+use super::screen::{Cell, Color, Screen};
+use super::value::RuntimeValue;
+
+
+pub type ComponentId = usize;
+
+pub const ROOT: ComponentId = 0;
+
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Align {
+    Start,
+    Center,
+    End,
+}
+
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ComponentKind {
+    Panel {
+        style: i32,
+    },
+    Text {
+        text: String,
+        align: Align,
+    },
+    Button {
+        text: String,
+        text_color: Option<Color>,
+        style: i32,
+        pressed: Option<String>,
+    },
+    Canvas {
+        cells: BTreeMap<(i32, i32), Cell>,
+    },
+}
+
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Component {
+    pub kind: ComponentKind,
+    pub parent: Option<ComponentId>,
+    pub children: Vec<ComponentId>,
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+    pub anchor: (Align, Align),
+    pub dock: (Align, Align),
+    pub margin: i32,
+    pub visible: bool,
+    pub color: Option<Color>,
+}
+
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+
+impl Rect {
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && y >= self.y && x < self.x.saturating_add(self.w) && y < self.y.saturating_add(self.h)
+    }
+
+    pub fn clip(&self, width: i32, height: i32) -> Rect {
+        let (left, top) = (self.x.max(-1), self.y.max(-1));
+        let right = (self.x as i64 + self.w as i64).min(width as i64 + 1);
+        let bottom = (self.y as i64 + self.h as i64).min(height as i64 + 1);
+
+        Rect {
+            x: left,
+            y: top,
+            w: (right - left as i64).max(0) as i32,
+            h: (bottom - top as i64).max(0) as i32,
+        }
+    }
+}
+
+
+#[derive(Debug, Clone)]
+pub struct Ui {
+    components: Vec<Component>,
+    width: i32,
+    height: i32,
+}
+
+
+impl Default for Ui {
+    fn default() -> Self {
+        Ui::new()
+    }
+}
+
+
+impl ComponentKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ComponentKind::Panel { .. } => "Panel",
+            ComponentKind::Text { .. } => "Text",
+            ComponentKind::Button { .. } => "Button",
+            ComponentKind::Canvas { .. } => "Canvas",
+        }
+    }
+}
+
+
+impl Component {
+    pub fn new(kind: ComponentKind) -> Self {
+        let (w, h) = match kind {
+            ComponentKind::Panel { .. } | ComponentKind::Canvas { .. } => (5, 5),
+            ComponentKind::Text { .. } => (10, 1),
+            ComponentKind::Button { .. } => (10, 3),
+        };
+
+        Component {
+            kind,
+            parent: None,
+            children: vec![],
+            x: 0,
+            y: 0,
+            w,
+            h,
+            anchor: (Align::Center, Align::Center),
+            dock: (Align::Center, Align::Center),
+            margin: 0,
+            visible: true,
+            color: None,
+        }
+    }
+}
+
+
+fn offset(align: Align, length: i32) -> i32 {
+    match align {
+        Align::Start => 0,
+        Align::Center => length / 2,
+        Align::End => length,
+    }
+}
+
+
+fn horizontal_name(align: Align) -> &'static str {
+    match align {
+        Align::Start => "left",
+        Align::Center => "center",
+        Align::End => "right",
+    }
+}
+
+
+fn vertical_name(align: Align) -> &'static str {
+    match align {
+        Align::Start => "top",
+        Align::Center => "center",
+        Align::End => "bottom",
+    }
+}
+
+
+fn parse_horizontal(name: &str) -> Option<Align> {
+    match name {
+        "left" => Some(Align::Start),
+        "center" => Some(Align::Center),
+        "right" => Some(Align::End),
+        _ => None,
+    }
+}
+
+
+fn parse_vertical(name: &str) -> Option<Align> {
+    match name {
+        "top" => Some(Align::Start),
+        "center" => Some(Align::Center),
+        "bottom" => Some(Align::End),
+        _ => None,
+    }
+}
+
+
+pub fn parse_alignment(name: &str) -> Option<(Align, Align)> {
+    let (vertical, horizontal) = name.split_once('_')?;
+
+    Some((parse_horizontal(horizontal)?, parse_vertical(vertical)?))
+}
+
+
+pub fn alignment_name((horizontal, vertical): (Align, Align)) -> String {
+    format!("{}_{}", vertical_name(vertical), horizontal_name(horizontal))
+}
+
+
+fn color_value(value: &RuntimeValue) -> Result<Option<Color>, Diagnostic> {
+    match value {
+        RuntimeValue::Null => Ok(None),
+        RuntimeValue::String(name) => match Color::parse(name) {
+            Some(color) => Ok(Some(color)),
+            None => Err(Diagnostic::error(format!("invalid color `{}`", name))),
+        },
+        value => Err(Diagnostic::error(format!("expected a color, found {}", value.type_name()))),
+    }
+}
+
+
+fn color_name(color: Option<Color>) -> RuntimeValue {
+    match color {
+        Some(color) => RuntimeValue::String(format!("#{:02x}{:02x}{:02x}", color.red, color.green, color.blue)),
+        None => RuntimeValue::Null,
+    }
+}
+
+
+fn property_error(kind: &ComponentKind, name: &str) -> Diagnostic {
+    Diagnostic::error(format!("{} has no property `{}`", kind.name(), name))
+}
+
+
+impl Ui {
+    pub fn new() -> Self {
+        let mut root = Component::new(ComponentKind::Panel { style: 0 });
+
+        root.anchor = (Align::Start, Align::Start);
+        root.dock = (Align::Start, Align::Start);
+
+        Ui { components: vec![root], width: 0, height: 0 }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Ui::new();
+    }
+
+    pub fn component(&self, id: ComponentId) -> &Component {
+        &self.components[id]
+    }
+
+    pub fn component_mut(&mut self, id: ComponentId) -> &mut Component {
+        &mut self.components[id]
+    }
+
+    pub fn add(&mut self, parent: ComponentId, kind: ComponentKind) -> ComponentId {
+        let id = self.components.len();
+
+        self.components.push(Component::new(kind));
+        self.attach(parent, id);
+
+        id
+    }
+
+    fn attach(&mut self, parent: ComponentId, id: ComponentId) {
+        self.detach(id);
+        self.components[id].parent = Some(parent);
+        self.components[parent].children.push(id);
+    }
+
+    fn detach(&mut self, id: ComponentId) {
+        if let Some(parent) = self.components[id].parent.take() {
+            self.components[parent].children.retain(|child| *child != id);
+        }
+    }
+
+    fn is_ancestor(&self, ancestor: ComponentId, mut id: ComponentId) -> bool {
+        loop {
+            if id == ancestor {
+                return true;
+            }
+
+            match self.components[id].parent {
+                Some(parent) => id = parent,
+                None => return false,
+            }
+        }
+    }
+
+    pub fn rect(&self, id: ComponentId) -> Rect {
+        let component = &self.components[id];
+
+        let Some(parent) = component.parent else {
+            return Rect { x: component.x, y: component.y, w: self.width, h: self.height };
+        };
+
+        let outer = self.rect(parent);
+        let margin = self.components[parent].margin;
+        let (width, height) = (outer.w.saturating_sub(margin.saturating_mul(2)), outer.h.saturating_sub(margin.saturating_mul(2)));
+
+        Rect {
+            x: outer.x
+                .saturating_add(margin)
+                .saturating_add(offset(component.dock.0, width))
+                .saturating_add(component.x)
+                .saturating_sub(offset(component.anchor.0, component.w)),
+            y: outer.y
+                .saturating_add(margin)
+                .saturating_add(offset(component.dock.1, height))
+                .saturating_add(component.y)
+                .saturating_sub(offset(component.anchor.1, component.h)),
+            w: component.w,
+            h: component.h,
+        }
+    }
+
+    fn is_visible(&self, mut id: ComponentId) -> bool {
+        loop {
+            let component = &self.components[id];
+
+            if !component.visible {
+                return false;
+            }
+
+            match component.parent {
+                Some(parent) => id = parent,
+                None => return id == ROOT,
+            }
+        }
+    }
+
+    pub fn button_at(&self, x: i32, y: i32) -> Option<ComponentId> {
+        let mut order = vec![];
+        self.collect(ROOT, &mut order);
+
+        order.into_iter().rev().find(|id| {
+            matches!(self.components[*id].kind, ComponentKind::Button { .. }) && self.rect(*id).contains(x, y)
+        })
+    }
+
+    fn collect(&self, id: ComponentId, order: &mut Vec<ComponentId>) {
+        if !self.components[id].visible {
+            return;
+        }
+
+        order.push(id);
+
+        for child in &self.components[id].children {
+            self.collect(*child, order);
+        }
+    }
+
+    pub fn pressed_callback(&self, id: ComponentId) -> Option<String> {
+        match &self.components[id].kind {
+            ComponentKind::Button { pressed, .. } if self.is_visible(id) => pressed.clone(),
+            _ => None,
+        }
+    }
+
+    pub fn render(&mut self, screen: &mut Screen) {
+        self.width = screen.width() as i32;
+        self.height = screen.height() as i32;
+
+        let mut order = vec![];
+        self.collect(ROOT, &mut order);
+
+        for id in order {
+            self.render_component(id, screen);
+        }
+    }
+
+    fn render_component(&self, id: ComponentId, screen: &mut Screen) {
+        let component = &self.components[id];
+        let rect = self.rect(id);
+        let clipped = rect.clip(screen.width() as i32, screen.height() as i32);
+
+        match &component.kind {
+            ComponentKind::Panel { style } => {
+                if *style != 0 {
+                    screen.draw_box(clipped.x, clipped.y, clipped.w, clipped.h, component.color);
+                }
+            },
+            ComponentKind::Text { text, align } => {
+                for (row, line) in text.split('\n').take(clipped.h.max(0) as usize).enumerate() {
+                    let line = line.chars().take(clipped.w.max(0) as usize).collect::<String>();
+                    let column = offset(*align, rect.w.saturating_sub(line.chars().count() as i32));
+
+                    screen.write(rect.x.saturating_add(column), rect.y.saturating_add(row as i32), &line, component.color);
+                }
+            },
+            ComponentKind::Button { text, text_color, style, .. } => {
+                if *style != 0 {
+                    screen.draw_box(clipped.x, clipped.y, clipped.w, clipped.h, component.color);
+                }
+
+                let line = text.chars().take(clipped.w.max(2) as usize - 2).collect::<String>();
+                let column = rect.w.saturating_sub(line.chars().count() as i32) / 2;
+
+                screen.write(rect.x.saturating_add(column), rect.y.saturating_add(rect.h / 2), &line, *text_color);
+            },
+            ComponentKind::Canvas { cells } => {
+                for (&(x, y), cell) in cells {
+                    if x < rect.w && y < rect.h {
+                        let (x, y) = (rect.x.saturating_add(x), rect.y.saturating_add(y));
+
+                        screen.put(x, y, cell.character, cell.color);
+                        screen.fill_background(x, y, 1, 1, cell.background);
+                    }
+                }
+            },
+        }
+    }
+
+    pub fn get(&self, id: ComponentId, name: &str) -> Result<RuntimeValue, Diagnostic> {
+        let component = &self.components[id];
+
+        let value = match (name, &component.kind) {
+            ("x", _) => component.x.into(),
+            ("y", _) => component.y.into(),
+            ("w", _) => component.w.into(),
+            ("h", _) => component.h.into(),
+            ("absoluteX", _) => self.rect(id).x.into(),
+            ("absoluteY", _) => self.rect(id).y.into(),
+            ("anchor", _) => alignment_name(component.anchor).into(),
+            ("ax", _) => horizontal_name(component.anchor.0).into(),
+            ("ay", _) => vertical_name(component.anchor.1).into(),
+            ("dock", _) => alignment_name(component.dock).into(),
+            ("dx", _) => horizontal_name(component.dock.0).into(),
+            ("dy", _) => vertical_name(component.dock.1).into(),
+            ("margin", _) => component.margin.into(),
+            ("visible", _) => component.visible.into(),
+            ("color", _) => color_name(component.color),
+            ("parent", _) => component.parent.map_or(RuntimeValue::Null, RuntimeValue::Component),
+            ("children", ComponentKind::Panel { .. }) => {
+                RuntimeValue::array(component.children.iter().copied().map(RuntimeValue::Component).collect())
+            },
+            ("style", ComponentKind::Panel { style } | ComponentKind::Button { style, .. }) => (*style).into(),
+            ("text", ComponentKind::Text { text, .. } | ComponentKind::Button { text, .. }) => text.as_str().into(),
+            ("align", ComponentKind::Text { align, .. }) => horizontal_name(*align).into(),
+            ("tcolor", ComponentKind::Button { text_color, .. }) => color_name(*text_color),
+            _ => return Err(property_error(&component.kind, name)),
+        };
+
+        Ok(value)
+    }
+
+    pub fn set(&mut self, id: ComponentId, name: &str, value: RuntimeValue) -> Result<(), Diagnostic> {
+        let integer = |value: &RuntimeValue| match value {
+            RuntimeValue::Integer(value) => Ok(*value),
+            RuntimeValue::Float(value) => Ok(*value as i32),
+            value => Err(Diagnostic::error(format!("`{}` expects a number, found {}", name, value.type_name()))),
+        };
+
+        let text = |value: &RuntimeValue| match value {
+            RuntimeValue::String(value) => Ok(value.clone()),
+            value => Err(Diagnostic::error(format!("`{}` expects a string, found {}", name, value.type_name()))),
+        };
+
+        let invalid = |value: &str| Diagnostic::error(format!("invalid value `{}` for `{}`", value, name));
+
+        let component = &mut self.components[id];
+
+        match (name, &mut component.kind) {
+            ("x", _) => component.x = integer(&value)?,
+            ("y", _) => component.y = integer(&value)?,
+            ("w", _) => component.w = integer(&value)?.max(0),
+            ("h", _) => component.h = integer(&value)?.max(0),
+            ("anchor", _) => {
+                let name = text(&value)?;
+                component.anchor = parse_alignment(&name).ok_or_else(|| invalid(&name))?;
+            },
+            ("ax", _) => {
+                let name = text(&value)?;
+                component.anchor.0 = parse_horizontal(&name).ok_or_else(|| invalid(&name))?;
+            },
+            ("ay", _) => {
+                let name = text(&value)?;
+                component.anchor.1 = parse_vertical(&name).ok_or_else(|| invalid(&name))?;
+            },
+            ("dock", _) => {
+                let name = text(&value)?;
+                component.dock = parse_alignment(&name).ok_or_else(|| invalid(&name))?;
+            },
+            ("dx", _) => {
+                let name = text(&value)?;
+                component.dock.0 = parse_horizontal(&name).ok_or_else(|| invalid(&name))?;
+            },
+            ("dy", _) => {
+                let name = text(&value)?;
+                component.dock.1 = parse_vertical(&name).ok_or_else(|| invalid(&name))?;
+            },
+            ("margin", _) => component.margin = integer(&value)?.max(0),
+            ("visible", _) => component.visible = value.is_truthy(),
+            ("color", _) => component.color = color_value(&value)?,
+            ("style", ComponentKind::Panel { style } | ComponentKind::Button { style, .. }) => *style = integer(&value)?,
+            ("text", ComponentKind::Text { text: content, .. } | ComponentKind::Button { text: content, .. }) => {
+                *content = value.to_string();
+            },
+            ("align", ComponentKind::Text { align, .. }) => {
+                let name = text(&value)?;
+                *align = parse_horizontal(&name).ok_or_else(|| invalid(&name))?;
+            },
+            ("tcolor", ComponentKind::Button { text_color, .. }) => *text_color = color_value(&value)?,
+            (_, kind) => return Err(property_error(kind, name)),
+        }
+
+        Ok(())
+    }
+
+    pub fn call(&mut self, id: ComponentId, method: &str, arguments: &[RuntimeValue]) -> Result<RuntimeValue, Diagnostic> {
+        let kind = self.components[id].kind.name();
+        let name = format!("{}.{}", kind, method);
+
+        let component = |value: &RuntimeValue| match value {
+            RuntimeValue::Component(id) => Ok(*id),
+            value => Err(Diagnostic::error(format!("`{}` expects a component, found {}", name, value.type_name()))),
+        };
+
+        match (method, &mut self.components[id].kind) {
+            ("AddPanel" | "AddText" | "AddButton" | "AddCanvas", ComponentKind::Panel { .. }) => {
+                expect_count(&name, arguments, 0, 1)?;
+
+                let text = arguments.first().map(ToString::to_string).unwrap_or_default();
+
+                let kind = match method {
+                    "AddPanel" => ComponentKind::Panel { style: 0 },
+                    "AddText" => ComponentKind::Text { text, align: Align::Start },
+                    "AddButton" => ComponentKind::Button { text, text_color: None, style: 1, pressed: None },
+                    _ => ComponentKind::Canvas { cells: BTreeMap::new() },
+                };
+
+                Ok(RuntimeValue::Component(self.add(id, kind)))
+            },
+            ("Add", ComponentKind::Panel { .. }) => {
+                expect_count(&name, arguments, 1, 1)?;
+
+                let child = component(&arguments[0])?;
+
+                if self.is_ancestor(child, id) {
+                    return Err(Diagnostic::error("cannot add a component to itself or its children"));
+                }
+
+                self.attach(id, child);
+
+                Ok(RuntimeValue::Null)
+            },
+            ("Remove", ComponentKind::Panel { .. }) => {
+                expect_count(&name, arguments, 1, 1)?;
+
+                let child = component(&arguments[0])?;
+
+                if self.components[child].parent == Some(id) {
+                    self.detach(child);
+                }
+
+                Ok(RuntimeValue::Null)
+            },
+            ("Clear", ComponentKind::Panel { .. }) => {
+                expect_count(&name, arguments, 0, 0)?;
+
+                for child in std::mem::take(&mut self.components[id].children) {
+                    self.components[child].parent = None;
+                }
+
+                Ok(RuntimeValue::Null)
+            },
+            ("Recycle", _) => {
+                expect_count(&name, arguments, 0, 0)?;
+
+                if id != ROOT {
+                    self.detach(id);
+                }
+
+                Ok(RuntimeValue::Null)
+            },
+            ("SetPressed", ComponentKind::Button { pressed, .. }) => {
+                expect_count(&name, arguments, 1, 1)?;
+
+                *pressed = Some(string_argument(&name, arguments, 0)?.to_string());
+
+                Ok(RuntimeValue::Null)
+            },
+            ("Set", ComponentKind::Canvas { cells }) => {
+                expect_count(&name, arguments, 3, 3)?;
+
+                let (x, y) = (int_argument(&name, arguments, 0)?, int_argument(&name, arguments, 1)?);
+
+                for (column, character) in arguments[2].to_string().chars().enumerate() {
+                    let cell = cells.entry((x + column as i32, y)).or_default();
+                    cell.character = character;
+                }
+
+                Ok(RuntimeValue::Null)
+            },
+            ("Get", ComponentKind::Canvas { cells }) => {
+                expect_count(&name, arguments, 2, 2)?;
+
+                let position = (int_argument(&name, arguments, 0)?, int_argument(&name, arguments, 1)?);
+
+                Ok(cells.get(&position).map_or(' ', |cell| cell.character).to_string().into())
+            },
+            ("SetFG" | "SetBG", ComponentKind::Canvas { cells }) => {
+                expect_count(&name, arguments, 3, 3)?;
+
+                let position = (int_argument(&name, arguments, 0)?, int_argument(&name, arguments, 1)?);
+                let color = color_value(&arguments[2])?;
+                let cell = cells.entry(position).or_default();
+
+                match method {
+                    "SetFG" => cell.color = color,
+                    _ => cell.background = color,
+                }
+
+                Ok(RuntimeValue::Null)
+            },
+            ("Clear", ComponentKind::Canvas { cells }) => {
+                expect_count(&name, arguments, 0, 0)?;
+
+                cells.clear();
+
+                Ok(RuntimeValue::Null)
+            },
+            _ => Err(Diagnostic::error(format!("{} has no method `{}`", kind, method))),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout() {
+        let mut ui = Ui::new();
+        let mut screen = Screen::new(20, 10);
+
+        ui.render(&mut screen);
+
+        let panel = ui.add(ROOT, ComponentKind::Panel { style: 1 });
+
+        ui.set(panel, "dock", "bottom_right".into()).unwrap();
+        ui.set(panel, "anchor", "bottom_right".into()).unwrap();
+        ui.set(panel, "w", 8.into()).unwrap();
+        ui.set(panel, "h", 4.into()).unwrap();
+        ui.set(panel, "margin", 1.into()).unwrap();
+
+        let text = ui.add(panel, ComponentKind::Text { text: "hi".to_string(), align: Align::End });
+
+        ui.set(text, "ax", "left".into()).unwrap();
+        ui.set(text, "dock", "top_left".into()).unwrap();
+        ui.set(text, "anchor", "top_left".into()).unwrap();
+        ui.set(text, "w", 6.into()).unwrap();
+
+        assert_eq!(ui.rect(panel), Rect { x: 12, y: 6, w: 8, h: 4 });
+        assert_eq!(ui.rect(text), Rect { x: 13, y: 7, w: 6, h: 1 });
+        assert_eq!(ui.get(panel, "anchor").unwrap(), RuntimeValue::from("bottom_right"));
+        assert_eq!(ui.get(text, "absoluteX").unwrap(), RuntimeValue::Integer(13));
+
+        let centered = ui.add(ROOT, ComponentKind::Panel { style: 0 });
+
+        assert_eq!(ui.rect(centered), Rect { x: 8, y: 3, w: 5, h: 5 });
+        assert!(ui.set(centered, "anchor", "middle".into()).is_err());
+        assert!(ui.set(centered, "text", "x".into()).is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let mut ui = Ui::new();
+        let mut screen = Screen::new(12, 5);
+
+        let panel = ui.add(ROOT, ComponentKind::Panel { style: 1 });
+
+        ui.set(panel, "w", 12.into()).unwrap();
+        ui.set(panel, "h", 3.into()).unwrap();
+        ui.set(panel, "dock", "top_left".into()).unwrap();
+        ui.set(panel, "anchor", "top_left".into()).unwrap();
+
+        let text = ui.add(panel, ComponentKind::Text { text: "mid".to_string(), align: Align::Center });
+        let canvas = ui.add(ROOT, ComponentKind::Canvas { cells: BTreeMap::new() });
+
+        ui.set(canvas, "dock", "bottom_left".into()).unwrap();
+        ui.set(canvas, "anchor", "bottom_left".into()).unwrap();
+        ui.set(canvas, "h", 1.into()).unwrap();
+        ui.call(canvas, "Set", &[0.into(), 0.into(), "<3>".into()]).unwrap();
+        ui.call(canvas, "Set", &[9.into(), 0.into(), "x".into()]).unwrap();
+
+        let hidden = ui.add(ROOT, ComponentKind::Text { text: "gone".to_string(), align: Align::Start });
+
+        ui.set(hidden, "visible", false.into()).unwrap();
+        ui.render(&mut screen);
+
+        assert_eq!(screen.to_plain(), "+----------+\n|   mid    |\n+----------+\n\n<3>\n");
+        assert_eq!(ui.call(canvas, "Get", &[1.into(), 0.into()]).unwrap(), RuntimeValue::from("3"));
+
+        ui.call(panel, "Remove", &[RuntimeValue::Component(text)]).unwrap();
+        ui.call(ROOT, "Add", &[RuntimeValue::Component(text)]).unwrap();
+
+        assert_eq!(ui.component(text).parent, Some(ROOT));
+        assert!(ui.call(text, "Add", &[RuntimeValue::Component(ROOT)]).is_err());
+        assert!(ui.call(panel, "Add", &[RuntimeValue::Component(ROOT)]).is_err());
+    }
+
+    #[test]
+    fn test_extreme_sizes() {
+        let mut ui = Ui::new();
+        let mut screen = Screen::new(10, 4);
+
+        let panel = ui.add(ROOT, ComponentKind::Panel { style: 1 });
+
+        ui.set(panel, "x", i32::MAX.into()).unwrap();
+        ui.set(panel, "w", 2.into()).unwrap();
+        ui.render(&mut screen);
+
+        assert!(!ui.rect(panel).contains(0, 0));
+        assert_eq!(screen.to_plain(), "");
+
+        ui.set(panel, "x", i32::MIN.into()).unwrap();
+        ui.set(panel, "dock", "top_left".into()).unwrap();
+        ui.set(panel, "anchor", "top_left".into()).unwrap();
+        ui.set(panel, "w", i32::MAX.into()).unwrap();
+        ui.set(panel, "h", 3.into()).unwrap();
+        ui.set(panel, "margin", i32::MAX.into()).unwrap();
+        ui.add(panel, ComponentKind::Text { text: "x".to_string(), align: Align::End });
+        ui.render(&mut screen);
+
+        assert_eq!(screen.to_plain(), "");
+
+        ui.set(panel, "x", 2.into()).unwrap();
+        ui.render(&mut screen);
+
+        assert!(ui.rect(panel).contains(i32::MAX - 1, 1));
+        assert_eq!(screen.to_plain(), "  +-------\n  |\n  +-------\n");
+    }
+}
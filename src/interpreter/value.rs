@@ -0,0 +1,213 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::parser::structs::Value;
+
+pub use crate::parser::parse::value::{parse_float, parse_int};
+
+use super::object::ObjectRef;
+use super::ui::ComponentId;
+
+
+type ArrayCell = RefCell<Vec<RuntimeValue>>;
+
+
+#[derive(Debug, Clone, Default)]
+pub enum RuntimeValue {
+    #[default]
+    Null,
+    Boolean(bool),
+    Integer(i32),
+    Float(f64),
+    String(String),
+    Array(Rc<RefCell<Vec<RuntimeValue>>>),
+    Component(ComponentId),
+    Object(ObjectRef),
+}
+
+
+impl RuntimeValue {
+    pub fn array(values: Vec<RuntimeValue>) -> RuntimeValue {
+        RuntimeValue::Array(Rc::new(RefCell::new(values)))
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            RuntimeValue::Null => "null",
+            RuntimeValue::Boolean(_) => "bool",
+            RuntimeValue::Integer(_) => "int",
+            RuntimeValue::Float(_) => "float",
+            RuntimeValue::String(_) => "string",
+            RuntimeValue::Array(_) => "array",
+            RuntimeValue::Component(_) => "component",
+            RuntimeValue::Object(_) => "object",
+        }
+    }
+
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            RuntimeValue::Null => false,
+            RuntimeValue::Boolean(value) => *value,
+            RuntimeValue::Integer(value) => *value != 0,
+            RuntimeValue::Float(value) => *value != 0.0,
+            RuntimeValue::String(value) => !value.is_empty(),
+            RuntimeValue::Array(_) | RuntimeValue::Component(_) | RuntimeValue::Object(_) => true,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            RuntimeValue::Integer(value) => Some(*value as f64),
+            RuntimeValue::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn loose_eq(&self, other: &RuntimeValue) -> bool {
+        self.loose_eq_visiting(other, &mut Vec::new())
+    }
+
+    fn loose_eq_visiting(&self, other: &RuntimeValue, visited: &mut Vec<(*const ArrayCell, *const ArrayCell)>) -> bool {
+        match (self, other) {
+            (RuntimeValue::Null, RuntimeValue::Null) => true,
+            (RuntimeValue::Boolean(left), RuntimeValue::Boolean(right)) => left == right,
+            (RuntimeValue::Integer(left), RuntimeValue::Integer(right)) => left == right,
+            (RuntimeValue::String(left), RuntimeValue::String(right)) => left == right,
+            (RuntimeValue::Component(left), RuntimeValue::Component(right)) => left == right,
+            (RuntimeValue::Object(left), RuntimeValue::Object(right)) => Rc::ptr_eq(left, right),
+            (RuntimeValue::Array(left), RuntimeValue::Array(right)) => {
+                let pair = (Rc::as_ptr(left), Rc::as_ptr(right));
+
+                if Rc::ptr_eq(left, right) || visited.contains(&pair) {
+                    return true;
+                }
+
+                visited.push(pair);
+
+                let (left, right) = (left.borrow(), right.borrow());
+                let equal = left.len() == right.len()
+                    && left.iter().zip(right.iter()).all(|(a, b)| a.loose_eq_visiting(b, visited));
+
+                visited.pop();
+                equal
+            },
+            (left, right) => match (left.as_float(), right.as_float()) {
+                (Some(left), Some(right)) => left == right,
+                _ => false,
+            },
+        }
+    }
+
+    pub fn compare(&self, other: &RuntimeValue) -> Option<Ordering> {
+        match (self, other) {
+            (RuntimeValue::Integer(left), RuntimeValue::Integer(right)) => Some(left.cmp(right)),
+            (RuntimeValue::String(left), RuntimeValue::String(right)) => Some(left.cmp(right)),
+            (left, right) => left.as_float()?.partial_cmp(&right.as_float()?),
+        }
+    }
+}
+
+
+impl From<&Value> for RuntimeValue {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::String(value) => RuntimeValue::String(value.clone()),
+            Value::Decimal(value) => RuntimeValue::Float(*value),
+            Value::Integer(value) => match i32::try_from(*value) {
+                Ok(value) => RuntimeValue::Integer(value),
+                Err(_) => RuntimeValue::Float(*value as f64),
+            },
+            Value::Boolean(value) => RuntimeValue::Boolean(*value),
+            Value::Null => RuntimeValue::Null,
+        }
+    }
+}
+
+
+impl From<bool> for RuntimeValue {
+    fn from(value: bool) -> Self {
+        RuntimeValue::Boolean(value)
+    }
+}
+
+
+impl From<i32> for RuntimeValue {
+    fn from(value: i32) -> Self {
+        RuntimeValue::Integer(value)
+    }
+}
+
+
+impl From<f64> for RuntimeValue {
+    fn from(value: f64) -> Self {
+        RuntimeValue::Float(value)
+    }
+}
+
+
+impl From<&str> for RuntimeValue {
+    fn from(value: &str) -> Self {
+        RuntimeValue::String(value.to_string())
+    }
+}
+
+
+impl From<String> for RuntimeValue {
+    fn from(value: String) -> Self {
+        RuntimeValue::String(value)
+    }
+}
+
+
+impl PartialEq for RuntimeValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (RuntimeValue::Integer(_), RuntimeValue::Float(_)) | (RuntimeValue::Float(_), RuntimeValue::Integer(_)) => false,
+            _ => self.loose_eq(other),
+        }
+    }
+}
+
+
+impl fmt::Display for RuntimeValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        display(self, f, &mut Vec::new())
+    }
+}
+
+
+fn display(value: &RuntimeValue, f: &mut fmt::Formatter<'_>, visiting: &mut Vec<*const ArrayCell>) -> fmt::Result {
+    match value {
+        RuntimeValue::Null => f.write_str("null"),
+        RuntimeValue::Boolean(value) => write!(f, "{}", value),
+        RuntimeValue::Integer(value) => write!(f, "{}", value),
+        RuntimeValue::Float(value) => write!(f, "{}", value),
+        RuntimeValue::String(value) => f.write_str(value),
+        RuntimeValue::Array(values) => {
+            if visiting.contains(&Rc::as_ptr(values)) {
+                return f.write_str("[...]");
+            }
+
+            visiting.push(Rc::as_ptr(values));
+            f.write_str("[")?;
+
+            for (index, value) in values.borrow().iter().enumerate() {
+                if index > 0 {
+                    f.write_str(", ")?;
+                }
+
+                match value {
+                    RuntimeValue::String(value) => write!(f, "\"{}\"", value)?,
+                    value => display(value, f, visiting)?,
+                }
+            }
+
+            visiting.pop();
+            f.write_str("]")
+        },
+        RuntimeValue::Component(id) => write!(f, "<component {}>", id),
+        RuntimeValue::Object(object) => write!(f, "<object {}>", object.borrow().name),
+    }
+}
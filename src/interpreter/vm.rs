@@ -0,0 +1,1003 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::diagnostics::{Diagnostic, Severity, Span};
+use crate::parser::structs::{Expression, UnaryOperator};
+
+use super::bytecode::{self, Instruction, Program, Slot, Variable};
+use super::error::{RuntimeError, StackFrame};
+use super::object::{Object, ObjectRef};
+use super::trace::{CommandEvent, GameCommand};
+use super::value::RuntimeValue;
+use super::{Interpreter, LoopCounter, NativeFunction, MAX_CALL_DEPTH};
+
+
+#[derive(Debug)]
+struct Frame {
+    chunk: usize,
+    caller: usize,
+    pc: usize,
+    locals: usize,
+    stack: usize,
+    loops: usize,
+    span: Span,
+    this: Option<ObjectRef>,
+    constructing: bool,
+}
+
+
+pub struct Vm {
+    program: Rc<Program>,
+    globals: Vec<Option<RuntimeValue>>,
+    functions: Vec<Option<u32>>,
+    natives: Vec<Option<NativeFunction>>,
+    scripts: HashMap<u32, u32>,
+    stack: Vec<RuntimeValue>,
+    locals: Vec<Option<RuntimeValue>>,
+    frames: Vec<Frame>,
+    chunk: usize,
+    pc: usize,
+    base: usize,
+}
+
+
+impl Vm {
+    pub fn new(program: Program) -> Self {
+        let names = program.names.len();
+
+        Vm {
+            program: Rc::new(program),
+            globals: vec![None; names],
+            functions: vec![None; names],
+            natives: vec![],
+            scripts: HashMap::new(),
+            stack: vec![],
+            locals: vec![],
+            frames: vec![],
+            chunk: 0,
+            pc: 0,
+            base: 0,
+        }
+    }
+
+    pub fn compile(program: &Expression) -> Result<Self, Diagnostic> {
+        Ok(Vm::new(bytecode::compile(program)?))
+    }
+
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    pub fn global(&self, name: &str) -> Option<&RuntimeValue> {
+        let index = self.program.names.iter().position(|global| global == name)?;
+
+        self.globals[index].as_ref()
+    }
+
+    pub fn globals(&self) -> Vec<(String, RuntimeValue)> {
+        let mut globals = self.program.names
+            .iter()
+            .zip(&self.globals)
+            .filter_map(|(name, value)| Some((name.clone(), value.clone()?)))
+            .collect::<Vec<_>>();
+
+        globals.sort_by(|(left, _), (right, _)| left.cmp(right));
+        globals
+    }
+
+    pub fn reset(&mut self) {
+        self.globals.fill(None);
+    }
+
+    pub fn run(&mut self, interpreter: &mut Interpreter) -> Result<(), RuntimeError> {
+        interpreter.steps = 0;
+        interpreter.loops.clear();
+        interpreter.fatal = false;
+        interpreter.failure = None;
+
+        self.natives = self.program.names
+            .iter()
+            .map(|name| interpreter.natives.get(name.as_str()).copied())
+            .collect();
+
+        (self.chunk, self.pc, self.base) = (0, 0, 0);
+
+        let result = loop {
+            let diagnostic = match self.dispatch(interpreter) {
+                Ok(()) => break Ok(()),
+                Err(diagnostic) => diagnostic,
+            };
+
+            let error = RuntimeError::new(diagnostic, self.call_stack());
+
+            if !interpreter.lenient || interpreter.fatal || !self.recover(interpreter) {
+                break Err(error);
+            }
+
+            let mut warning = error;
+
+            warning.diagnostic.severity = Severity::Warning;
+            interpreter.warnings.push(warning);
+        };
+
+        self.stack.clear();
+        self.locals.clear();
+        self.frames.clear();
+        interpreter.loops.clear();
+
+        result
+    }
+
+    pub fn tick(&mut self, interpreter: &mut Interpreter) -> Result<(), RuntimeError> {
+        let location = interpreter.location.clone();
+
+        interpreter.begin_tick();
+
+        if location.is_some() && location != interpreter.location {
+            self.reset();
+        }
+
+        let result = self.run(interpreter);
+
+        interpreter.end_tick();
+
+        result
+    }
+
+    fn call_stack(&self) -> Vec<StackFrame> {
+        self.frames
+            .iter()
+            .map(|frame| {
+                let name = self.program.name(self.program.chunks[frame.chunk].name);
+
+                let function = match &frame.this {
+                    Some(object) if !frame.constructing => format!("{}.{}", object.borrow().name, name),
+                    _ => name.to_string(),
+                };
+
+                StackFrame { function, span: frame.span }
+            })
+            .collect()
+    }
+
+    fn recover(&mut self, interpreter: &mut Interpreter) -> bool {
+        let at = self.pc as u32 - 1;
+
+        let Some(statement) = self.program.chunks[self.chunk].statements
+            .iter()
+            .find(|statement| statement.start <= at && at < statement.end)
+        else {
+            return false;
+        };
+
+        let statement = *statement;
+        let (stack, loops) = self.frames.last().map_or((0, 0), |frame| (frame.stack, frame.loops));
+
+        self.stack.truncate(stack + statement.depth as usize);
+        interpreter.loops.truncate(loops + statement.loops as usize);
+        self.pc = statement.end as usize;
+
+        true
+    }
+
+    fn pop(&mut self) -> RuntimeValue {
+        self.stack.pop().unwrap_or_default()
+    }
+
+    fn pop_many(&mut self, count: u32) -> Vec<RuntimeValue> {
+        self.stack.split_off(self.stack.len() - count as usize)
+    }
+
+    fn counter(&self, offset: usize) -> i32 {
+        match self.stack[self.stack.len() - offset] {
+            RuntimeValue::Integer(value) => value,
+            _ => 0,
+        }
+    }
+
+    fn get(&self, variable: Variable) -> Option<&RuntimeValue> {
+        variable.local
+            .and_then(|slot| self.locals[self.base + slot as usize].as_ref())
+            .or_else(|| self.globals[variable.name as usize].as_ref())
+    }
+
+    fn this(&self) -> Option<&ObjectRef> {
+        self.frames.last()?.this.as_ref()
+    }
+
+    // Inside an object, fields sit between locals and globals.
+    fn value(&self, variable: Variable) -> Option<RuntimeValue> {
+        if let Some(value) = variable.local.and_then(|slot| self.locals[self.base + slot as usize].as_ref()) {
+            return Some(value.clone());
+        }
+
+        if let Some(value) = self.this().and_then(|object| object.borrow().field(self.program.name(variable.name))) {
+            return Some(value);
+        }
+
+        self.globals[variable.name as usize].clone()
+    }
+
+    fn set(&mut self, slot: Slot, value: RuntimeValue) {
+        match slot {
+            Slot::Local(slot) => self.locals[self.base + slot as usize] = Some(value),
+            Slot::Global(name) => self.globals[name as usize] = Some(value),
+        }
+    }
+
+    fn assign(&mut self, variable: Variable, value: RuntimeValue) -> bool {
+        if let Some(slot) = variable.local.map(|slot| &mut self.locals[self.base + slot as usize]).filter(|slot| slot.is_some()) {
+            *slot = Some(value);
+            return true;
+        }
+
+        if let Some(object) = self.this() {
+            if let Some(field) = object.borrow_mut().fields.get_mut(self.program.name(variable.name)) {
+                *field = value;
+                return true;
+            }
+        }
+
+        match &mut self.globals[variable.name as usize] {
+            Some(slot) => {
+                *slot = value;
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn text(&mut self, parts: u32) -> String {
+        self.pop_many(parts).iter().map(RuntimeValue::to_string).collect()
+    }
+
+    fn enter(&mut self, interpreter: &mut Interpreter, chunk: u32, arguments: u32, this: Option<ObjectRef>) -> Result<(), Diagnostic> {
+        let program = self.program.clone();
+        let function = &program.chunks[chunk as usize];
+
+        if arguments as usize > function.parameters.len() {
+            return Err(interpreter.error(format!(
+                "function `{}` takes {} argument(s) but {} were supplied",
+                program.name(function.name),
+                function.parameters.len(),
+                arguments,
+            )));
+        }
+
+        if self.frames.len() >= MAX_CALL_DEPTH {
+            interpreter.fatal = true;
+            return Err(interpreter.error(format!("call stack overflow in `{}`", program.name(function.name))));
+        }
+
+        let start = self.stack.len() - arguments as usize;
+        let base = self.locals.len();
+
+        self.locals.resize(base + function.locals as usize, None);
+
+        for (index, slot) in function.parameters.iter().enumerate() {
+            let value = match index < arguments as usize {
+                true => std::mem::take(&mut self.stack[start + index]),
+                false => RuntimeValue::Null,
+            };
+
+            self.locals[base + *slot as usize] = Some(value);
+        }
+
+        self.stack.truncate(start);
+
+        self.frames.push(Frame {
+            chunk: chunk as usize,
+            caller: self.chunk,
+            pc: self.pc,
+            locals: base,
+            stack: self.stack.len(),
+            loops: interpreter.loops.len(),
+            span: interpreter.span,
+            this,
+            constructing: false,
+        });
+
+        (self.chunk, self.pc, self.base) = (chunk as usize, 0, base);
+
+        Ok(())
+    }
+
+    fn instantiate(&mut self, interpreter: &mut Interpreter, path: u32) -> Result<(), Diagnostic> {
+        let chunk = self.script(interpreter, path)?;
+        let object = Object::new(self.program.name(path));
+
+        self.enter(interpreter, chunk, 0, Some(object))?;
+
+        if let Some(frame) = self.frames.last_mut() {
+            frame.constructing = true;
+        }
+
+        Ok(())
+    }
+
+    // Scripts are compiled the first time they are instantiated and their
+    // chunks appended to the program.
+    fn script(&mut self, interpreter: &mut Interpreter, path: u32) -> Result<u32, Diagnostic> {
+        if let Some(chunk) = self.scripts.get(&path) {
+            return Ok(*chunk);
+        }
+
+        let name = self.program.name(path).to_string();
+        let script = interpreter.script(&name)?;
+        let (program, chunk) = bytecode::compile_object((*self.program).clone(), &name, &script)
+            .map_err(|diagnostic| interpreter.locate(diagnostic))?;
+
+        let names = program.names.len();
+        let natives = program.names[self.natives.len()..]
+            .iter()
+            .map(|name| interpreter.natives.get(name.as_str()).copied())
+            .collect::<Vec<_>>();
+
+        self.program = Rc::new(program);
+        self.globals.resize(names, None);
+        self.functions.resize(names, None);
+        self.natives.extend(natives);
+        self.scripts.insert(path, chunk);
+
+        Ok(chunk)
+    }
+
+    fn native(&mut self, interpreter: &mut Interpreter, native: NativeFunction, arguments: u32) -> Result<(), Diagnostic> {
+        let arguments = self.pop_many(arguments);
+        let value = native(interpreter, &arguments).map_err(|diagnostic| interpreter.locate(diagnostic))?;
+
+        self.stack.push(value);
+
+        Ok(())
+    }
+
+    fn dispatch(&mut self, interpreter: &mut Interpreter) -> Result<(), Diagnostic> {
+        let mut program = self.program.clone();
+
+        loop {
+            let instruction = program.chunks[self.chunk].code[self.pc];
+
+            self.pc += 1;
+
+            match instruction {
+                Instruction::Constant(index) => self.stack.push(program.constants[index as usize].clone()),
+                Instruction::Null => self.stack.push(RuntimeValue::Null),
+                Instruction::Pop => {
+                    self.pop();
+                },
+                Instruction::Dup => {
+                    let value = self.stack.last().cloned().unwrap_or_default();
+                    self.stack.push(value);
+                },
+                Instruction::Span(index) => interpreter.span = program.spans[index as usize],
+                Instruction::Step => interpreter.step()?,
+                Instruction::Load(variable) => {
+                    let value = match self.value(variable) {
+                        Some(value) => value,
+                        None => interpreter.lookup(program.name(variable.name)),
+                    };
+
+                    self.stack.push(value);
+                },
+                Instruction::Resolve { variable, found } => {
+                    let value = match self.value(variable) {
+                        Some(value) => Some(value),
+                        None => interpreter.resolve(program.name(variable.name)),
+                    };
+
+                    if let Some(value) = value {
+                        self.stack.push(value);
+                        self.pc = found as usize;
+                    }
+                },
+                Instruction::JumpIfDeclared { variable, target } => {
+                    if self.get(variable).is_some() {
+                        self.pc = target as usize;
+                    }
+                },
+                Instruction::Declare(slot) => {
+                    let value = self.pop();
+                    self.set(slot, value);
+                },
+                Instruction::Store(variable) => {
+                    let value = self.pop();
+
+                    if !self.assign(variable, value) {
+                        let name = program.name(variable.name);
+
+                        return Err(
+                            interpreter.error(format!("cannot assign to undeclared variable `{}`", name))
+                                .with_help(format!("declare it first with `var {}`", name))
+                        );
+                    }
+                },
+                Instruction::Binary(operator) => {
+                    let right = self.pop();
+                    let left = self.pop();
+
+                    self.stack.push(interpreter.binary(&operator, left, right)?);
+                },
+                Instruction::Compound(operator) => {
+                    let current = self.pop();
+                    let value = self.pop();
+
+                    self.stack.push(interpreter.binary(&operator, current, value)?);
+                },
+                Instruction::Not => {
+                    let value = self.pop();
+                    self.stack.push(RuntimeValue::Boolean(!value.is_truthy()));
+                },
+                Instruction::Negate => {
+                    let value = match self.pop() {
+                        RuntimeValue::Integer(value) => RuntimeValue::Integer(value.wrapping_neg()),
+                        RuntimeValue::Float(value) => RuntimeValue::Float(-value),
+                        value => return Err(interpreter.error(format!("cannot negate {}", value.type_name()))),
+                    };
+
+                    self.stack.push(value);
+                },
+                Instruction::Adjust(operator) => {
+                    let delta = match operator {
+                        UnaryOperator::Increment => 1,
+                        _ => -1,
+                    };
+
+                    let value = match self.pop() {
+                        RuntimeValue::Integer(value) => RuntimeValue::Integer(value.wrapping_add(delta)),
+                        RuntimeValue::Float(value) => RuntimeValue::Float(value + delta as f64),
+                        value => return Err(interpreter.error(format!("cannot apply `{}` to {}", operator, value.type_name()))),
+                    };
+
+                    self.stack.push(value);
+                },
+                Instruction::And(target) => {
+                    if !self.pop().is_truthy() {
+                        self.stack.push(RuntimeValue::Boolean(false));
+                        self.pc = target as usize;
+                    }
+                },
+                Instruction::Or(target) => {
+                    if self.pop().is_truthy() {
+                        self.stack.push(RuntimeValue::Boolean(true));
+                        self.pc = target as usize;
+                    }
+                },
+                Instruction::Truthy => {
+                    let value = self.pop();
+                    self.stack.push(RuntimeValue::Boolean(value.is_truthy()));
+                },
+                Instruction::Jump(target) => self.pc = target as usize,
+                Instruction::JumpIfFalse(target) => {
+                    if !self.pop().is_truthy() {
+                        self.pc = target as usize;
+                    }
+                },
+                Instruction::Index => {
+                    let index = self.pop();
+                    let target = self.pop();
+
+                    self.stack.push(interpreter.index(&target, &index)?);
+                },
+                Instruction::SetIndex => {
+                    let index = self.pop();
+                    let target = self.pop();
+                    let value = self.pop();
+
+                    interpreter.set_index(&target, &index, value)?;
+                },
+                Instruction::Field { name, fallback } => {
+                    let value = self.pop();
+                    let fallback = fallback.map(|full| program.name(full).to_string());
+
+                    self.stack.push(interpreter.member_of(value, program.name(name), fallback)?);
+                },
+                Instruction::SetField(name) => {
+                    let object = self.pop();
+                    let value = self.pop();
+
+                    interpreter.set_member(object, program.name(name), value)?;
+                },
+                Instruction::Array(count) => {
+                    let values = self.pop_many(count);
+                    self.stack.push(RuntimeValue::array(values));
+                },
+                Instruction::Call { name, arguments } => {
+                    let method = self.this().and_then(|object| {
+                        let chunk = object.borrow().methods.get(program.name(name)).copied()?;
+                        Some((object.clone(), chunk))
+                    });
+
+                    if let Some((object, chunk)) = method {
+                        self.enter(interpreter, chunk, arguments, Some(object))?;
+                    } else if let Some(chunk) = self.functions[name as usize] {
+                        self.enter(interpreter, chunk, arguments, None)?;
+                    } else if let Some(native) = self.natives[name as usize] {
+                        self.native(interpreter, native, arguments)?;
+                    } else {
+                        return Err(interpreter.error(format!("unknown function `{}`", program.name(name))));
+                    }
+                },
+                Instruction::CallDotted { call, arguments, done } => {
+                    let call = program.calls[call as usize];
+
+                    if self.get(call.path).is_none() {
+                        if let Some(chunk) = self.functions[call.function as usize] {
+                            self.pc = done as usize;
+                            self.enter(interpreter, chunk, arguments, None)?;
+                        } else if let Some(native) = self.natives[call.function as usize] {
+                            self.pc = done as usize;
+                            self.native(interpreter, native, arguments)?;
+                        }
+                    }
+                },
+                Instruction::Method { name, arguments, call } => {
+                    let target = self.pop();
+
+                    if let RuntimeValue::Object(object) = target {
+                        let chunk = object.borrow().methods.get(program.name(name)).copied();
+
+                        match chunk {
+                            Some(chunk) => self.enter(interpreter, chunk, arguments, Some(object))?,
+                            None => return Err(interpreter.error(format!("`{}` has no method `{}`", object.borrow().name, program.name(name)))),
+                        }
+
+                        continue;
+                    }
+
+                    let arguments = self.pop_many(arguments);
+
+                    let function = call
+                        .map(|call| program.calls[call as usize])
+                        .filter(|call| self.get(call.path).is_none())
+                        .map(|call| program.name(call.function).to_string());
+
+                    let value = interpreter.call_member(target, program.name(name), &arguments, function)?;
+
+                    self.stack.push(value);
+                },
+                Instruction::Define(chunk) => {
+                    self.functions[program.chunks[chunk as usize].name as usize] = Some(chunk);
+                },
+                Instruction::DefineMethod(chunk) => {
+                    if let Some(object) = self.this() {
+                        let name = program.name(program.chunks[chunk as usize].name).to_string();
+                        object.borrow_mut().methods.insert(name, chunk);
+                    }
+                },
+                Instruction::New(path) => {
+                    self.instantiate(interpreter, path)?;
+                    program = self.program.clone();
+                },
+                Instruction::This => {
+                    let Some(object) = self.this().cloned() else {
+                        return Err(interpreter.error("`this` can only be used inside an object"));
+                    };
+
+                    self.stack.push(RuntimeValue::Object(object));
+                },
+                Instruction::Fail(message) => {
+                    return Err(interpreter.error(program.constants[message as usize].to_string()));
+                },
+                Instruction::ToInteger => {
+                    let value = self.pop();
+                    let value = interpreter.to_integer(value)?;
+
+                    self.stack.push(RuntimeValue::Integer(value));
+                },
+                Instruction::Range => {
+                    let step = if self.counter(2) <= self.counter(1) { 1 } else { -1 };
+                    self.stack.push(RuntimeValue::Integer(step));
+                },
+                Instruction::LoopEnter(span) => {
+                    interpreter.loops.push(LoopCounter { span: program.spans[span as usize], iterations: 0 });
+                },
+                Instruction::Current(slot) => {
+                    let current = self.stack[self.stack.len() - 3].clone();
+                    self.set(slot, current);
+                },
+                Instruction::Iterate => {
+                    if let Some(counter) = interpreter.loops.last_mut() {
+                        counter.iterations += 1;
+                    }
+                },
+                Instruction::RangeNext(top) => {
+                    let (current, end, step) = (self.counter(3), self.counter(2), self.counter(1));
+
+                    if current != end {
+                        let length = self.stack.len();
+
+                        self.stack[length - 3] = RuntimeValue::Integer(current + step);
+                        self.pc = top as usize;
+                    }
+                },
+                Instruction::Each => {
+                    let values = match self.pop() {
+                        RuntimeValue::Array(values) => values.borrow().clone(),
+                        value => return Err(interpreter.error(format!("cannot iterate over {}", value.type_name()))),
+                    };
+
+                    self.stack.push(RuntimeValue::array(values));
+                    self.stack.push(RuntimeValue::Integer(0));
+                },
+                Instruction::EachNext { slot, exit } => {
+                    let length = self.stack.len();
+                    let index = self.counter(1);
+
+                    let value = match &self.stack[length - 2] {
+                        RuntimeValue::Array(values) => values.borrow().get(index as usize).cloned(),
+                        _ => None,
+                    };
+
+                    match value {
+                        Some(value) => {
+                            self.stack[length - 1] = RuntimeValue::Integer(index + 1);
+                            self.set(slot, value);
+                        },
+                        None => self.pc = exit as usize,
+                    }
+                },
+                Instruction::LoopExit(count) => {
+                    self.stack.truncate(self.stack.len() - count as usize);
+                    interpreter.loops.pop();
+                },
+                Instruction::Print(parts) => {
+                    let text = self.text(parts);
+                    let shown = interpreter.screen.render_print(&text).to_string();
+
+                    interpreter.output.push(shown);
+                },
+                Instruction::Command { name, span, parts } => {
+                    let arguments = self.text(parts);
+                    let command = GameCommand::parse(program.name(name), &arguments).map_err(|diagnostic| interpreter.locate(diagnostic))?;
+
+                    interpreter.trace.push(CommandEvent { frame: interpreter.frame, command, span: program.spans[span as usize] });
+                },
+                Instruction::Return => {
+                    let value = self.pop();
+                    let Some(frame) = self.frames.pop() else {
+                        return Ok(());
+                    };
+
+                    self.stack.truncate(frame.stack);
+                    self.locals.truncate(frame.locals);
+                    interpreter.loops.truncate(frame.loops);
+                    interpreter.span = frame.span;
+
+                    self.chunk = frame.caller;
+                    self.pc = frame.pc;
+                    self.base = self.frames.last().map_or(0, |frame| frame.locals);
+
+                    // A script run by `new` evaluates to its object, whatever it returns.
+                    match frame.this {
+                        Some(object) if frame.constructing => self.stack.push(RuntimeValue::Object(object)),
+                        _ => self.stack.push(value),
+                    }
+                },
+                Instruction::Halt => return Ok(()),
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::interpreter::loader::MemoryLoader;
+    use crate::interpreter::DEFAULT_STEP_LIMIT;
+
+    #[derive(Debug, PartialEq)]
+    struct Frame {
+        output: Vec<String>,
+        trace: Vec<String>,
+        error: Option<(String, Option<Span>, Vec<StackFrame>)>,
+        warnings: Vec<(String, Option<Span>)>,
+        steps: u64,
+    }
+
+    fn record(interpreter: &mut Interpreter, result: Result<(), RuntimeError>) -> Frame {
+        Frame {
+            output: interpreter.take_output(),
+            trace: interpreter.take_trace().iter().map(|event| format!("{:?}", event)).collect(),
+            error: result.err().map(|error| (error.message().to_string(), error.diagnostic.span(), error.stack)),
+            warnings: interpreter.take_warnings().iter().map(|warning| (warning.message().to_string(), warning.diagnostic.span())).collect(),
+            steps: interpreter.steps(),
+        }
+    }
+
+    fn differential(source: &str, frames: usize, setup: impl Fn(&mut Interpreter)) {
+        let program = crate::parse(source).unwrap();
+        let mut vm = Vm::compile(&program).unwrap();
+
+        let mut walker = Interpreter::new();
+        let mut machine = Interpreter::new();
+
+        setup(&mut walker);
+        setup(&mut machine);
+
+        for frame in 0..frames {
+            let expected = walker.tick(&program);
+            let expected = record(&mut walker, expected);
+
+            let actual = vm.tick(&mut machine);
+            let actual = record(&mut machine, actual);
+
+            assert_eq!(actual, expected, "frame {} of:\n{}", frame, source);
+            assert_eq!(snapshot(vm.globals()), snapshot(walker.globals()), "globals after frame {} of:\n{}", frame, source);
+            assert_eq!(machine.screen.to_plain(), walker.screen.to_plain());
+        }
+    }
+
+    // Objects compare by identity, so globals are compared by what they hold.
+    // Nesting is cut off for arrays that contain themselves.
+    fn snapshot(globals: Vec<(String, RuntimeValue)>) -> Vec<(String, String)> {
+        globals.iter().map(|(name, value)| (name.clone(), show(value, 0))).collect()
+    }
+
+    fn show(value: &RuntimeValue, depth: usize) -> String {
+        match value {
+            _ if depth > 8 => "...".to_string(),
+            RuntimeValue::Array(values) => {
+                let values = values.borrow().iter().map(|value| show(value, depth + 1)).collect::<Vec<_>>();
+
+                format!("[{}]", values.join(", "))
+            },
+            RuntimeValue::Object(object) => {
+                let object = object.borrow();
+                let mut fields = object.fields.iter().map(|(field, value)| format!("{} = {}", field, show(value, depth + 1))).collect::<Vec<_>>();
+
+                fields.sort();
+
+                format!("{} {{ {} }}", object.name, fields.join(", "))
+            },
+            value => format!("{:?}", value),
+        }
+    }
+
+    fn same(source: &str) {
+        differential(source, 3, |_| {});
+    }
+
+    #[test]
+    fn test_arithmetic_and_variables() {
+        same("\
+var a = 7 / 2
+var b = 7 / 2.0
+var c = 1 + 2 * 3 - 4 % 3
+var d = -a
+var s = \"hp: \" + 10 + 0.5
+var t = 1 = 1.0 & !(2 > 3) | x
+var n = 0
+n++
+n += 10
+n *= -2
+b--
+>@a@ @b@ @c@ @d@ @s@ @t@ @n@
+");
+    }
+
+    #[test]
+    fn test_control_flow() {
+        same("\
+var total = 0
+for i = 1..10
+  ?i % 2 = 0
+    continue
+  :? i > 7
+    break
+  total += i
+var down = []
+for i = 3..1
+  down.Add(i)
+var sum = 0
+for value : [4, 5, 6]
+  ?value = 5
+    continue
+  sum += value
+?time % 2 = 0
+  >even @time@ @total@ @down@ @sum@ @i@ @value@
+:
+  >odd
+  return
+>after
+");
+    }
+
+    #[test]
+    fn test_long_chains() {
+        same(&format!(">@{}@ @{}@", vec!["2 * 3 - 1"; 5000].join(" + "), vec!["0 > 1"; 5000].join(" & ")));
+    }
+
+    #[test]
+    fn test_self_containing_arrays() {
+        same("var a = [1]\na.Add(a)\n>@a@ @a.Contains([1])@ @a.IndexOf(a)@");
+    }
+
+    #[test]
+    fn test_objects() {
+        let scripts = || MemoryLoader::new()
+            .with_file("Components/Counter", "\
+var count = 0
+var step = 1
+var seen = []
+for i = 1..2
+  seen.Add(i)
+func Increment()
+  count += step
+  seen.Add(count)
+  return Describe()
+func Describe()
+  var text = name() + \" \" + this.count
+  return text
+func name()
+  return Label()
+func Fail()
+  return 1 + []
+?seen.Count() > 0
+  return 5
+var never = 1
+")
+            .with_file("Empty", "")
+            .with_file("Broken", "var x = (1")
+            .with_file("Loop", "var next = new Loop\n");
+
+        let setup = |interpreter: &mut Interpreter| interpreter.loader = Box::new(scripts());
+
+        differential("\
+func Label()
+  return \"counter\"
+var a = new Components/Counter
+var b = new Components/Counter
+a.step = 5
+a.Increment()
+var last = \"\"
+last = b.Increment()
+var same = a = b
+var nested = [a, new Empty]
+>@a.count@ @b.count@ @last@ @typeof(a)@ @same@ @a.seen@ @nested[1]@ @a.never@
+", 3, setup);
+
+        for source in [
+            "var o = new Missing",
+            "var o = new Broken",
+            "var o = new Empty\no.Go()",
+            "var o = new Components/Counter\no.Increment(1, 2)",
+            "var o = new Components/Counter\n>@o.Fail()@",
+            "var o = this",
+            "var o = new Loop",
+        ] {
+            differential(source, 2, setup);
+        }
+    }
+
+    #[test]
+    fn test_functions_and_recursion() {
+        same("\
+var calls = 0
+func Fib(n)
+  calls++
+  ?n < 2
+    return n
+  return Fib(n - 1) + Fib(n - 2)
+func Count(items)
+  var total = 0
+  for item : items
+    ?item = 3
+      return total
+    total += item
+  return -1
+func Shadow(calls)
+  calls = calls * 2
+  return calls
+func Nothing()
+  ?calls > 0
+    break
+  return 1
+var result = Fib(12)
+>@result@ @calls@ @Count([1, 2, 3, 4])@ @Count([])@ @Shadow(21)@ @Nothing()@
+>@Defined()@
+func Defined()
+  return \"hoisted\"
+");
+    }
+
+    #[test]
+    fn test_members_natives_and_state() {
+        differential("\
+var hits = [1, 2]
+hits.Add(math.Max(3, hp))
+hits[0] = hits[0] + hits.Count()
+>@hits@ @foe.hp@ @loc.id@ @string.Size(\"abc\")@ @screen.w@ @rng % 10@
+?foe.distance < 5 & hp > 3
+  >close @foe@
+equipL sword
+activate potion
+var p = ui.AddButton()
+p.text = \"Go @hp@\"
+>@p.text@ @unknown.path.here@ @math.pi@
+", 4, |interpreter| {
+            interpreter.state.hp = 7;
+            interpreter.state.foe.distance = 3;
+        });
+    }
+
+    #[test]
+    fn test_runtime_errors() {
+        same("\
+func Divide(a, b)
+  return a / b
+func Outer(b)
+  return Divide(10, b)
+var x = Outer(time)
+>@x@
+");
+        same("var a = [1]\n>@a[time]@");
+        same("func F(a)\n  return a\nF(1, 2)");
+        same("var a = 1\na.Push(2)");
+        same("undeclared = 5");
+        same("Missing(1)");
+        same("var n = 0\nn = -\"text\"");
+        same("for x : 5\n  >x");
+        same("for i = 1..\"a\"\n  >i");
+        same("var s = \"a\"\ns++");
+        same("var a = 1\nvar b = a.field");
+        same("var a = [1]\na[0] = 2\na[5] = 3");
+        same("var a = [1]\na[0]()");
+        same("var n = 1\n5++");
+    }
+
+    #[test]
+    fn test_call_stack_overflow() {
+        same("func F(n)\n  return F(n + 1)\nF(0)");
+    }
+
+    #[test]
+    fn test_step_limit() {
+        differential("\
+var n = 0
+for i = 1..100
+  for j = 1..100
+    n++
+", 2, |interpreter| interpreter.set_step_limit(5_000));
+
+        differential("var n = 0\nfor i = 1..10\n  n++\n>@n@", 2, |interpreter| interpreter.set_step_limit(DEFAULT_STEP_LIMIT));
+    }
+
+    #[test]
+    fn test_lenient_mode() {
+        differential("\
+func Risky(value)
+  var result = 10 / value
+  >risky @result@
+  return result
+var total = 0
+for i = 0..2
+  total += Risky(i)
+  total = total + nope.Method()
+>total @total@
+undeclared = 1
+>done
+", 2, |interpreter| interpreter.set_lenient(true));
+    }
+
+    #[test]
+    fn test_location_change_resets_globals() {
+        let program = crate::parse("var n = 0\nn++\n>@n@").unwrap();
+        let mut vm = Vm::compile(&program).unwrap();
+        let mut interpreter = Interpreter::new();
+
+        interpreter.set_location("caves");
+
+        vm.tick(&mut interpreter).unwrap();
+        vm.tick(&mut interpreter).unwrap();
+
+        assert_eq!(vm.global("n"), Some(&RuntimeValue::Integer(2)));
+
+        interpreter.state.loc.id = "deadwood".to_string();
+        vm.tick(&mut interpreter).unwrap();
+
+        assert_eq!(interpreter.take_output(), vec!["1"]);
+        assert_eq!(vm.global("missing"), None);
+    }
+}
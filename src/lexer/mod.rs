@@ -10,7 +10,7 @@ pub struct ContentIterator<'a>  {
 }
 
 impl ContentIterator<'_> {
-    pub fn new(content: &str) -> ContentIterator {
+    pub fn new(content: &str) -> ContentIterator<'_> {
         ContentIterator {
             iterator: content.chars().peekable(),
             line: 1,
@@ -18,7 +18,20 @@ impl ContentIterator<'_> {
         }
     }
 
-    pub fn next(&mut self) -> Option<char> {
+    pub fn peek(&mut self) -> Option<&char> {
+        self.iterator.peek()
+    }
+
+    pub fn peek_second(&self) -> Option<char> {
+        self.iterator.clone().nth(1)
+    }
+}
+
+
+impl Iterator for ContentIterator<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
         match self.iterator.next() {
             Some(c) => {
                 if c == '\n' {
@@ -33,16 +46,30 @@ impl ContentIterator<'_> {
             None => None,
         }
     }
-
-    pub fn peek(&mut self) -> Option<&char> {
-        self.iterator.peek()
-    }
 }
 
 
+pub const COMMANDS: [&str; 10] = [
+    "equip",
+    "equipL",
+    "equipR",
+    "activate",
+    "loadout",
+    "brew",
+    "play",
+    "import",
+    "enable",
+    "disable",
+];
+
+// Commands handled by the script host, the rest of `COMMANDS` go to the game.
+pub const HOST_COMMANDS: [&str; 3] = ["import", "enable", "disable"];
+
+
 pub struct Lexer<'a> {
     content_iterator: ContentIterator<'a>,
     previous_token: Option<Token>,
+    pending_token: Option<Token>,
     line: usize,
     column: usize,
 }
@@ -52,6 +79,7 @@ impl<'a> Lexer<'a> {
         Lexer {
             content_iterator: ContentIterator::new(content),
             previous_token: None,
+            pending_token: None,
             line: 1,
             column: 0,
         }
@@ -66,6 +94,112 @@ impl Lexer<'_> {
             column: self.column,
         }
     }
+
+    fn at_line_start(&self) -> bool {
+        match &self.previous_token {
+            Some(token) => matches!(token.token_type, TokenType::EndLine | TokenType::NewLineIndent(_)),
+            None => true,
+        }
+    }
+
+    fn next_is(&mut self, expected: char) -> bool {
+        if self.content_iterator.peek() == Some(&expected) {
+            self.content_iterator.next();
+            return true;
+        }
+
+        false
+    }
+
+    fn lex_ascii_block(&mut self) -> String {
+        let mut ascii_block = "".to_string();
+
+        while let Some(&c) = self.content_iterator.peek() {
+            ascii_block.push(c);
+            self.content_iterator.next();
+
+            if ascii_block.ends_with("\nasciiend") {
+                ascii_block.truncate(ascii_block.len() - "\nasciiend".len());
+                break;
+            }
+        }
+
+        ascii_block
+    }
+
+    fn lex_text(&mut self) -> Option<Token> {
+        while self.content_iterator.peek() == Some(&' ') {
+            self.content_iterator.next();
+        }
+
+        let line = self.content_iterator.line;
+        let column = self.content_iterator.column + 1;
+
+        let mut text = "".to_string();
+
+        while let Some(&c) = self.content_iterator.peek() {
+            if c == '\n' {
+                break;
+            }
+
+            text.push(c);
+            self.content_iterator.next();
+        }
+
+        let is_ascii = text
+            .strip_suffix("ascii")
+            .is_some_and(|rest| !rest.ends_with(|c: char| c.is_alphanumeric()));
+
+        if is_ascii && self.content_iterator.peek() == Some(&'\n') {
+            self.content_iterator.next();
+            text.truncate(text.len() - "ascii".len());
+            text.push_str(&self.lex_ascii_block());
+        }
+
+        if text.is_empty() {
+            return None;
+        }
+
+        Some(Token { token_type: TokenType::Text(text), line, column })
+    }
+
+    fn lex_number(&mut self, c: char) -> TokenType {
+        let mut number = c.to_string();
+
+        while let Some(&c) = self.content_iterator.peek() {
+            let fraction = c == '.'
+                && !number.contains('.')
+                && self.content_iterator.peek_second().is_some_and(|c| c.is_ascii_digit());
+
+            if c.is_ascii_digit() || fraction {
+                number.push(c);
+                self.content_iterator.next();
+            } else {
+                break;
+            }
+        }
+
+        TokenType::Identifier(number)
+    }
+
+    fn lex_identifier(&mut self, c: char) -> TokenType {
+        let mut identifier = c.to_string();
+
+        while let Some(&c) = self.content_iterator.peek() {
+            if c.is_alphanumeric() || c == '_' {
+                identifier.push(c);
+                self.content_iterator.next();
+            } else if c == '\n' && identifier == "ascii" {
+                self.content_iterator.next();
+
+                return TokenType::AsciiBlock(self.lex_ascii_block());
+            } else {
+                break;
+            }
+        }
+
+        TokenType::Identifier(identifier)
+    }
 }
 
 
@@ -73,15 +207,26 @@ impl Iterator for Lexer<'_> {
     type Item = Token;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(token) = self.pending_token.take() {
+            self.previous_token = Some(token);
+            return self.previous_token.clone();
+        }
+
+        let at_line_start = self.at_line_start();
+
+        self.line = self.content_iterator.line;
+        self.column = self.content_iterator.column + 1;
+
         self.previous_token = match self.content_iterator.next() {
             Some(c) => {
                 match c {
-                    ' ' => {
-                        if self.previous_token.clone().is_some_and(|t| t.token_type == TokenType::EndLine) || self.previous_token == None {
+                    // A tab is one column of indentation.
+                    ' ' | '\t' => {
+                        if at_line_start {
                             let mut indent = 1;
 
                             while let Some(&c) = self.content_iterator.peek() {
-                                if c == ' ' {
+                                if c == ' ' || c == '\t' {
                                     indent += 1;
                                     self.content_iterator.next();
                                 } else {
@@ -94,6 +239,7 @@ impl Iterator for Lexer<'_> {
                             self.next()
                         }
                     },
+                    '\r' => self.next(),
                     '/' => {
                         match self.content_iterator.peek() {
                             Some('/') => {
@@ -135,6 +281,10 @@ impl Iterator for Lexer<'_> {
 
                                 Some(self.create_token(TokenType::CommentBlock(comment)))
                             },
+                            Some('=') => {
+                                self.content_iterator.next();
+                                Some(self.create_token(TokenType::DivideAssign))
+                            },
                             _ => Some(self.create_token(TokenType::Divide)),
                         }
                     },
@@ -158,7 +308,15 @@ impl Iterator for Lexer<'_> {
                                 self.content_iterator.next();
                                 Some(self.create_token(TokenType::GreaterEqual))
                             },
-                            _ => Some(self.create_token(TokenType::Greater)),
+                            _ => {
+                                let token = self.create_token(TokenType::Greater);
+
+                                if at_line_start {
+                                    self.pending_token = self.lex_text();
+                                }
+
+                                Some(token)
+                            },
                         }
                     },
                     '<' => {
@@ -176,6 +334,10 @@ impl Iterator for Lexer<'_> {
                                 self.content_iterator.next();
                                 Some(self.create_token(TokenType::Increment))
                             },
+                            Some('=') => {
+                                self.content_iterator.next();
+                                Some(self.create_token(TokenType::AddAssign))
+                            },
                             _ => Some(self.create_token(TokenType::Add)),
                         }
                     },
@@ -185,11 +347,31 @@ impl Iterator for Lexer<'_> {
                                 self.content_iterator.next();
                                 Some(self.create_token(TokenType::Decrement))
                             },
+                            Some('=') => {
+                                self.content_iterator.next();
+                                Some(self.create_token(TokenType::SubtractAssign))
+                            },
                             _ => Some(self.create_token(TokenType::Subtract)),
                         }
                     }
-                    '*' => Some(self.create_token(TokenType::Multiply)),
-                    '%' => Some(self.create_token(TokenType::Modulo)),
+                    '*' => {
+                        match self.next_is('=') {
+                            true => Some(self.create_token(TokenType::MultiplyAssign)),
+                            false => Some(self.create_token(TokenType::Multiply)),
+                        }
+                    },
+                    '%' => {
+                        match self.next_is('=') {
+                            true => Some(self.create_token(TokenType::ModuloAssign)),
+                            false => Some(self.create_token(TokenType::Modulo)),
+                        }
+                    },
+                    '.' => {
+                        match self.next_is('.') {
+                            true => Some(self.create_token(TokenType::Range)),
+                            false => Some(self.create_token(TokenType::Dot)),
+                        }
+                    },
                     '(' => Some(self.create_token(TokenType::ParenthesisOpen)),
                     ')' => Some(self.create_token(TokenType::ParenthesisClose)),
                     '[' => Some(self.create_token(TokenType::SquareBracketOpen)),
@@ -210,45 +392,28 @@ impl Iterator for Lexer<'_> {
 
                         Some(self.create_token(TokenType::String(string)))
                     },
-                    'a'..='z' | 'A'..='Z' => {
-                        let mut identifier = c.to_string();
-
-                        while let Some(&c) = self.content_iterator.peek() {
-                            if c.is_alphanumeric() {
-                                identifier.push(c);
-                                self.content_iterator.next();
-                            } else if c == '\n' {
-                                match identifier.as_str() {
-                                    "ascii" => {
-                                        self.content_iterator.next();
-
-                                        let mut ascii_block = "".to_string();
-
-                                        while let Some(&c) = self.content_iterator.peek() {
-                                            ascii_block.push(c);
-                                            self.content_iterator.next();
+                    '0'..='9' => {
+                        let token_type = self.lex_number(c);
+                        Some(self.create_token(token_type))
+                    },
+                    'a'..='z' | 'A'..='Z' | '_' => {
+                        let token_type = self.lex_identifier(c);
+                        let token = self.create_token(token_type);
 
-                                            if ascii_block.ends_with("\nasciiend") {
-                                                ascii_block.truncate(ascii_block.len() - "\nasciiend".len());
-                                                break;
-                                            }
-                                        }
+                        let is_command = at_line_start
+                            && matches!(&token.token_type, TokenType::Identifier(name) if COMMANDS.contains(&name.as_str()))
+                            && self.content_iterator.peek() == Some(&' ');
 
-                                        return Some(self.create_token(TokenType::AsciiBlock(ascii_block)));
-                                    },
-                                    _ => break,
-                                }
-                            } else {
-                                break;
-                            }
+                        if is_command {
+                            self.pending_token = self.lex_text();
                         }
 
-                        Some(self.create_token(TokenType::Identifier(identifier)))
+                        Some(token)
                     },
                     '\n' => {
                         Some(self.create_token(TokenType::EndLine))
                     },
-                    _ => panic!("Unexpected character: {}", c),
+                    _ => Some(self.create_token(TokenType::Unknown(c))),
                 }
             },
             None => None,
@@ -267,7 +432,7 @@ mod tests {
     fn test_empty() {
         let lexer = Lexer::new("");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(tokens.len(), 0);
     }
@@ -276,7 +441,7 @@ mod tests {
     fn test_identifier() {
         let lexer = Lexer::new("hello");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -290,7 +455,7 @@ mod tests {
     fn test_multiple_identifiers() {
         let lexer = Lexer::new("hello world");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -305,7 +470,7 @@ mod tests {
     fn test_newlines() {
         let lexer = Lexer::new("hello\nworld");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -321,7 +486,7 @@ mod tests {
     fn test_newlines_and_indent() {
         let lexer = Lexer::new("hello\n  world");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -334,11 +499,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tab_indent() {
+        let lexer = Lexer::new("?a\n\tb\n\t c\td");
+
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
+
+        assert_eq!(
+            tokens,
+            vec![
+                TokenType::If,
+                TokenType::Identifier("a".to_string()),
+                TokenType::EndLine,
+                TokenType::NewLineIndent(1),
+                TokenType::Identifier("b".to_string()),
+                TokenType::EndLine,
+                TokenType::NewLineIndent(2),
+                TokenType::Identifier("c".to_string()),
+                TokenType::Identifier("d".to_string()),
+            ]
+        );
+        assert_eq!(crate::parse("?a\n\tb()\nc()"), crate::parse("?a\n b()\nc()"));
+    }
+
     #[test]
     fn test_if() {
         let lexer = Lexer::new("?test");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -353,7 +541,7 @@ mod tests {
     fn test_if_else() {
         let lexer = Lexer::new("?test\n  hello\n:\n  world");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -376,7 +564,7 @@ mod tests {
     fn test_if_elseif_else() {
         let lexer = Lexer::new("?test1\n  hello\n:?\n  world\n  test2\n:\n  world");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -407,7 +595,7 @@ mod tests {
     fn test_comment() {
         let lexer = Lexer::new("// this is a comment");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -421,7 +609,7 @@ mod tests {
     fn test_comment_block() {
         let lexer = Lexer::new("/* this is a comment block */");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -435,7 +623,7 @@ mod tests {
     fn test_comment_block_with_newlines() {
         let lexer = Lexer::new("/* this is a comment block\nwith newlines */");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -449,7 +637,7 @@ mod tests {
     fn test_equal() {
         let lexer = Lexer::new("=");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -463,7 +651,7 @@ mod tests {
     fn test_not_equal() {
         let lexer = Lexer::new("!");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -477,7 +665,7 @@ mod tests {
     fn test_and() {
         let lexer = Lexer::new("&");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -491,7 +679,7 @@ mod tests {
     fn test_or() {
         let lexer = Lexer::new("|");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -505,7 +693,7 @@ mod tests {
     fn test_greater() {
         let lexer = Lexer::new(">");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -519,7 +707,7 @@ mod tests {
     fn test_less() {
         let lexer = Lexer::new("<");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -533,7 +721,7 @@ mod tests {
     fn test_greater_equal() {
         let lexer = Lexer::new(">=");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -547,7 +735,7 @@ mod tests {
     fn test_less_equal() {
         let lexer = Lexer::new("<=");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -561,7 +749,7 @@ mod tests {
     fn test_add() {
         let lexer = Lexer::new("+");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -575,7 +763,7 @@ mod tests {
     fn test_subtract() {
         let lexer = Lexer::new("-");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -589,7 +777,7 @@ mod tests {
     fn test_multiply() {
         let lexer = Lexer::new("*");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -603,7 +791,7 @@ mod tests {
     fn test_divide() {
         let lexer = Lexer::new("/");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -617,7 +805,7 @@ mod tests {
     fn test_increment() {
         let lexer = Lexer::new("++");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -631,7 +819,7 @@ mod tests {
     fn test_decrement() {
         let lexer = Lexer::new("--");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -645,7 +833,7 @@ mod tests {
     fn test_modulo() {
         let lexer = Lexer::new("%");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -659,7 +847,7 @@ mod tests {
     fn test_parenthesis_open() {
         let lexer = Lexer::new("(");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -673,7 +861,7 @@ mod tests {
     fn test_parenthesis_close() {
         let lexer = Lexer::new(")");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -687,7 +875,7 @@ mod tests {
     fn test_square_bracket_open() {
         let lexer = Lexer::new("[");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -702,7 +890,7 @@ mod tests {
     fn test_square_bracket_close() {
         let lexer = Lexer::new("]");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -716,7 +904,7 @@ mod tests {
     fn test_comma() {
         let lexer = Lexer::new(",");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -730,7 +918,7 @@ mod tests {
     fn test_ascii_block() {
         let lexer = Lexer::new("ascii\nhello\nasciiend");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -744,7 +932,7 @@ mod tests {
     fn test_string() {
         let lexer = Lexer::new("\"hello\"");
 
-        let tokens = lexer.collect::<Vec<Token>>();
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
 
         assert_eq!(
             tokens,
@@ -753,4 +941,166 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_positions() {
+        let lexer = Lexer::new("var a\n  b  + c");
+
+        let positions = lexer
+            .map(|token| (token.line, token.column))
+            .collect::<Vec<(usize, usize)>>();
+
+        assert_eq!(positions, vec![(1, 1), (1, 5), (1, 6), (2, 1), (2, 3), (2, 6), (2, 8)]);
+    }
+
+    #[test]
+    fn test_unknown_character() {
+        let lexer = Lexer::new("a $");
+
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
+
+        assert_eq!(
+            tokens,
+            vec![
+                TokenType::Identifier("a".to_string()),
+                TokenType::Unknown('$'),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_numbers() {
+        let lexer = Lexer::new("12 3.5 1..10");
+
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
+
+        assert_eq!(
+            tokens,
+            vec![
+                TokenType::Identifier("12".to_string()),
+                TokenType::Identifier("3.5".to_string()),
+                TokenType::Identifier("1".to_string()),
+                TokenType::Range,
+                TokenType::Identifier("10".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_member_access() {
+        let lexer = Lexer::new("foe.hp_max");
+
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
+
+        assert_eq!(
+            tokens,
+            vec![
+                TokenType::Identifier("foe".to_string()),
+                TokenType::Dot,
+                TokenType::Identifier("hp_max".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compound_assignment() {
+        let lexer = Lexer::new("a += 1 -= *= /= %=");
+
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
+
+        assert_eq!(
+            tokens,
+            vec![
+                TokenType::Identifier("a".to_string()),
+                TokenType::AddAssign,
+                TokenType::Identifier("1".to_string()),
+                TokenType::SubtractAssign,
+                TokenType::MultiplyAssign,
+                TokenType::DivideAssign,
+                TokenType::ModuloAssign,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_print_text() {
+        let lexer = Lexer::new("?a > b\n  >o1,2,Hello @a@!\n>");
+
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
+
+        assert_eq!(
+            tokens,
+            vec![
+                TokenType::If,
+                TokenType::Identifier("a".to_string()),
+                TokenType::Greater,
+                TokenType::Identifier("b".to_string()),
+                TokenType::EndLine,
+                TokenType::NewLineIndent(2),
+                TokenType::Greater,
+                TokenType::Text("o1,2,Hello @a@!".to_string()),
+                TokenType::EndLine,
+                TokenType::Greater,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_print_ascii() {
+        let lexer = Lexer::new(">o1,1,ascii\n/\\\n\\/\nasciiend\na");
+
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
+
+        assert_eq!(
+            tokens,
+            vec![
+                TokenType::Greater,
+                TokenType::Text("o1,1,/\\\n\\/".to_string()),
+                TokenType::EndLine,
+                TokenType::Identifier("a".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_command_text() {
+        let lexer = Lexer::new("equip vigor crossbow *8 +5\nequipped = 1");
+
+        let tokens = lexer.map(|token| token.token_type).collect::<Vec<TokenType>>();
+
+        assert_eq!(
+            tokens,
+            vec![
+                TokenType::Identifier("equip".to_string()),
+                TokenType::Text("vigor crossbow *8 +5".to_string()),
+                TokenType::EndLine,
+                TokenType::Identifier("equipped".to_string()),
+                TokenType::Equal,
+                TokenType::Identifier("1".to_string()),
+            ]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_json_shape() {
+        let tokens = vec![
+            Token { token_type: TokenType::Identifier("hp".to_string()), line: 1, column: 1 },
+            Token { token_type: TokenType::LessEqual, line: 1, column: 4 },
+            Token { token_type: TokenType::NewLineIndent(2), line: 2, column: 1 },
+        ];
+
+        let json = serde_json::to_value(&tokens).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!([
+                { "type": "identifier", "value": "hp", "line": 1, "column": 1 },
+                { "type": "less_equal", "line": 1, "column": 4 },
+                { "type": "new_line_indent", "value": 2, "line": 2, "column": 1 },
+            ])
+        );
+
+        assert_eq!(serde_json::from_value::<Vec<Token>>(json).unwrap(), tokens);
+    }
 }
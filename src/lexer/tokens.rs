@@ -1,4 +1,11 @@
+use std::fmt;
+
+use crate::diagnostics::Span;
+
+
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value", rename_all = "snake_case"))]
 pub enum TokenType {
     NewLineIndent(u32),
     EndLine,
@@ -27,6 +34,12 @@ pub enum TokenType {
     Decrement,
     Modulo,
 
+    AddAssign,
+    SubtractAssign,
+    MultiplyAssign,
+    DivideAssign,
+    ModuloAssign,
+
     ParenthesisOpen,
     ParenthesisClose,
 
@@ -34,23 +47,102 @@ pub enum TokenType {
     SquareBracketClose,
 
     Comma,
+    Dot,
+    Range,
 
     AsciiBlock(String),
+    Text(String),
 
     Identifier(String),
 
     String(String),
+
+    Unknown(char),
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Token {
+    #[cfg_attr(feature = "serde", serde(flatten))]
     pub token_type: TokenType,
     pub line: usize,
     pub column: usize,
 }
 
-impl PartialEq<TokenType> for Token {
-    fn eq(&self, other: &TokenType) -> bool {
-        self.token_type == *other
+
+impl Token {
+    pub fn span(&self) -> Span {
+        Span::new(self.line, self.column, self.token_type.width())
+    }
+}
+
+
+impl TokenType {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            TokenType::If => "?",
+            TokenType::Else => ":",
+            TokenType::ElseIf => ":?",
+            TokenType::Equal => "=",
+            TokenType::NotEqual => "!",
+            TokenType::And => "&",
+            TokenType::Or => "|",
+            TokenType::Greater => ">",
+            TokenType::Less => "<",
+            TokenType::GreaterEqual => ">=",
+            TokenType::LessEqual => "<=",
+            TokenType::Add => "+",
+            TokenType::Subtract => "-",
+            TokenType::Multiply => "*",
+            TokenType::Divide => "/",
+            TokenType::Increment => "++",
+            TokenType::Decrement => "--",
+            TokenType::Modulo => "%",
+            TokenType::AddAssign => "+=",
+            TokenType::SubtractAssign => "-=",
+            TokenType::MultiplyAssign => "*=",
+            TokenType::DivideAssign => "/=",
+            TokenType::ModuloAssign => "%=",
+            TokenType::ParenthesisOpen => "(",
+            TokenType::ParenthesisClose => ")",
+            TokenType::SquareBracketOpen => "[",
+            TokenType::SquareBracketClose => "]",
+            TokenType::Comma => ",",
+            TokenType::Dot => ".",
+            TokenType::Range => "..",
+            _ => "",
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        match self {
+            TokenType::NewLineIndent(indent) => *indent as usize,
+            TokenType::EndLine => 1,
+            TokenType::Comment(comment) => comment.chars().count() + 2,
+            TokenType::CommentBlock(comment) => comment.lines().next().unwrap_or("").chars().count() + 2,
+            TokenType::AsciiBlock(_) => "ascii".len(),
+            TokenType::Identifier(name) => name.chars().count(),
+            TokenType::Text(text) => text.lines().next().unwrap_or("").chars().count(),
+            TokenType::String(value) => value.chars().count() + 2,
+            TokenType::Unknown(_) => 1,
+            _ => self.symbol().len(),
+        }
+    }
+}
+
+
+impl fmt::Display for TokenType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenType::NewLineIndent(_) => f.write_str("indentation"),
+            TokenType::EndLine => f.write_str("end of line"),
+            TokenType::Comment(_) | TokenType::CommentBlock(_) => f.write_str("comment"),
+            TokenType::AsciiBlock(_) => f.write_str("ascii block"),
+            TokenType::Text(_) => f.write_str("text"),
+            TokenType::Identifier(name) => write!(f, "`{}`", name),
+            TokenType::String(value) => write!(f, "string \"{}\"", value),
+            TokenType::Unknown(c) => write!(f, "`{}`", c),
+            _ => write!(f, "`{}`", self.symbol()),
+        }
     }
 }
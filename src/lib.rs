@@ -0,0 +1,73 @@
+//! Lexer, parser and tooling for Stone Story RPG's Stonescript.
+//!
+//! ```
+//! let tree = stonescript::parse("a + b * c").unwrap();
+//!
+//! assert_eq!(tree.to_string(), "a + b * c");
+//! ```
+
+pub mod lexer;
+pub mod parser;
+pub mod formatter;
+pub mod diagnostics;
+pub mod interpreter;
+
+pub use diagnostics::{Diagnostic, Label, Renderer, Severity, Span};
+pub use formatter::{format, FormatOptions};
+pub use interpreter::Interpreter;
+pub use interpreter::state::GameState;
+pub use interpreter::value::RuntimeValue;
+pub use lexer::tokens::{Token, TokenType};
+pub use parser::structs::{BinaryOperator, Expression, UnaryOperator, Value};
+
+
+pub fn tokenize(source: &str) -> Vec<Token> {
+    lexer::Lexer::new(source).collect()
+}
+
+
+pub fn parse(source: &str) -> Result<Expression, Diagnostic> {
+    parser::parse(&tokenize(source))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("a + b").into_iter().map(|token| token.token_type).collect::<Vec<TokenType>>(),
+            vec![
+                TokenType::Identifier("a".to_string()),
+                TokenType::Add,
+                TokenType::Identifier("b".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("a * b"),
+            Ok(Expression::Block {
+                statements: vec![
+                    Expression::Binary {
+                        left: Box::new(Expression::Identifier("a".to_string())),
+                        operator: BinaryOperator::Multiply,
+                        right: Box::new(Expression::Identifier("b".to_string())),
+                    }
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let diagnostic = parse("a $").unwrap_err();
+
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!(diagnostic.span(), Some(Span::new(1, 3, 1)));
+    }
+}
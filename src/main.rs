@@ -1,21 +1,11 @@
 use std::env;
-use std::fs;
+use std::process::ExitCode;
 
-pub mod lexer;
-pub mod parser;
+mod cli;
 
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    let filename = &args[1];
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
 
-    let file_content = fs::read_to_string(filename)
-        .expect("Something went wrong reading the file");
-
-    let lexer = lexer::Lexer::new(&file_content);
-    let tokens = lexer.collect::<Vec<lexer::tokens::Token>>();
-    println!("Tokens: {:?}", tokens);
-
-    let syntax_tree = parser::parse(&tokens);
-    println!("Syntax Tree: {:?}", syntax_tree);
+    cli::main(&args)
 }
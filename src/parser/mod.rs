@@ -1,22 +1,85 @@
 pub mod structs;
-pub mod parse;
-pub mod types;
+pub mod visit;
+pub(crate) mod parse;
+pub(crate) mod types;
+mod printer;
 
-use crate::lexer::tokens::Token;
+use crate::diagnostics::Diagnostic;
+use crate::lexer::tokens::{Token, TokenType};
 use structs::Expression;
 use parse::block::parse_expression_block;
 
 
-pub fn parse(tokens: &[Token]) -> Expression {
+// Brackets are parsed recursively, so nesting is capped well below what the native stack can hold.
+const MAX_NESTING_DEPTH: usize = 100;
+
+
+pub fn parse(tokens: &[Token]) -> Result<Expression, Diagnostic> {
+    let mut depth = 0usize;
+
+    for token in tokens {
+        match token.token_type {
+            TokenType::Unknown(c) => {
+                return Err(
+                    Diagnostic::error(format!("unexpected character `{}`", c))
+                        .with_primary_label(token.span(), "not valid here")
+                );
+            },
+            TokenType::ParenthesisOpen | TokenType::SquareBracketOpen => depth += 1,
+            TokenType::ParenthesisClose | TokenType::SquareBracketClose => depth = depth.saturating_sub(1),
+            _ => {},
+        }
+
+        if depth > MAX_NESTING_DEPTH {
+            return Err(
+                Diagnostic::error("expression is nested too deeply")
+                    .with_primary_label(token.span(), format!("more than {} levels of brackets", MAX_NESTING_DEPTH))
+            );
+        }
+    }
+
     parse_expression_block(tokens)
 }
 
 
+// Lets trees parsed from differently laid out sources be compared.
+pub(crate) fn without_spans(expression: Expression) -> Expression {
+    use crate::diagnostics::Span;
+    use visit::fold::{walk_fold_expression, Fold};
+
+    struct ClearSpans;
+
+    impl Fold for ClearSpans {
+        fn fold_expression(&mut self, expression: Expression) -> Expression {
+            let mut expression = walk_fold_expression(self, expression);
+
+            match &mut expression {
+                Expression::Assignment { span, .. }
+                | Expression::Reassignment { span, .. }
+                | Expression::Condition { span, .. }
+                | Expression::Call { span, .. }
+                | Expression::New { span, .. }
+                | Expression::ForRange { span, .. }
+                | Expression::ForEach { span, .. }
+                | Expression::Return { span, .. }
+                | Expression::Print { span, .. }
+                | Expression::Command { span, .. } => *span = Span::default(),
+                _ => {},
+            }
+
+            expression
+        }
+    }
+
+    ClearSpans.fold_expression(expression)
+}
+
+
 #[cfg(test)]
 mod tests {
     use std::vec;
 
-    use crate::{lexer::tokens::TokenType, parser::structs::{BinaryOperator, UnaryOperator, Value}};
+    use crate::{diagnostics::Span, parser::structs::{BinaryOperator, UnaryOperator, Value}};
 
     use super::*;
 
@@ -29,7 +92,7 @@ mod tests {
         ];
 
         assert_eq!(
-            parse(&tokens),
+            parse(&tokens).unwrap(),
             Expression::Block {
                 statements: vec![
                     Expression::Binary {
@@ -53,17 +116,17 @@ mod tests {
         ];
 
         assert_eq!(
-            parse(&tokens),
+            parse(&tokens).unwrap(),
             Expression::Block {
                 statements: vec![
                     Expression::Binary {
-                        left: Box::new(Expression::Value(Value::Integer(1))),
-                        operator: BinaryOperator::Add,
-                        right: Box::new(Expression::Binary {
-                            left: Box::new(Expression::Value(Value::Integer(2))),
-                            operator: BinaryOperator::Subtract,
-                            right: Box::new(Expression::Value(Value::Integer(3))),
+                        left: Box::new(Expression::Binary {
+                            left: Box::new(Expression::Value(Value::Integer(1))),
+                            operator: BinaryOperator::Add,
+                            right: Box::new(Expression::Value(Value::Integer(2))),
                         }),
+                        operator: BinaryOperator::Subtract,
+                        right: Box::new(Expression::Value(Value::Integer(3))),
                     }
                 ]
             }
@@ -81,7 +144,7 @@ mod tests {
         ];
 
         assert_eq!(
-            parse(&tokens),
+            parse(&tokens).unwrap(),
             Expression::Block {
                 statements: vec![
                     Expression::Binary {
@@ -111,21 +174,21 @@ mod tests {
         ];
 
         assert_eq!(
-            parse(&tokens),
+            parse(&tokens).unwrap(),
             Expression::Block {
                 statements: vec![
                     Expression::Binary {
                         left: Box::new(Expression::Binary {
-                            left: Box::new(Expression::Value(Value::Integer(1))),
-                            operator: BinaryOperator::Multiply,
-                            right: Box::new(Expression::Value(Value::Integer(2))),
-                        }),
-                        operator: BinaryOperator::Add,
-                        right: Box::new(Expression::Binary {
-                            left: Box::new(Expression::Value(Value::Integer(3))),
+                            left: Box::new(Expression::Binary {
+                                left: Box::new(Expression::Value(Value::Integer(1))),
+                                operator: BinaryOperator::Multiply,
+                                right: Box::new(Expression::Value(Value::Integer(2))),
+                            }),
                             operator: BinaryOperator::Add,
-                            right: Box::new(Expression::Value(Value::Integer(4))),
+                            right: Box::new(Expression::Value(Value::Integer(3))),
                         }),
+                        operator: BinaryOperator::Add,
+                        right: Box::new(Expression::Value(Value::Integer(4))),
                     }
                 ]
             }
@@ -140,7 +203,7 @@ mod tests {
         ];
 
         assert_eq!(
-            parse(&tokens),
+            parse(&tokens).unwrap(),
             Expression::Block {
                 statements: vec![
                     Expression::Nope,
@@ -158,7 +221,7 @@ mod tests {
         ];
 
         assert_eq!(
-            parse(&tokens),
+            parse(&tokens).unwrap(),
             Expression::Block {
                 statements: vec![
                     Expression::Value(Value::Integer(1)),
@@ -178,7 +241,7 @@ mod tests {
         ];
 
         assert_eq!(
-            parse(&tokens),
+            parse(&tokens).unwrap(),
             Expression::Block {
                 statements: vec![
                     Expression::Binary {
@@ -199,7 +262,7 @@ mod tests {
         ];
 
         assert_eq!(
-            parse(&tokens),
+            parse(&tokens).unwrap(),
             Expression::Block {
                 statements: vec![
                     Expression::Unary {
@@ -226,7 +289,7 @@ mod tests {
         ];
 
         assert_eq!(
-            parse(&tokens),
+            parse(&tokens).unwrap(),
             Expression::Block {
                 statements: vec![
                     Expression::Binary {
@@ -252,7 +315,7 @@ mod tests {
         ];
 
         assert_eq!(
-            parse(&tokens),
+            parse(&tokens).unwrap(),
             Expression::Block {
                 statements: vec![
                     Expression::Function {
@@ -282,7 +345,7 @@ mod tests {
         ];
 
         assert_eq!(
-            parse(&tokens),
+            parse(&tokens).unwrap(),
             Expression::Block {
                 statements: vec![
                     Expression::Function {
@@ -298,4 +361,253 @@ mod tests {
             }
         )
     }
+
+    #[test]
+    fn test_error_unexpected_character() {
+        let tokens = vec![
+            Token { token_type: TokenType::Identifier("a".to_string()), line: 1, column: 1 },
+            Token { token_type: TokenType::Unknown('$'), line: 1, column: 3 },
+        ];
+
+        let diagnostic = parse(&tokens).unwrap_err();
+
+        assert_eq!(diagnostic.message, "unexpected character `$`");
+        assert_eq!(diagnostic.span(), Some(Span::new(1, 3, 1)));
+    }
+
+    #[test]
+    fn test_error_function_parameters() {
+        let tokens = vec![
+            Token { token_type: TokenType::Identifier("func".to_string()), line: 1, column: 1 },
+            Token { token_type: TokenType::Identifier("test".to_string()), line: 1, column: 6 },
+            Token { token_type: TokenType::ParenthesisOpen, line: 1, column: 10 },
+            Token { token_type: TokenType::Add, line: 1, column: 11 },
+        ];
+
+        let diagnostic = parse(&tokens).unwrap_err();
+
+        assert_eq!(diagnostic.message, "expected parameter name or `)`, found `+`");
+        assert_eq!(diagnostic.span(), Some(Span::new(1, 11, 1)));
+    }
+
+    #[test]
+    fn test_error_end_of_input() {
+        let tokens = vec![
+            Token { token_type: TokenType::Identifier("func".to_string()), line: 1, column: 1 },
+            Token { token_type: TokenType::Identifier("test".to_string()), line: 1, column: 6 },
+        ];
+
+        let diagnostic = parse(&tokens).unwrap_err();
+
+        assert_eq!(diagnostic.message, "expected `(`, found end of input");
+        assert_eq!(diagnostic.span(), Some(Span::new(1, 10, 1)));
+    }
+
+    fn parse_source(source: &str) -> Expression {
+        crate::parse(source).unwrap()
+    }
+
+    #[test]
+    fn test_left_associative() {
+        assert_eq!(
+            parse_source("a - b - c"),
+            Expression::Block {
+                statements: vec![
+                    Expression::Binary {
+                        left: Box::new(Expression::Binary {
+                            left: Box::new(Expression::Identifier("a".to_string())),
+                            operator: BinaryOperator::Subtract,
+                            right: Box::new(Expression::Identifier("b".to_string())),
+                        }),
+                        operator: BinaryOperator::Subtract,
+                        right: Box::new(Expression::Identifier("c".to_string())),
+                    }
+                ]
+            }
+        );
+
+        assert_eq!(parse_source("x = a | b & c = d + -1").to_string(), "x = a | b & c = d + -1");
+        assert_eq!(parse_source("y = !(a & b) | !c").to_string(), "y = !(a & b) | !c");
+    }
+
+    #[test]
+    fn test_condition_chain() {
+        let source = "?hp < 10\n  a\n\n:? foe = boss\n  ?b\n    c\n  :\n    d\n:\n  e\nf";
+
+        assert_eq!(
+            parse_source(source).to_string(),
+            "?hp < 10\n  a\n:?foe = boss\n  ?b\n    c\n  :\n    d\n:\n  e\nf"
+        );
+    }
+
+    #[test]
+    fn test_calls_members_and_arrays() {
+        let expression = parse_source("x = math.Max(a.b[i + 1], -c)\narr = [1, \"two\", [3]]\nv.y -= arr[0].Count()");
+
+        assert_eq!(
+            expression.to_string(),
+            "x = math.Max(a.b[i + 1], -c)\narr = [1, \"two\", [3]]\nv.y -= arr[0].Count()"
+        );
+
+        match expression {
+            Expression::Block { statements } => {
+                assert!(matches!(
+                    &statements[0],
+                    Expression::Reassignment { operator: None, span, .. } if *span == Span::new(1, 1, 28)
+                ));
+            },
+            _ => panic!("Expected block"),
+        }
+    }
+
+    #[test]
+    fn test_loops_and_functions() {
+        let source = "func Sum(list, from)\n  var total = from\n  for v : list\n    ?v = 0\n      continue\n    total += v\n  return total\nfor i = 1..n\n  break";
+
+        assert_eq!(
+            parse_source(source).to_string(),
+            "func Sum(list, from)\n  var total = from\n  for v : list\n    ?v = 0\n      continue\n    total += v\n  return total\nfor i = 1..n\n  break"
+        );
+    }
+
+    #[test]
+    fn test_print_and_commands() {
+        let expression = parse_source(">hp: @hp@\n  // comment\nequip vigor sword\nactivate potion");
+
+        assert_eq!(
+            expression,
+            Expression::Block {
+                statements: vec![
+                    Expression::Print { text: "hp: @hp@".to_string(), span: Span::new(1, 1, 9) },
+                    Expression::Command {
+                        name: "equip".to_string(),
+                        arguments: "vigor sword".to_string(),
+                        span: Span::new(3, 1, 17),
+                    },
+                    Expression::Command {
+                        name: "activate".to_string(),
+                        arguments: "potion".to_string(),
+                        span: Span::new(4, 1, 15),
+                    },
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn test_new_and_this() {
+        let expression = parse_source("var c = new Components/Counter\nthis.count += 1");
+
+        assert_eq!(
+            expression,
+            Expression::Block {
+                statements: vec![
+                    Expression::Assignment {
+                        name: "c".to_string(),
+                        value: Box::new(Expression::New {
+                            path: "Components/Counter".to_string(),
+                            span: Span::new(1, 9, 22),
+                        }),
+                        span: Span::new(1, 1, 30),
+                    },
+                    Expression::Reassignment {
+                        target: Box::new(Expression::Member {
+                            target: Box::new(Expression::This),
+                            name: "count".to_string(),
+                        }),
+                        operator: Some(BinaryOperator::Add),
+                        value: Box::new(Expression::Value(Value::Integer(1))),
+                        span: Span::new(2, 1, 15),
+                    },
+                ]
+            }
+        );
+
+        assert_eq!(expression.to_string(), "var c = new Components/Counter\nthis.count += 1");
+        assert!(crate::parse("var c = new Components/(Counter)").is_err());
+    }
+
+    #[test]
+    fn test_error_missing_operand() {
+        let diagnostic = crate::parse("a = b +").unwrap_err();
+
+        assert_eq!(diagnostic.message, "expected expression after `+`");
+        assert_eq!(diagnostic.span(), Some(Span::new(1, 7, 1)));
+
+        let diagnostic = crate::parse("a\n:\n  b").unwrap_err();
+
+        assert_eq!(diagnostic.message, "`:` without a matching `?`");
+    }
+
+    #[test]
+    fn test_long_chains_and_deep_nesting() {
+        let source = format!("var a = {}", vec!["1"; 5000].join(" - "));
+
+        let Ok(Expression::Block { statements }) = crate::parse(&source) else {
+            panic!("expected a block");
+        };
+
+        let Expression::Assignment { value, .. } = &statements[0] else {
+            panic!("expected an assignment");
+        };
+
+        assert!(matches!(**value, Expression::Binary { operator: BinaryOperator::Subtract, ref right, .. } if **right == Expression::Value(Value::Integer(1))));
+
+        let source = format!("var a = {}1{}", "(".repeat(101), ")".repeat(101));
+        let diagnostic = crate::parse(&source).unwrap_err();
+
+        assert_eq!(diagnostic.message, "expression is nested too deeply");
+        assert_eq!(diagnostic.span(), Some(Span::new(1, 109, 1)));
+        assert!(crate::parse(&format!("var a = {}1{}", "[".repeat(100), "]".repeat(100))).is_ok());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_json_shape() {
+        let expression = Expression::Block {
+            statements: vec![
+                Expression::Assignment {
+                    name: "a".to_string(),
+                    value: Box::new(Expression::Binary {
+                        left: Box::new(Expression::Value(Value::Integer(1))),
+                        operator: BinaryOperator::GreaterEqual,
+                        right: Box::new(Expression::Identifier("b".to_string())),
+                    }),
+                    span: Span::new(1, 1, 12),
+                },
+                Expression::Nope,
+            ],
+        };
+
+        let json = serde_json::to_value(&expression).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "type": "block",
+                "value": {
+                    "statements": [
+                        {
+                            "type": "assignment",
+                            "value": {
+                                "name": "a",
+                                "value": {
+                                    "type": "binary",
+                                    "value": {
+                                        "left": { "type": "value", "value": { "type": "integer", "value": 1 } },
+                                        "operator": "greater_equal",
+                                        "right": { "type": "identifier", "value": "b" },
+                                    },
+                                },
+                                "span": { "line": 1, "column": 1, "length": 12 },
+                            },
+                        },
+                        { "type": "nope" },
+                    ],
+                },
+            })
+        );
+
+        assert_eq!(serde_json::from_value::<Expression>(json).unwrap(), expression);
+    }
 }
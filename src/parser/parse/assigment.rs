@@ -1,24 +1,99 @@
-use crate::{lexer::tokens::TokenType, parser::{structs::Expression, types::TokenIterator}};
+use crate::{diagnostics::Diagnostic, lexer::tokens::TokenType, parser::{structs::{BinaryOperator, Expression}, types::{tokens_span, unexpected_token, ParseResult, TokenIterator}}};
 
-use super::primary::parse_primary_expression;
+use super::primary::{find_top_level, int_parse_primary_expression};
+
+
+const ASSIGNMENT_OPERATORS: [TokenType; 6] = [
+    TokenType::Equal,
+    TokenType::AddAssign,
+    TokenType::SubtractAssign,
+    TokenType::MultiplyAssign,
+    TokenType::DivideAssign,
+    TokenType::ModuloAssign,
+];
 
 
 pub fn parse_assigment_expression<'a>(
     mut iterator: TokenIterator<'a>,
-) -> (TokenIterator<'a>, Option<Expression>) {
-    let name = match iterator.next_some().token_type {
-        TokenType::Identifier(name) => name.clone(),
-        _ => panic!("Expected identifier"),
+) -> ParseResult<'a, Expression> {
+    let keyword = iterator.next_expected(TokenType::Identifier("var".to_string()))?;
+
+    let token = iterator.next_some()?;
+
+    let name = match token.token_type {
+        TokenType::Identifier(ref name) => name.clone(),
+        _ => return Err(unexpected_token(&token, "variable name")),
     };
 
-    iterator.next_expected(TokenType::Equal);
+    let tokens = iterator.take_line();
+    let span = keyword.span().to(tokens.last().map_or(token.span(), |last| last.span()));
+
+    let value = match tokens.first() {
+        None => Expression::Nope,
+        Some(first) if first.token_type == TokenType::Equal => {
+            if tokens.len() == 1 {
+                return Err(unexpected_token(first, "expression"));
+            }
 
-    let (new_iter, value) = parse_primary_expression(iterator);
+            int_parse_primary_expression(&tokens[1..])?
+        },
+        Some(first) => return Err(unexpected_token(first, "`=`")),
+    };
 
     let expression = Expression::Assignment {
         name,
         value: Box::new(value),
+        span,
     };
 
-    (new_iter, Some(expression))
+    Ok((iterator, expression))
+}
+
+
+pub fn parse_statement_line<'a>(
+    mut iterator: TokenIterator<'a>,
+) -> ParseResult<'a, Expression> {
+    let tokens = iterator.take_line();
+
+    let index = match find_top_level(&tokens, &ASSIGNMENT_OPERATORS) {
+        Some(index) if index > 0 => index,
+        _ => return Ok((iterator, int_parse_primary_expression(&tokens)?)),
+    };
+
+    let target = int_parse_primary_expression(&tokens[0..index])?;
+
+    if !matches!(target, Expression::Identifier(_) | Expression::Member { .. } | Expression::Index { .. }) {
+        return Err(
+            Diagnostic::error("invalid assignment target")
+                .with_primary_label(tokens_span(&tokens[0..index]), "cannot assign to this expression")
+        );
+    }
+
+    if index + 1 == tokens.len() {
+        return Err(
+            Diagnostic::error("expected expression after assignment")
+                .with_primary_label(tokens[index].span(), "nothing is assigned here")
+        );
+    }
+
+    let operator = match tokens[index].token_type {
+        TokenType::AddAssign => Some(BinaryOperator::Add),
+        TokenType::SubtractAssign => Some(BinaryOperator::Subtract),
+        TokenType::MultiplyAssign => Some(BinaryOperator::Multiply),
+        TokenType::DivideAssign => Some(BinaryOperator::Divide),
+        TokenType::ModuloAssign => Some(BinaryOperator::Modulo),
+        _ => None,
+    };
+
+    let value = int_parse_primary_expression(&tokens[index + 1..])?;
+
+    Ok((
+        iterator,
+        Expression::Reassignment {
+            target: Box::new(target),
+            operator,
+            value: Box::new(value),
+            span: tokens_span(&tokens),
+        },
+    ))
 }
@@ -1,22 +1,24 @@
+use crate::diagnostics::Diagnostic;
 use crate::lexer::tokens::{Token, TokenType};
 use crate::parser::structs::BinaryOperator;
+use crate::parser::types::unexpected_token;
 
 
-pub fn parse_binary_operator(token: &Token) -> BinaryOperator {
+pub fn parse_binary_operator(token: &Token) -> Result<BinaryOperator, Diagnostic> {
     match token.token_type {
-        TokenType::Add => BinaryOperator::Add,
-        TokenType::Subtract => BinaryOperator::Subtract,
-        TokenType::Multiply => BinaryOperator::Multiply,
-        TokenType::Divide => BinaryOperator::Divide,
-        TokenType::Modulo => BinaryOperator::Modulo,
-        TokenType::Equal => BinaryOperator::Equal,
-        TokenType::NotEqual => BinaryOperator::NotEqual,
-        TokenType::And => BinaryOperator::And,
-        TokenType::Or => BinaryOperator::Or,
-        TokenType::Greater => BinaryOperator::Greater,
-        TokenType::Less => BinaryOperator::Less,
-        TokenType::GreaterEqual => BinaryOperator::GreaterEqual,
-        TokenType::LessEqual => BinaryOperator::LessEqual,
-        _ => panic!("Expected binary operator"),
+        TokenType::Add => Ok(BinaryOperator::Add),
+        TokenType::Subtract => Ok(BinaryOperator::Subtract),
+        TokenType::Multiply => Ok(BinaryOperator::Multiply),
+        TokenType::Divide => Ok(BinaryOperator::Divide),
+        TokenType::Modulo => Ok(BinaryOperator::Modulo),
+        TokenType::Equal => Ok(BinaryOperator::Equal),
+        TokenType::NotEqual => Ok(BinaryOperator::NotEqual),
+        TokenType::And => Ok(BinaryOperator::And),
+        TokenType::Or => Ok(BinaryOperator::Or),
+        TokenType::Greater => Ok(BinaryOperator::Greater),
+        TokenType::Less => Ok(BinaryOperator::Less),
+        TokenType::GreaterEqual => Ok(BinaryOperator::GreaterEqual),
+        TokenType::LessEqual => Ok(BinaryOperator::LessEqual),
+        _ => Err(unexpected_token(token, "binary operator")),
     }
 }
@@ -1,25 +1,70 @@
-use crate::{lexer::tokens::Token, parser::{structs::Expression, types::TokenIterator}};
+use crate::{diagnostics::Diagnostic, lexer::tokens::{Token, TokenType}, parser::{structs::Expression, types::{ParseResult, TokenIterator}}};
 
 use super::expression::parse_expression;
 
 
-pub fn parse_expression_block(tokens: &[Token]) -> Expression {
-    let mut iterator = TokenIterator::new(tokens.iter().peekable());
+pub fn parse_statements<'a>(
+    mut iterator: TokenIterator<'a>,
+    indent: u32,
+) -> ParseResult<'a, Vec<Expression>> {
+    let mut statements = vec![];
 
-    let mut expressions = vec![];
+    loop {
+        iterator.skip_blank_lines();
 
-    while iterator.peek().is_some() {
-        let (new_iter, expression) = parse_expression(iterator);
+        let token = match iterator.peek() {
+            Some(token) => token.clone(),
+            None => break,
+        };
 
+        match token.token_type {
+            TokenType::NewLineIndent(current) if current == indent => {
+                iterator.next();
+            },
+            TokenType::NewLineIndent(current) if current < indent => break,
+            TokenType::NewLineIndent(_) => {
+                return Err(
+                    Diagnostic::error("unexpected indentation")
+                        .with_primary_label(token.span(), "this line is indented deeper than the block")
+                );
+            },
+            _ if indent > 0 => break,
+            _ => {},
+        }
+
+        let (new_iter, statement) = parse_expression(iterator, indent)?;
         iterator = new_iter;
 
-        match expression {
-            Some(expression) => expressions.push(expression),
-            None => continue,
+        if let Some(statement) = statement {
+            statements.push(statement);
         }
     }
 
-    Expression::Block {
-        statements: expressions,
-    }
-}
\ No newline at end of file
+    Ok((iterator, statements))
+}
+
+
+pub fn parse_indented_block<'a>(
+    mut iterator: TokenIterator<'a>,
+    parent_indent: u32,
+) -> ParseResult<'a, Expression> {
+    iterator.skip_blank_lines();
+
+    let indent = match iterator.peek().map(|token| &token.token_type) {
+        Some(TokenType::NewLineIndent(indent)) if *indent > parent_indent => *indent,
+        _ => return Ok((iterator, Expression::Block { statements: vec![] })),
+    };
+
+    let (iterator, statements) = parse_statements(iterator, indent)?;
+
+    Ok((iterator, Expression::Block { statements }))
+}
+
+
+pub fn parse_expression_block(tokens: &[Token]) -> Result<Expression, Diagnostic> {
+    let iterator = TokenIterator::new(tokens.iter().peekable());
+
+    let (_, statements) = parse_statements(iterator, 0)?;
+
+    Ok(Expression::Block { statements })
+}
@@ -0,0 +1,49 @@
+use crate::{diagnostics::Span, lexer::tokens::TokenType, parser::{structs::Expression, types::{unexpected_token, ParseResult, TokenIterator}}};
+
+
+fn take_text(iterator: &mut TokenIterator) -> Option<(String, Span)> {
+    match iterator.peek().map(|token| token.token_type.clone()) {
+        Some(TokenType::Text(text)) => {
+            let span = iterator.next().map(|token| token.span()).unwrap_or_default();
+            Some((text, span))
+        },
+        _ => None,
+    }
+}
+
+
+pub fn parse_print_expression<'a>(
+    mut iterator: TokenIterator<'a>,
+) -> ParseResult<'a, Expression> {
+    let token = iterator.next_expected(TokenType::Greater)?;
+
+    let (text, span) = match take_text(&mut iterator) {
+        Some((text, span)) => (text, token.span().to(span)),
+        None => (String::new(), token.span()),
+    };
+
+    iterator.expect_line_end()?;
+
+    Ok((iterator, Expression::Print { text, span }))
+}
+
+
+pub fn parse_command_expression<'a>(
+    mut iterator: TokenIterator<'a>,
+) -> ParseResult<'a, Expression> {
+    let token = iterator.next_some()?;
+
+    let name = match token.token_type {
+        TokenType::Identifier(ref name) => name.clone(),
+        _ => return Err(unexpected_token(&token, "command")),
+    };
+
+    let (arguments, span) = match take_text(&mut iterator) {
+        Some((text, span)) => (text, token.span().to(span)),
+        None => (String::new(), token.span()),
+    };
+
+    iterator.expect_line_end()?;
+
+    Ok((iterator, Expression::Command { name, arguments, span }))
+}
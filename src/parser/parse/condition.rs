@@ -1,8 +1,88 @@
-use crate::parser::{structs::Expression, types::TokenIterator};
+use crate::{diagnostics::Diagnostic, lexer::tokens::{Token, TokenType}, parser::{structs::Expression, types::{tokens_span, ParseResult, TokenIterator}}};
+
+use super::{block::parse_indented_block, primary::int_parse_primary_expression};
+
+
+fn peek_continuation(iterator: &TokenIterator, indent: u32) -> Option<TokenType> {
+    let mut lookahead = iterator.clone();
+    lookahead.skip_blank_lines();
+
+    if indent > 0 {
+        match lookahead.next() {
+            Some(token) if token.token_type == TokenType::NewLineIndent(indent) => {},
+            _ => return None,
+        }
+    }
+
+    match lookahead.peek().map(|token| &token.token_type) {
+        Some(TokenType::Else) => Some(TokenType::Else),
+        Some(TokenType::ElseIf) => Some(TokenType::ElseIf),
+        _ => None,
+    }
+}
+
+
+fn parse_condition_chain<'a>(
+    mut iterator: TokenIterator<'a>,
+    indent: u32,
+    keyword: Token,
+) -> ParseResult<'a, Expression> {
+    let tokens = iterator.take_line();
+
+    if tokens.is_empty() {
+        return Err(
+            Diagnostic::error(format!("expected condition after {}", keyword.token_type))
+                .with_primary_label(keyword.span(), "condition is missing")
+        );
+    }
+
+    let span = keyword.span().to(tokens_span(&tokens));
+    let condition = int_parse_primary_expression(&tokens)?;
+
+    let (mut iterator, then_branch) = parse_indented_block(iterator, indent)?;
+
+    let else_branch = match peek_continuation(&iterator, indent) {
+        Some(continuation) => {
+            iterator.skip_blank_lines();
+
+            if indent > 0 {
+                iterator.next();
+            }
+
+            let keyword = iterator.next_some()?;
+
+            let (new_iter, branch) = match continuation {
+                TokenType::ElseIf => parse_condition_chain(iterator, indent, keyword)?,
+                _ => {
+                    iterator.expect_line_end()?;
+                    parse_indented_block(iterator, indent)?
+                },
+            };
+
+            iterator = new_iter;
+
+            Some(Box::new(branch))
+        },
+        None => None,
+    };
+
+    Ok((
+        iterator,
+        Expression::Condition {
+            condition: Box::new(condition),
+            then_branch: Box::new(then_branch),
+            else_branch,
+            span,
+        },
+    ))
+}
 
 
 pub fn parse_condition_expression<'a>(
-    mut _iterator: TokenIterator<'a>,
-) -> (TokenIterator<'a>, Option<Expression>) {
-    todo!()
+    mut iterator: TokenIterator<'a>,
+    indent: u32,
+) -> ParseResult<'a, Expression> {
+    let keyword = iterator.next_expected(TokenType::If)?;
+
+    parse_condition_chain(iterator, indent, keyword)
 }
@@ -0,0 +1,38 @@
+use crate::{lexer::tokens::TokenType, parser::{structs::Expression, types::{tokens_span, unexpected_token, ParseResult, TokenIterator}}};
+
+use super::primary::int_parse_primary_expression;
+
+
+pub fn parse_control_expression<'a>(
+    mut iterator: TokenIterator<'a>,
+) -> ParseResult<'a, Expression> {
+    let keyword = iterator.next_some()?;
+    let tokens = iterator.take_line();
+
+    let expression = match keyword.token_type {
+        TokenType::Identifier(ref name) if name == "return" => {
+            let value = match tokens.is_empty() {
+                true => None,
+                false => Some(Box::new(int_parse_primary_expression(&tokens)?)),
+            };
+
+            Expression::Return {
+                value,
+                span: keyword.span().to(tokens_span(&tokens)),
+            }
+        },
+        TokenType::Identifier(ref name) if name == "break" || name == "continue" => {
+            if let Some(token) = tokens.first() {
+                return Err(unexpected_token(token, "end of line"));
+            }
+
+            match name.as_str() {
+                "break" => Expression::Break,
+                _ => Expression::Continue,
+            }
+        },
+        _ => return Err(unexpected_token(&keyword, "`return`, `break` or `continue`")),
+    };
+
+    Ok((iterator, expression))
+}
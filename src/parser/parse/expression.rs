@@ -1,33 +1,49 @@
-use crate::{lexer::tokens::TokenType, parser::{structs::Expression, types::TokenIterator}};
+use crate::{diagnostics::Diagnostic, lexer::{tokens::TokenType, COMMANDS}, parser::{structs::Expression, types::{ParseResult, TokenIterator}}};
 
-use super::{assigment::parse_assigment_expression, condition::parse_condition_expression, function::parse_function_expression, primary::parse_primary_expression};
+use super::{assigment::{parse_assigment_expression, parse_statement_line}, command::{parse_command_expression, parse_print_expression}, condition::parse_condition_expression, control::parse_control_expression, for_loop::parse_for_expression, function::parse_function_expression};
 
 
-pub fn parse_expression<'a>(mut iterator: TokenIterator<'a>) -> (TokenIterator<'a>, Option<Expression>) {
-    match iterator.peek() {
-        Some(token) => {
-            match &token.token_type {
-                TokenType::NotEqual | TokenType::ParenthesisOpen => {
-                    let (new_iter, expr) = parse_primary_expression(iterator);
-                    (new_iter, Some(expr))
-                },
-                TokenType::Identifier(name) => {
-                    match name.as_str() {
-                        "var" => parse_assigment_expression(iterator),
-                        "func" => {
-                            let (new_iter, expr) = parse_function_expression(iterator);
-                            (new_iter, Some(expr))
-                        },
-                        _ => {
-                            let (new_iter, expr) = parse_primary_expression(iterator);
-                            (new_iter, Some(expr))
-                        }
-                    }
+pub fn parse_expression<'a>(
+    iterator: TokenIterator<'a>,
+    indent: u32,
+) -> ParseResult<'a, Option<Expression>> {
+    let mut lookahead = iterator.clone();
+    let token = lookahead.peek_some()?;
+
+    let (new_iter, expression) = match &token.token_type {
+        TokenType::Identifier(name) => {
+            match name.as_str() {
+                "var" => parse_assigment_expression(iterator)?,
+                "func" => parse_function_expression(iterator, indent)?,
+                "for" => parse_for_expression(iterator, indent)?,
+                "return" | "break" | "continue" => parse_control_expression(iterator)?,
+                _ if COMMANDS.contains(&name.as_str()) && is_command(&iterator) => {
+                    parse_command_expression(iterator)?
                 },
-                TokenType::If => parse_condition_expression(iterator),
-                _ => panic!("Unexpected identifier: {:?}", token),
+                _ => parse_statement_line(iterator)?,
             }
         },
-        None => todo!(),
-    }
+        TokenType::If => parse_condition_expression(iterator, indent)?,
+        TokenType::Greater => parse_print_expression(iterator)?,
+        TokenType::Else | TokenType::ElseIf => {
+            return Err(
+                Diagnostic::error(format!("{} without a matching `?`", token.token_type))
+                    .with_primary_label(token.span(), "no condition to continue")
+            );
+        },
+        _ => parse_statement_line(iterator)?,
+    };
+
+    Ok((new_iter, Some(expression)))
+}
+
+
+fn is_command(iterator: &TokenIterator) -> bool {
+    let mut lookahead = iterator.clone();
+    lookahead.next();
+
+    matches!(
+        lookahead.peek().map(|token| &token.token_type),
+        None | Some(TokenType::Text(_) | TokenType::EndLine | TokenType::Comment(_))
+    )
 }
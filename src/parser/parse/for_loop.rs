@@ -0,0 +1,77 @@
+use crate::{diagnostics::Diagnostic, lexer::tokens::TokenType, parser::{structs::Expression, types::{tokens_span, unexpected_token, ParseResult, TokenIterator}}};
+
+use super::{block::parse_indented_block, primary::{find_top_level, int_parse_primary_expression}};
+
+
+pub fn parse_for_expression<'a>(
+    mut iterator: TokenIterator<'a>,
+    indent: u32,
+) -> ParseResult<'a, Expression> {
+    let keyword = iterator.next_expected(TokenType::Identifier("for".to_string()))?;
+
+    let tokens = iterator.take_line();
+    let span = keyword.span().to(tokens_span(&tokens));
+
+    let variable = match tokens.first().map(|token| &token.token_type) {
+        Some(TokenType::Identifier(name)) => name.clone(),
+        Some(_) => return Err(unexpected_token(tokens[0], "loop variable")),
+        None => return Err(iterator.unexpected_end("loop variable")),
+    };
+
+    let separator = match tokens.get(1) {
+        Some(token) => token,
+        None => return Err(
+            Diagnostic::error("expected `=` or `:` after loop variable")
+                .with_primary_label(tokens[0].span(), "loop has no range")
+        ),
+    };
+
+    let header = &tokens[2..];
+
+    if header.is_empty() {
+        return Err(
+            Diagnostic::error("expected expression in loop header")
+                .with_primary_label(separator.span(), "loop has no range")
+        );
+    }
+
+    let (iterator, body) = match separator.token_type {
+        TokenType::Equal => {
+            let range = match find_top_level(header, &[TokenType::Range]) {
+                Some(range) if range > 0 && range + 1 < header.len() => range,
+                _ => return Err(
+                    Diagnostic::error("expected range `start..end` in loop header")
+                        .with_primary_label(tokens_span(header), "not a range")
+                ),
+            };
+
+            let start = int_parse_primary_expression(&header[0..range])?;
+            let end = int_parse_primary_expression(&header[range + 1..])?;
+
+            let (iterator, body) = parse_indented_block(iterator, indent)?;
+
+            (iterator, Expression::ForRange {
+                variable,
+                start: Box::new(start),
+                end: Box::new(end),
+                body: Box::new(body),
+                span,
+            })
+        },
+        TokenType::Else => {
+            let iterable = int_parse_primary_expression(header)?;
+
+            let (iterator, body) = parse_indented_block(iterator, indent)?;
+
+            (iterator, Expression::ForEach {
+                variable,
+                iterable: Box::new(iterable),
+                body: Box::new(body),
+                span,
+            })
+        },
+        _ => return Err(unexpected_token(separator, "`=` or `:`")),
+    };
+
+    Ok((iterator, body))
+}
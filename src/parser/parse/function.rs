@@ -1,84 +1,57 @@
-use crate::{lexer::tokens::TokenType, parser::{structs::Expression, types::TokenIterator}};
+use crate::{lexer::tokens::TokenType, parser::{structs::Expression, types::{unexpected_token, ParseResult, TokenIterator}}};
 
-use super::expression::parse_expression;
+use super::block::parse_indented_block;
 
 
 pub fn parse_function_expression<'a>(
     mut iterator: TokenIterator<'a>,
-) -> (TokenIterator<'a>, Expression) {
-    iterator.next_expected(TokenType::Identifier("func".to_string()));
+    indent: u32,
+) -> ParseResult<'a, Expression> {
+    iterator.next_expected(TokenType::Identifier("func".to_string()))?;
 
-    let name = match iterator.next_some().token_type {
+    let token = iterator.next_some()?;
+
+    let name = match token.token_type {
         TokenType::Identifier(name) => name,
-        _ => panic!("Expected identifier"),
+        _ => return Err(unexpected_token(&token, "function name")),
     };
 
     let parameters = {
-        iterator.next_expected(TokenType::ParenthesisOpen);
+        iterator.next_expected(TokenType::ParenthesisOpen)?;
 
         let mut paramaters: Vec<String> = vec![];
 
-        match iterator.peek_some().token_type {
-            TokenType::ParenthesisClose => {
-                iterator.next();
-            }
-            TokenType::Identifier(name) => {
-                paramaters.push(name.to_string());
-                iterator.next();
-            }
-            _ => panic!("Unexpected token"),
-        }
-
-        iterator.next_expected(TokenType::EndLine);
-
-        paramaters
-    };
+        loop {
+            let token = iterator.next_some()?;
 
-    let indent = {
-        match iterator.peek_some().token_type {
-            TokenType::NewLineIndent(indent) => {
-                indent
+            match token.token_type {
+                TokenType::ParenthesisClose if paramaters.is_empty() => break,
+                TokenType::Identifier(name) => paramaters.push(name),
+                _ => return Err(unexpected_token(&token, "parameter name or `)`")),
             }
-            _ => panic!("Expected indent"),
-        }
-    };
-
-    let body_statements = {
-        let mut statements: Vec<Expression> = vec![];
-
-        loop {
-            match iterator.peek() {
-                Some(token) => {
-                    match token.token_type {
-                        TokenType::NewLineIndent(i) => {
-                            if i != indent {
-                                break;
-                            }
 
-                            iterator.next();
+            let token = iterator.next_some()?;
 
-                            let (new_iter, statement) = parse_expression(iterator);
-                            iterator = new_iter;
-                            statements.push(statement.unwrap());
-                        }
-                        _ => {
-                            panic!("Unexpected token");
-                        }
-                    }
-                }
-                None => break,
+            match token.token_type {
+                TokenType::Comma => {},
+                TokenType::ParenthesisClose => break,
+                _ => return Err(unexpected_token(&token, "`,` or `)`")),
             }
         }
 
-        statements
+        iterator.expect_line_end()?;
+
+        paramaters
     };
 
-    (
+    let (iterator, body) = parse_indented_block(iterator, indent)?;
+
+    Ok((
         iterator,
         Expression::Function {
             name,
             parameters,
-            body: Box::new(Expression::Block { statements: body_statements }),
+            body: Box::new(body),
         }
-)
+    ))
 }
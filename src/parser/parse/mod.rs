@@ -6,3 +6,6 @@ pub mod primary;
 pub mod expression;
 pub mod condition;
 pub mod function;
+pub mod for_loop;
+pub mod control;
+pub mod command;
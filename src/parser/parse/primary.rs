@@ -1,48 +1,67 @@
-use crate::{lexer::tokens::{Token, TokenType}, parser::{structs::{Expression, UnaryOperator}, types::TokenIterator}};
+use crate::{diagnostics::Diagnostic, lexer::tokens::{Token, TokenType}, parser::{structs::{Expression, UnaryOperator, Value}, types::{tokens_span, unexpected_token}}};
 
-use super::{binary_operator::parse_binary_operator, value::parse_expression_value};
+use super::{binary_operator::parse_binary_operator, value::{is_integer, parse_expression_value, parse_negative_integer}};
 
 
-const LOW_LEVEL_OPERATORS: [TokenType; 2] = [
-    TokenType::Add,
-    TokenType::Subtract,
+const OPERATOR_LEVELS: [&[TokenType]; 5] = [
+    &[TokenType::Or],
+    &[TokenType::And],
+    &[
+        TokenType::Equal,
+        TokenType::NotEqual,
+        TokenType::Greater,
+        TokenType::Less,
+        TokenType::GreaterEqual,
+        TokenType::LessEqual,
+    ],
+    &[TokenType::Add, TokenType::Subtract],
+    &[TokenType::Multiply, TokenType::Divide, TokenType::Modulo],
 ];
 
-const HIGH_LEVEL_OPERATORS: [TokenType; 2] = [
-    TokenType::Multiply,
-    TokenType::Divide,
-];
 
+fn unexpected_expression(tokens: &[&Token]) -> Diagnostic {
+    Diagnostic::error("unexpected expression")
+        .with_primary_label(tokens_span(tokens), "could not parse this expression")
+}
 
-pub fn parse_primary_split_by_operators(
-    tokens: &[&Token],
-    operators: &[TokenType],
- ) -> Option<Expression> {
-    for operator in operators {
-        let mut level = 0;
-
-        for (index, token) in tokens.iter().enumerate() {
-            match token.token_type {
-                TokenType::ParenthesisOpen => {
-                    level += 1;
-                },
-                TokenType::ParenthesisClose => {
-                    level -= 1;
-                },
-                _ => {},
-            }
 
-            if **token == *operator && level == 0 {
-                let left = int_parse_primary_expression(&tokens[0..index]);
-                let operator = parse_binary_operator(token);
-                let right = int_parse_primary_expression(&tokens[index + 1..]);
+fn missing_operand(token: &Token) -> Diagnostic {
+    Diagnostic::error(format!("expected expression after {}", token.token_type))
+        .with_primary_label(token.span(), "operator is missing an operand")
+}
 
-                return Some(Expression::Binary {
-                    left: Box::new(left),
-                    operator,
-                    right: Box::new(right)
-                });
-            }
+
+fn is_operand_end(token: &Token) -> bool {
+    matches!(
+        token.token_type,
+        TokenType::Identifier(_)
+            | TokenType::String(_)
+            | TokenType::AsciiBlock(_)
+            | TokenType::ParenthesisClose
+            | TokenType::SquareBracketClose
+            | TokenType::Increment
+            | TokenType::Decrement
+    )
+}
+
+
+fn level_change(token: &Token) -> i32 {
+    match token.token_type {
+        TokenType::ParenthesisOpen | TokenType::SquareBracketOpen => 1,
+        TokenType::ParenthesisClose | TokenType::SquareBracketClose => -1,
+        _ => 0,
+    }
+}
+
+
+pub fn find_top_level(tokens: &[&Token], expected: &[TokenType]) -> Option<usize> {
+    let mut level = 0;
+
+    for (index, token) in tokens.iter().enumerate() {
+        level += level_change(token);
+
+        if level == 0 && expected.contains(&token.token_type) {
+            return Some(index);
         }
     }
 
@@ -50,155 +69,245 @@ pub fn parse_primary_split_by_operators(
 }
 
 
-pub fn parse_primary_expression_remove_parenthesis(
-    tokens: &[&Token],
-) -> Option<Expression> {
+fn find_matching_open(tokens: &[&Token]) -> Option<usize> {
     let mut level = 0;
 
-    let mut first_level_1 = 0;
-    let mut last_level_0 = 0;
+    for (index, token) in tokens.iter().enumerate().rev() {
+        level += level_change(token);
 
-    for (index, token) in tokens.iter().enumerate() {
-        match token.token_type {
-            TokenType::ParenthesisOpen => {
-                level += 1;
+        if level == 0 {
+            return Some(index);
+        }
+    }
 
-                if level == 1 {
-                    first_level_1 = index;
-                }
-            },
-            TokenType::ParenthesisClose => {
-                level -= 1;
+    None
+}
 
-                if level == 0 {
-                    last_level_0 = index;
-                }
-            },
-            _ => {},
+
+// Splits at every operator of one precedence level and folds the operands
+// from the left, so long chains like `1 + 2 + ... + n` do not recurse.
+pub fn parse_primary_split_by_operators(
+    tokens: &[&Token],
+    operators: &[TokenType],
+ ) -> Result<Option<Expression>, Diagnostic> {
+    let mut level = 0;
+    let mut splits = vec![];
+
+    for (index, token) in tokens.iter().enumerate() {
+        level += level_change(token);
+
+        if level == 0 && index > 0 && operators.contains(&token.token_type) && is_operand_end(tokens[index - 1]) {
+            splits.push(index);
         }
     }
 
-    if first_level_1 == 0 && last_level_0 == tokens.len() - 1 {
-        return Some(int_parse_primary_expression(&tokens[1..tokens.len() - 1]));
+    let Some(&last) = splits.last() else {
+        return Ok(None);
+    };
+
+    if last + 1 == tokens.len() {
+        return Err(missing_operand(tokens[last]));
     }
 
-    if *tokens[0] == TokenType::NotEqual && first_level_1 == 1 && last_level_0 == tokens.len() - 1 {
-        return Some(Expression::Unary {
-            operator: UnaryOperator::Negate,
-            right: Box::new(int_parse_primary_expression(&tokens[2..tokens.len() - 1])),
-        });
+    let mut expression = int_parse_primary_expression(&tokens[0..splits[0]])?;
+
+    for (position, &index) in splits.iter().enumerate() {
+        let end = splits.get(position + 1).copied().unwrap_or(tokens.len());
+
+        expression = Expression::Binary {
+            left: Box::new(expression),
+            operator: parse_binary_operator(tokens[index])?,
+            right: Box::new(int_parse_primary_expression(&tokens[index + 1..end])?),
+        };
     }
 
-    None
+    Ok(Some(expression))
 }
 
 
-pub fn int_parse_primary_expression(
-    tokens: &[&Token],
-) -> Expression {
-    match tokens.len() {
-        0 => Expression::Nope,
-        1 => {
-            match tokens[0].token_type {
-                TokenType::Identifier(_) => {
-                    parse_expression_value(tokens[0])
-                },
-                _ => panic!("Expected identifier"),
+pub fn parse_arguments(tokens: &[&Token]) -> Result<Vec<Expression>, Diagnostic> {
+    let mut arguments = vec![];
+
+    if tokens.is_empty() {
+        return Ok(arguments);
+    }
+
+    let mut rest = tokens;
+
+    loop {
+        let end = find_top_level(rest, &[TokenType::Comma]).unwrap_or(rest.len());
+
+        if end == 0 {
+            return Err(unexpected_token(rest[0], "expression"));
+        }
+
+        arguments.push(int_parse_primary_expression(&rest[0..end])?);
+
+        if end == rest.len() {
+            return Ok(arguments);
+        }
+
+        if end + 1 == rest.len() {
+            return Err(missing_operand(rest[end]));
+        }
+
+        rest = &rest[end + 1..];
+    }
+}
+
+
+fn parse_unary_expression(tokens: &[&Token]) -> Result<Expression, Diagnostic> {
+    let first = tokens[0];
+    let last = tokens[tokens.len() - 1];
+
+    match first.token_type {
+        TokenType::NotEqual | TokenType::Subtract | TokenType::Add => {
+            if tokens.len() == 1 {
+                return Err(missing_operand(first));
             }
-        },
-        2 => {
-            match (tokens[0].clone().token_type, tokens[1].clone().token_type) {
-                (TokenType::ParenthesisOpen, TokenType::ParenthesisClose) => Expression::Nope,
-                (TokenType::Identifier(name), TokenType::Decrement | TokenType::Increment) => {
-                    Expression::Unary {
-                        operator: match tokens[1].token_type {
-                            TokenType::Decrement => UnaryOperator::Decrement,
-                            TokenType::Increment => UnaryOperator::Increment,
-                            _ => panic!("Expected increment or decrement"),
-                        },
-                        right: Box::new(Expression::Identifier(name.to_string())),
-                    }
+
+            if first.token_type == TokenType::Subtract && tokens.len() == 2 && is_integer(tokens[1]) {
+                return parse_negative_integer(tokens[1]);
+            }
+
+            let right = parse_unary_expression(&tokens[1..])?;
+
+            return Ok(match (&first.token_type, right) {
+                (TokenType::Add, right) => right,
+                (TokenType::Subtract, Expression::Value(Value::Integer(value))) if value != i64::from(i32::MIN) => {
+                    Expression::Value(Value::Integer(-value))
                 },
-                (TokenType::NotEqual, TokenType::Identifier(name)) => {
-                    Expression::Unary {
-                        operator: UnaryOperator::Negate,
-                        right: Box::new(Expression::Identifier(name.to_string())),
-                    }
+                (TokenType::Subtract, Expression::Value(Value::Decimal(value))) => {
+                    Expression::Value(Value::Decimal(-value))
                 },
-                _ => panic!("Expected unary operator"),
-            }
-        },
-        3 => {
-            match (tokens[0].clone().token_type, tokens[1].clone().token_type, tokens[2].clone().token_type) {
-                (TokenType::ParenthesisOpen, _, TokenType::ParenthesisClose) => {
-                    int_parse_primary_expression(&tokens[1..2])
+                (TokenType::Subtract, right) => Expression::Unary {
+                    operator: UnaryOperator::Minus,
+                    right: Box::new(right),
                 },
-                (
-                    TokenType::Identifier(_),
-                    TokenType::Add
-                    | TokenType::Subtract
-                    | TokenType::Multiply
-                    | TokenType::Divide
-                    | TokenType::Modulo
-                    | TokenType::Equal
-                    | TokenType::NotEqual
-                    | TokenType::And
-                    | TokenType::Or
-                    | TokenType::Greater
-                    | TokenType::Less
-                    | TokenType::GreaterEqual
-                    | TokenType::LessEqual,
-                    TokenType::Identifier(_)
-                ) => {
-                    let left = int_parse_primary_expression(&tokens[0..1]);
-                    let operator = parse_binary_operator(&tokens[1]);
-                    let right = int_parse_primary_expression(&tokens[2..3]);
-
-                    Expression::Binary {
-                        left: Box::new(left),
-                        operator,
-                        right: Box::new(right)
-                    }
+                (_, right) => Expression::Unary {
+                    operator: UnaryOperator::Negate,
+                    right: Box::new(right),
                 },
-                _ => panic!("Unexpected expression"),
-            }
+            });
         },
-        _ => {
-            if let Some(expression) = parse_primary_split_by_operators(tokens, LOW_LEVEL_OPERATORS.as_ref()) {
-                return expression;
+        _ => {},
+    }
+
+    if tokens.len() > 1 {
+        let operator = match last.token_type {
+            TokenType::Increment => Some(UnaryOperator::Increment),
+            TokenType::Decrement => Some(UnaryOperator::Decrement),
+            _ => None,
+        };
+
+        if let Some(operator) = operator {
+            return Ok(Expression::Unary {
+                operator,
+                right: Box::new(parse_postfix_expression(&tokens[0..tokens.len() - 1])?),
+            });
+        }
+    }
+
+    parse_postfix_expression(tokens)
+}
+
+
+fn parse_postfix_expression(tokens: &[&Token]) -> Result<Expression, Diagnostic> {
+    let length = tokens.len();
+    let last = tokens[length - 1];
+
+    if length == 1 {
+        return match last.token_type {
+            TokenType::Identifier(_) | TokenType::String(_) | TokenType::AsciiBlock(_) => {
+                parse_expression_value(last)
+            },
+            _ => Err(unexpected_token(last, "expression")),
+        };
+    }
+
+    match last.token_type {
+        TokenType::ParenthesisClose => {
+            let open = match find_matching_open(tokens) {
+                Some(open) => open,
+                None => return Err(unexpected_token(last, "expression")),
+            };
+
+            if open == 0 {
+                return int_parse_primary_expression(&tokens[1..length - 1]);
             }
 
-            if let Some(expression) = parse_primary_split_by_operators(tokens, HIGH_LEVEL_OPERATORS.as_ref()) {
-                return expression;
+            Ok(Expression::Call {
+                callee: Box::new(parse_postfix_expression(&tokens[0..open])?),
+                arguments: parse_arguments(&tokens[open + 1..length - 1])?,
+                span: tokens_span(tokens),
+            })
+        },
+        TokenType::SquareBracketClose => {
+            let open = match find_matching_open(tokens) {
+                Some(open) => open,
+                None => return Err(unexpected_token(last, "expression")),
+            };
+
+            if open == 0 {
+                return Ok(Expression::Array {
+                    elements: parse_arguments(&tokens[1..length - 1])?,
+                });
             }
 
-            if let Some(expression) = parse_primary_expression_remove_parenthesis(tokens) {
-                return expression;
+            if open + 2 == length {
+                return Err(unexpected_token(last, "index expression"));
             }
 
-            panic!("Unexpected expression");
-        }
+            Ok(Expression::Index {
+                target: Box::new(parse_postfix_expression(&tokens[0..open])?),
+                index: Box::new(int_parse_primary_expression(&tokens[open + 1..length - 1])?),
+            })
+        },
+        TokenType::Identifier(ref name) if length > 2 && tokens[length - 2].token_type == TokenType::Dot => {
+            Ok(Expression::Member {
+                target: Box::new(parse_postfix_expression(&tokens[0..length - 2])?),
+                name: name.clone(),
+            })
+        },
+        _ => Err(unexpected_expression(tokens)),
     }
 }
 
 
-pub fn parse_primary_expression<'a>(
-    mut iterator: TokenIterator<'a>,
-) -> (TokenIterator<'a>, Expression) {
-    let mut tokens: Vec<Token> = vec![];
+fn parse_new_expression(tokens: &[&Token]) -> Result<Expression, Diagnostic> {
+    let mut path = String::new();
 
-    while let Some(token) = iterator.next() {
-        match token.token_type {
-            TokenType::EndLine => {
-                break;
-            },
-            _ => {
-                tokens.push(token.clone());
-            },
+    for token in &tokens[1..] {
+        match &token.token_type {
+            TokenType::Identifier(name) => path.push_str(name),
+            TokenType::Divide => path.push('/'),
+            TokenType::Dot => path.push('.'),
+            TokenType::Subtract => path.push('-'),
+            _ => return Err(unexpected_token(token, "file path")),
         }
     }
 
-    let expression = int_parse_primary_expression(&tokens.iter().collect::<Vec<_>>());
+    Ok(Expression::New { path, span: tokens_span(tokens) })
+}
+
+
+pub fn int_parse_primary_expression(
+    tokens: &[&Token],
+) -> Result<Expression, Diagnostic> {
+    if tokens.is_empty() {
+        return Ok(Expression::Nope);
+    }
 
-    (iterator, expression)
+    if tokens.len() > 1 && tokens[0].token_type == TokenType::Identifier("new".to_string()) {
+        return parse_new_expression(tokens);
+    }
+
+    for operators in OPERATOR_LEVELS {
+        if let Some(expression) = parse_primary_split_by_operators(tokens, operators)? {
+            return Ok(expression);
+        }
+    }
+
+    parse_unary_expression(tokens)
 }
+
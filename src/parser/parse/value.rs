@@ -1,18 +1,78 @@
-use crate::{lexer::tokens::{Token, TokenType}, parser::structs::{Expression, Value}};
+use crate::diagnostics::Diagnostic;
+use crate::lexer::tokens::{Token, TokenType};
+use crate::parser::structs::{Expression, Value};
+use crate::parser::types::unexpected_token;
 
 
-pub fn parse_expression_value(token: &Token) -> Expression {
+// Number literals follow the same rules as `int.Parse` and `float.Parse`.
+pub fn parse_int(text: &str) -> Option<i32> {
+    let text = text.trim();
+    let digits = text.strip_prefix(['+', '-']).unwrap_or(text);
+
+    if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+
+    text.parse().ok()
+}
+
+
+pub fn parse_float(text: &str) -> Option<f64> {
+    let text = text.trim();
+
+    if !text.bytes().any(|byte| byte.is_ascii_digit())
+        || !text.bytes().all(|byte| byte.is_ascii_digit() || matches!(byte, b'+' | b'-' | b'.' | b'e' | b'E')) {
+        return None;
+    }
+
+    text.parse().ok()
+}
+
+
+pub fn parse_expression_value(token: &Token) -> Result<Expression, Diagnostic> {
+    parse_literal(token, "")
+}
+
+
+// `-2147483648` only fits an int with its sign, so a negated integer is parsed as one literal.
+pub fn parse_negative_integer(token: &Token) -> Result<Expression, Diagnostic> {
+    parse_literal(token, "-")
+}
+
+
+pub fn is_integer(token: &Token) -> bool {
+    matches!(&token.token_type, TokenType::Identifier(name) if !name.is_empty() && name.bytes().all(|byte| byte.is_ascii_digit()))
+}
+
+
+fn parse_literal(token: &Token, sign: &str) -> Result<Expression, Diagnostic> {
     match &token.token_type {
-        TokenType::Identifier(name) => {
-            if let Ok(value) = name.parse::<i64>() {
-                Expression::Value(Value::Integer(value))
-            } else if let Ok(value) = name.parse::<f64>() {
-                Expression::Value(Value::Decimal(value))
-            } else {
-                Expression::Identifier(name.clone())
+        TokenType::Identifier(name) if is_integer(token) => {
+            let text = format!("{}{}", sign, name);
+
+            match parse_int(&text) {
+                Some(value) => Ok(Expression::Value(Value::Integer(value.into()))),
+                None => Err(
+                    Diagnostic::error(format!("integer literal `{}` is out of range", text))
+                        .with_primary_label(token.span(), "does not fit in an int")
+                        .with_help(format!("ints range from -2147483648 to 2147483647, write `{}.0` for a float", text))
+                ),
             }
         },
-        TokenType::String(value) => Expression::Value(Value::String(value.clone())),
-        _ => panic!("Expected identifier"),
+        TokenType::Identifier(name) => match name.as_str() {
+            _ if name.starts_with(|c: char| c.is_ascii_digit()) => match parse_float(name) {
+                Some(value) => Ok(Expression::Value(Value::Decimal(value))),
+                None => Ok(Expression::Identifier(name.clone())),
+            },
+            "true" => Ok(Expression::Value(Value::Boolean(true))),
+            "false" => Ok(Expression::Value(Value::Boolean(false))),
+            "null" => Ok(Expression::Value(Value::Null)),
+            "this" => Ok(Expression::This),
+            _ => Ok(Expression::Identifier(name.clone())),
+        },
+        TokenType::String(value) | TokenType::AsciiBlock(value) => {
+            Ok(Expression::Value(Value::String(value.clone())))
+        },
+        _ => Err(unexpected_token(token, "identifier")),
     }
 }
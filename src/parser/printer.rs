@@ -0,0 +1,508 @@
+use std::fmt;
+
+use super::structs::{BinaryOperator, Expression, UnaryOperator, Value};
+
+
+const INDENT: &str = "  ";
+
+const UNARY_PRECEDENCE: u8 = 6;
+
+const POSTFIX_PRECEDENCE: u8 = 7;
+
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::String(value) if value.contains(['\n', '"']) => write!(f, "ascii\n{}\nasciiend", value),
+            Value::String(value) => write!(f, "\"{}\"", value),
+            Value::Decimal(value) if value.fract() == 0.0 => write!(f, "{}.0", value),
+            Value::Decimal(value) => write!(f, "{}", value),
+            Value::Integer(value) => write!(f, "{}", value),
+            Value::Boolean(value) => write!(f, "{}", value),
+            Value::Null => f.write_str("null"),
+        }
+    }
+}
+
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            BinaryOperator::Add => "+",
+            BinaryOperator::Subtract => "-",
+            BinaryOperator::Multiply => "*",
+            BinaryOperator::Divide => "/",
+            BinaryOperator::Modulo => "%",
+            BinaryOperator::Equal => "=",
+            BinaryOperator::NotEqual => "!",
+            BinaryOperator::And => "&",
+            BinaryOperator::Or => "|",
+            BinaryOperator::Greater => ">",
+            BinaryOperator::Less => "<",
+            BinaryOperator::GreaterEqual => ">=",
+            BinaryOperator::LessEqual => "<=",
+        };
+
+        f.write_str(symbol)
+    }
+}
+
+
+impl fmt::Display for UnaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            UnaryOperator::Negate => "!",
+            UnaryOperator::Minus => "-",
+            UnaryOperator::Increment => "++",
+            UnaryOperator::Decrement => "--",
+        };
+
+        f.write_str(symbol)
+    }
+}
+
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_block(f, self, 0)
+    }
+}
+
+
+fn write_indent(f: &mut fmt::Formatter<'_>, level: usize) -> fmt::Result {
+    for _ in 0..level {
+        f.write_str(INDENT)?;
+    }
+
+    Ok(())
+}
+
+
+fn write_block(f: &mut fmt::Formatter<'_>, expression: &Expression, level: usize) -> fmt::Result {
+    match expression {
+        Expression::Block { statements } => {
+            for (index, statement) in statements.iter().enumerate() {
+                if index > 0 {
+                    f.write_str("\n")?;
+                }
+
+                write_statement(f, statement, level)?;
+            }
+
+            Ok(())
+        },
+        _ => write_statement(f, expression, level),
+    }
+}
+
+
+fn write_statement(f: &mut fmt::Formatter<'_>, expression: &Expression, level: usize) -> fmt::Result {
+    match expression {
+        Expression::Block { .. } => write_block(f, expression, level),
+        Expression::Condition { .. } => {
+            write_indent(f, level)?;
+            f.write_str("?")?;
+            write_condition(f, expression, level)
+        },
+        Expression::Function { name, parameters, body } => {
+            write_indent(f, level)?;
+            writeln!(f, "func {}({})", name, parameters.join(", "))?;
+            write_block(f, body, level + 1)
+        },
+        Expression::ForRange { variable, start, end, body, .. } => {
+            write_indent(f, level)?;
+            write!(f, "for {} = ", variable)?;
+            write_expression(f, start)?;
+            f.write_str("..")?;
+            write_expression(f, end)?;
+            f.write_str("\n")?;
+            write_block(f, body, level + 1)
+        },
+        Expression::ForEach { variable, iterable, body, .. } => {
+            write_indent(f, level)?;
+            write!(f, "for {} : ", variable)?;
+            write_expression(f, iterable)?;
+            f.write_str("\n")?;
+            write_block(f, body, level + 1)
+        },
+        Expression::Nope => Ok(()),
+        _ if starts_with_equality(expression) => {
+            write_indent(f, level)?;
+            write_operand(f, expression, true)
+        },
+        _ => {
+            write_indent(f, level)?;
+            write_expression(f, expression)
+        },
+    }
+}
+
+
+// A statement that begins with `a = b` reparses as an assignment, so those are kept in parentheses.
+fn starts_with_equality(expression: &Expression) -> bool {
+    match expression {
+        Expression::Binary { operator: BinaryOperator::Equal, .. } => true,
+        Expression::Binary { left, operator, .. } => {
+            operator.precedence() < BinaryOperator::Equal.precedence() && starts_with_equality(left)
+        },
+        _ => false,
+    }
+}
+
+
+fn write_condition(f: &mut fmt::Formatter<'_>, expression: &Expression, level: usize) -> fmt::Result {
+    match expression {
+        Expression::Condition { condition, then_branch, else_branch, .. } => {
+            write_expression(f, condition)?;
+            f.write_str("\n")?;
+            write_block(f, then_branch, level + 1)?;
+
+            match else_branch.as_deref() {
+                Some(else_branch @ Expression::Condition { .. }) => {
+                    f.write_str("\n")?;
+                    write_indent(f, level)?;
+                    f.write_str(":?")?;
+                    write_condition(f, else_branch, level)
+                },
+                Some(else_branch) => {
+                    f.write_str("\n")?;
+                    write_indent(f, level)?;
+                    f.write_str(":\n")?;
+                    write_block(f, else_branch, level + 1)
+                },
+                None => Ok(()),
+            }
+        },
+        _ => write_expression(f, expression),
+    }
+}
+
+
+fn precedence(expression: &Expression) -> Option<u8> {
+    match expression {
+        Expression::Binary { operator, .. } => Some(operator.precedence()),
+        Expression::Unary { .. } => Some(UNARY_PRECEDENCE),
+        Expression::Call { .. } | Expression::Member { .. } | Expression::Index { .. } => Some(POSTFIX_PRECEDENCE),
+        Expression::New { .. } => Some(0),
+        _ => None,
+    }
+}
+
+
+fn is_negative(expression: &Expression) -> bool {
+    match expression {
+        Expression::Unary { operator: UnaryOperator::Minus, .. } => true,
+        Expression::Value(Value::Integer(value)) => *value < 0,
+        Expression::Value(Value::Decimal(value)) => value.is_sign_negative(),
+        _ => false,
+    }
+}
+
+
+fn write_operand(f: &mut fmt::Formatter<'_>, expression: &Expression, needs_parenthesis: bool) -> fmt::Result {
+    if needs_parenthesis {
+        f.write_str("(")?;
+        write_expression(f, expression)?;
+        f.write_str(")")
+    } else {
+        write_expression(f, expression)
+    }
+}
+
+
+fn write_list(f: &mut fmt::Formatter<'_>, expressions: &[Expression]) -> fmt::Result {
+    for (index, expression) in expressions.iter().enumerate() {
+        if index > 0 {
+            f.write_str(", ")?;
+        }
+
+        write_expression(f, expression)?;
+    }
+
+    Ok(())
+}
+
+
+fn write_expression(f: &mut fmt::Formatter<'_>, expression: &Expression) -> fmt::Result {
+    match expression {
+        Expression::Value(value) => write!(f, "{}", value),
+        Expression::Identifier(name) => f.write_str(name),
+        Expression::Binary { left, operator, right } => {
+            let own = operator.precedence();
+
+            write_operand(f, left, precedence(left).is_some_and(|p| p < own))?;
+            write!(f, " {} ", operator)?;
+            write_operand(f, right, precedence(right).is_some_and(|p| p <= own))
+        },
+        Expression::Unary { operator: UnaryOperator::Minus, right } => {
+            f.write_str("-")?;
+            write_operand(f, right, is_negative(right) || precedence(right).is_some_and(|p| p < UNARY_PRECEDENCE))
+        },
+        Expression::Unary { operator: UnaryOperator::Negate, right } => {
+            f.write_str("!")?;
+            write_operand(f, right, precedence(right).is_some_and(|p| p < UNARY_PRECEDENCE))
+        },
+        Expression::Unary { operator, right } => {
+            write_operand(f, right, precedence(right).is_some_and(|p| p < POSTFIX_PRECEDENCE))?;
+            write!(f, "{}", operator)
+        },
+        Expression::Assignment { name, value, .. } => match value.as_ref() {
+            Expression::Nope => write!(f, "var {}", name),
+            value => {
+                write!(f, "var {} = ", name)?;
+                write_expression(f, value)
+            },
+        },
+        Expression::Reassignment { target, operator, value, .. } => {
+            write_expression(f, target)?;
+
+            match operator {
+                Some(operator) => write!(f, " {}= ", operator)?,
+                None => f.write_str(" = ")?,
+            }
+
+            write_expression(f, value)
+        },
+        Expression::Call { callee, arguments, .. } => {
+            write_operand(f, callee, precedence(callee).is_some_and(|p| p < POSTFIX_PRECEDENCE))?;
+            f.write_str("(")?;
+            write_list(f, arguments)?;
+            f.write_str(")")
+        },
+        Expression::Member { target, name } => {
+            write_operand(f, target, precedence(target).is_some_and(|p| p < POSTFIX_PRECEDENCE))?;
+            write!(f, ".{}", name)
+        },
+        Expression::Index { target, index } => {
+            write_operand(f, target, precedence(target).is_some_and(|p| p < POSTFIX_PRECEDENCE))?;
+            f.write_str("[")?;
+            write_expression(f, index)?;
+            f.write_str("]")
+        },
+        Expression::Array { elements } => {
+            f.write_str("[")?;
+            write_list(f, elements)?;
+            f.write_str("]")
+        },
+        Expression::New { path, .. } => write!(f, "new {}", path),
+        Expression::This => f.write_str("this"),
+        Expression::Return { value: Some(value), .. } => {
+            f.write_str("return ")?;
+            write_expression(f, value)
+        },
+        Expression::Return { value: None, .. } => f.write_str("return"),
+        Expression::Break => f.write_str("break"),
+        Expression::Continue => f.write_str("continue"),
+        Expression::Print { text, .. } => write!(f, ">{}", text),
+        Expression::Command { name, arguments, .. } if arguments.is_empty() => f.write_str(name),
+        Expression::Command { name, arguments, .. } => write!(f, "{} {}", name, arguments),
+        Expression::Nope => Ok(()),
+        Expression::Condition { .. }
+        | Expression::Function { .. }
+        | Expression::ForRange { .. }
+        | Expression::ForEach { .. }
+        | Expression::Block { .. } => write_statement(f, expression, 0),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::without_spans;
+    use crate::diagnostics::Span;
+
+    fn integer(value: i64) -> Box<Expression> {
+        Box::new(Expression::Value(Value::Integer(value)))
+    }
+
+    fn identifier(name: &str) -> Box<Expression> {
+        Box::new(Expression::Identifier(name.to_string()))
+    }
+
+    #[test]
+    fn test_values() {
+        assert_eq!(Expression::Value(Value::Integer(5)).to_string(), "5");
+        assert_eq!(Expression::Value(Value::Decimal(2.0)).to_string(), "2.0");
+        assert_eq!(Expression::Value(Value::Decimal(0.25)).to_string(), "0.25");
+        assert_eq!(Expression::Value(Value::String("hi".to_string())).to_string(), "\"hi\"");
+        assert_eq!(Expression::Value(Value::Decimal(1e20)).to_string(), "100000000000000000000.0");
+    }
+
+    #[test]
+    fn test_minimal_parenthesis() {
+        let expression = Expression::Binary {
+            left: Box::new(Expression::Binary {
+                left: integer(1),
+                operator: BinaryOperator::Add,
+                right: integer(2),
+            }),
+            operator: BinaryOperator::Multiply,
+            right: Box::new(Expression::Binary {
+                left: integer(3),
+                operator: BinaryOperator::Multiply,
+                right: integer(4),
+            }),
+        };
+
+        assert_eq!(expression.to_string(), "(1 + 2) * (3 * 4)");
+
+        let expression = Expression::Binary {
+            left: Box::new(Expression::Binary {
+                left: integer(1),
+                operator: BinaryOperator::Multiply,
+                right: integer(2),
+            }),
+            operator: BinaryOperator::Subtract,
+            right: integer(3),
+        };
+
+        assert_eq!(expression.to_string(), "1 * 2 - 3");
+    }
+
+    #[test]
+    fn test_logical_and_comparison() {
+        let expression = Expression::Binary {
+            left: Box::new(Expression::Binary {
+                left: identifier("hp"),
+                operator: BinaryOperator::Less,
+                right: integer(10),
+            }),
+            operator: BinaryOperator::And,
+            right: Box::new(Expression::Binary {
+                left: identifier("foe"),
+                operator: BinaryOperator::NotEqual,
+                right: identifier("boss"),
+            }),
+        };
+
+        assert_eq!(expression.to_string(), "hp < 10 & foe ! boss");
+    }
+
+    #[test]
+    fn test_unary() {
+        let negate = Expression::Unary {
+            operator: UnaryOperator::Negate,
+            right: Box::new(Expression::Binary {
+                left: identifier("a"),
+                operator: BinaryOperator::Or,
+                right: identifier("b"),
+            }),
+        };
+
+        assert_eq!(negate.to_string(), "!(a | b)");
+
+        let increment = Expression::Unary {
+            operator: UnaryOperator::Increment,
+            right: identifier("i"),
+        };
+
+        assert_eq!(increment.to_string(), "i++");
+
+        let minus = Expression::Unary {
+            operator: UnaryOperator::Minus,
+            right: Box::new(Expression::Unary { operator: UnaryOperator::Minus, right: identifier("x") }),
+        };
+
+        assert_eq!(minus.to_string(), "-(-x)");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let expressions = [
+            Expression::Unary {
+                operator: UnaryOperator::Minus,
+                right: Box::new(Expression::Unary { operator: UnaryOperator::Minus, right: identifier("x") }),
+            },
+            Expression::Binary { left: identifier("a"), operator: BinaryOperator::Subtract, right: integer(-5) },
+            Expression::Value(Value::Decimal(1e20)),
+            Expression::Value(Value::Decimal(-2.5e-7)),
+            Expression::Value(Value::String("say \"hi\"".to_string())),
+        ];
+
+        for expression in expressions {
+            let source = format!("var x = {}", expression);
+
+            let Expression::Block { statements } = crate::parse(&source).unwrap() else {
+                panic!("expected a block for {}", source);
+            };
+
+            match &statements[..] {
+                [Expression::Assignment { value, .. }] => assert_eq!(**value, expression, "{}", source),
+                statements => panic!("unexpected statements {:?} for {}", statements, source),
+            }
+        }
+
+        for source in ["(a = b)", "(hp - \"s\" = -(f(a)))", "(a = b) & c", "?(a = b)\n  (c = d)"] {
+            let expression = without_spans(crate::parse(source).unwrap());
+            let printed = expression.to_string();
+
+            assert_eq!(without_spans(crate::parse(&printed).unwrap()), expression, "{} printed as {}", source, printed);
+        }
+    }
+
+    #[test]
+    fn test_function() {
+        let expression = Expression::Block {
+            statements: vec![
+                Expression::Function {
+                    name: "Add".to_string(),
+                    parameters: vec!["a".to_string(), "b".to_string()],
+                    body: Box::new(Expression::Block {
+                        statements: vec![
+                            Expression::Assignment {
+                                name: "c".to_string(),
+                                value: Box::new(Expression::Binary {
+                                    left: identifier("a"),
+                                    operator: BinaryOperator::Add,
+                                    right: identifier("b"),
+                                }),
+                                span: Span::default(),
+                            },
+                        ],
+                    }),
+                },
+                Expression::Identifier("x".to_string()),
+            ],
+        };
+
+        assert_eq!(expression.to_string(), "func Add(a, b)\n  var c = a + b\nx");
+    }
+
+    #[test]
+    fn test_condition_chain() {
+        let expression = Expression::Condition {
+            condition: Box::new(Expression::Binary {
+                left: identifier("loc"),
+                operator: BinaryOperator::Equal,
+                right: identifier("caves"),
+            }),
+            then_branch: Box::new(Expression::Block {
+                statements: vec![Expression::Identifier("a".to_string())],
+            }),
+            else_branch: Some(Box::new(Expression::Condition {
+                condition: identifier("b"),
+                then_branch: Box::new(Expression::Block {
+                    statements: vec![
+                        Expression::Condition {
+                            condition: identifier("c"),
+                            then_branch: identifier("d"),
+                            else_branch: None,
+                            span: Span::default(),
+                        },
+                    ],
+                }),
+                else_branch: Some(Box::new(Expression::Block {
+                    statements: vec![Expression::Identifier("e".to_string())],
+                })),
+                span: Span::default(),
+            })),
+            span: Span::default(),
+        };
+
+        assert_eq!(
+            expression.to_string(),
+            "?loc = caves\n  a\n:?b\n  ?c\n    d\n:\n  e"
+        );
+    }
+}
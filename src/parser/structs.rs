@@ -1,12 +1,21 @@
-#[derive(Debug, PartialEq)]
+use crate::diagnostics::Span;
+
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value", rename_all = "snake_case"))]
 pub enum Value {
     String(String),
     Decimal(f64),
     Integer(i64),
+    Boolean(bool),
+    Null,
 }
 
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum BinaryOperator {
     Add,
     Subtract,
@@ -24,15 +33,20 @@ pub enum BinaryOperator {
 }
 
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum UnaryOperator {
     Negate,
+    Minus,
     Increment,
     Decrement,
 }
 
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value", rename_all = "snake_case"))]
 pub enum Expression {
     Value(Value),
     Identifier(String),
@@ -48,19 +62,113 @@ pub enum Expression {
     Assignment {
         name: String,
         value: Box<Expression>,
+        span: Span,
+    },
+    Reassignment {
+        target: Box<Expression>,
+        operator: Option<BinaryOperator>,
+        value: Box<Expression>,
+        span: Span,
     },
     Condition {
         condition: Box<Expression>,
         then_branch: Box<Expression>,
         else_branch: Option<Box<Expression>>,
+        span: Span,
     },
     Function {
         name: String,
         parameters: Vec<String>,
         body: Box<Expression>,
     },
+    Call {
+        callee: Box<Expression>,
+        arguments: Vec<Expression>,
+        span: Span,
+    },
+    Member {
+        target: Box<Expression>,
+        name: String,
+    },
+    Index {
+        target: Box<Expression>,
+        index: Box<Expression>,
+    },
+    Array {
+        elements: Vec<Expression>,
+    },
+    New {
+        path: String,
+        span: Span,
+    },
+    This,
+    ForRange {
+        variable: String,
+        start: Box<Expression>,
+        end: Box<Expression>,
+        body: Box<Expression>,
+        span: Span,
+    },
+    ForEach {
+        variable: String,
+        iterable: Box<Expression>,
+        body: Box<Expression>,
+        span: Span,
+    },
+    Return {
+        value: Option<Box<Expression>>,
+        span: Span,
+    },
+    Break,
+    Continue,
+    Print {
+        text: String,
+        span: Span,
+    },
+    Command {
+        name: String,
+        arguments: String,
+        span: Span,
+    },
     Block {
         statements: Vec<Expression>,
     },
     Nope,
 }
+
+
+impl BinaryOperator {
+    pub fn precedence(&self) -> u8 {
+        match self {
+            BinaryOperator::Or => 1,
+            BinaryOperator::And => 2,
+            BinaryOperator::Equal
+            | BinaryOperator::NotEqual
+            | BinaryOperator::Greater
+            | BinaryOperator::Less
+            | BinaryOperator::GreaterEqual
+            | BinaryOperator::LessEqual => 3,
+            BinaryOperator::Add | BinaryOperator::Subtract => 4,
+            BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Modulo => 5,
+        }
+    }
+}
+
+
+impl Expression {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Expression::Assignment { span, .. }
+            | Expression::Reassignment { span, .. }
+            | Expression::Condition { span, .. }
+            | Expression::Call { span, .. }
+            | Expression::New { span, .. }
+            | Expression::ForRange { span, .. }
+            | Expression::ForEach { span, .. }
+            | Expression::Return { span, .. }
+            | Expression::Print { span, .. }
+            | Expression::Command { span, .. } => Some(*span),
+            _ => None,
+        }
+    }
+}
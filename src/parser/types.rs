@@ -1,45 +1,135 @@
+use crate::diagnostics::{Diagnostic, Span};
 use crate::lexer::tokens::{Token, TokenType};
 
+
+pub type ParseResult<'a, T> = Result<(TokenIterator<'a>, T), Diagnostic>;
+
+
 #[derive(Debug, Clone)]
 pub struct TokenIterator<'a> {
     iterator: std::iter::Peekable<std::slice::Iter<'a, Token>>,
+    previous: Option<&'a Token>,
 }
 
 impl<'a> TokenIterator<'a> {
     pub fn new(iterator: std::iter::Peekable<std::slice::Iter<'a, Token>>) -> Self {
-        TokenIterator { iterator }
+        TokenIterator { iterator, previous: None }
     }
 
     pub fn peek(&mut self) -> Option<&Token> {
         self.iterator.peek().map(|v| &**v)
     }
 
-    pub fn next(&mut self) -> Option<&Token> {
-        self.iterator.next()
+    pub fn end_span(&self) -> Span {
+        match self.previous {
+            Some(token) => {
+                let span = token.span();
+                Span::new(span.line, span.column + span.length, 1)
+            },
+            None => Span::new(1, 1, 1),
+        }
+    }
+
+    pub fn unexpected_end(&self, expected: &str) -> Diagnostic {
+        Diagnostic::error(format!("expected {}, found end of input", expected))
+            .with_primary_label(self.end_span(), format!("expected {}", expected))
     }
 
-    pub fn peek_some(&mut self) -> Token {
+    pub fn peek_some(&mut self) -> Result<Token, Diagnostic> {
         match self.peek() {
-            Some(token) => token.clone(),
-            None => panic!("Expected token, found None"),
+            Some(token) => Ok(token.clone()),
+            None => Err(self.unexpected_end("token")),
         }
     }
 
-    pub fn next_some(&mut self) -> Token {
+    pub fn next_some(&mut self) -> Result<Token, Diagnostic> {
         match self.next() {
-            Some(token) => token.clone(),
-            None => panic!("Expected token, found None"),
+            Some(token) => Ok(token.clone()),
+            None => Err(self.unexpected_end("token")),
+        }
+    }
+
+    pub fn take_line(&mut self) -> Vec<&'a Token> {
+        let mut tokens = vec![];
+
+        for token in self.by_ref() {
+            match token.token_type {
+                TokenType::EndLine => break,
+                TokenType::Comment(_) | TokenType::CommentBlock(_) => {},
+                _ => tokens.push(token),
+            }
+        }
+
+        tokens
+    }
+
+    pub fn expect_line_end(&mut self) -> Result<(), Diagnostic> {
+        let tokens = self.take_line();
+
+        match tokens.first() {
+            Some(token) => Err(unexpected_token(token, "end of line")),
+            None => Ok(()),
         }
     }
 
-    pub fn next_expected(&mut self, expected: TokenType) {
+    pub fn skip_blank_lines(&mut self) {
+        loop {
+            let mut lookahead = self.clone();
+
+            match lookahead.next().map(|token| &token.token_type) {
+                Some(TokenType::EndLine | TokenType::Comment(_) | TokenType::CommentBlock(_)) => {},
+                Some(TokenType::NewLineIndent(_)) => {
+                    match lookahead.peek().map(|token| &token.token_type) {
+                        None | Some(TokenType::EndLine | TokenType::Comment(_) | TokenType::CommentBlock(_)) => {},
+                        _ => return,
+                    }
+                },
+                _ => return,
+            }
+
+            self.next();
+        }
+    }
+
+    pub fn next_expected(&mut self, expected: TokenType) -> Result<Token, Diagnostic> {
         match self.next() {
             Some(token) => {
                 if token.token_type != expected {
-                    panic!("Expected {:?}, found {:?}", expected, token.token_type);
+                    return Err(unexpected_token(token, &expected.to_string()));
                 }
+
+                Ok(token.clone())
             }
-            None => panic!("Expected {:?}, found None", expected),
+            None => Err(self.unexpected_end(&expected.to_string())),
         }
     }
 }
+
+
+impl<'a> Iterator for TokenIterator<'a> {
+    type Item = &'a Token;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.iterator.next();
+
+        if token.is_some() {
+            self.previous = token;
+        }
+
+        token
+    }
+}
+
+
+pub fn tokens_span(tokens: &[&Token]) -> Span {
+    match (tokens.first(), tokens.last()) {
+        (Some(first), Some(last)) => first.span().to(last.span()),
+        _ => Span::default(),
+    }
+}
+
+
+pub fn unexpected_token(token: &Token, expected: &str) -> Diagnostic {
+    Diagnostic::error(format!("expected {}, found {}", expected, token.token_type))
+        .with_primary_label(token.span(), format!("expected {}", expected))
+}
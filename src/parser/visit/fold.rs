@@ -0,0 +1,106 @@
+use crate::parser::structs::{BinaryOperator, Expression, UnaryOperator, Value};
+
+
+pub trait Fold: Sized {
+    fn fold_expression(&mut self, expression: Expression) -> Expression {
+        walk_fold_expression(self, expression)
+    }
+
+    fn fold_value(&mut self, value: Value) -> Value {
+        value
+    }
+
+    fn fold_identifier(&mut self, name: String) -> String {
+        name
+    }
+
+    fn fold_binary_operator(&mut self, operator: BinaryOperator) -> BinaryOperator {
+        operator
+    }
+
+    fn fold_unary_operator(&mut self, operator: UnaryOperator) -> UnaryOperator {
+        operator
+    }
+}
+
+
+pub fn walk_fold_expression<F: Fold>(folder: &mut F, expression: Expression) -> Expression {
+    match expression {
+        Expression::Value(value) => Expression::Value(folder.fold_value(value)),
+        Expression::Identifier(name) => Expression::Identifier(folder.fold_identifier(name)),
+        Expression::Binary { left, operator, right } => Expression::Binary {
+            left: Box::new(folder.fold_expression(*left)),
+            operator: folder.fold_binary_operator(operator),
+            right: Box::new(folder.fold_expression(*right)),
+        },
+        Expression::Unary { operator, right } => Expression::Unary {
+            operator: folder.fold_unary_operator(operator),
+            right: Box::new(folder.fold_expression(*right)),
+        },
+        Expression::Assignment { name, value, span } => Expression::Assignment {
+            name: folder.fold_identifier(name),
+            value: Box::new(folder.fold_expression(*value)),
+            span,
+        },
+        Expression::Reassignment { target, operator, value, span } => Expression::Reassignment {
+            target: Box::new(folder.fold_expression(*target)),
+            operator: operator.map(|operator| folder.fold_binary_operator(operator)),
+            value: Box::new(folder.fold_expression(*value)),
+            span,
+        },
+        Expression::Condition { condition, then_branch, else_branch, span } => Expression::Condition {
+            condition: Box::new(folder.fold_expression(*condition)),
+            then_branch: Box::new(folder.fold_expression(*then_branch)),
+            else_branch: else_branch.map(|else_branch| Box::new(folder.fold_expression(*else_branch))),
+            span,
+        },
+        Expression::Function { name, parameters, body } => Expression::Function {
+            name: folder.fold_identifier(name),
+            parameters: parameters.into_iter().map(|parameter| folder.fold_identifier(parameter)).collect(),
+            body: Box::new(folder.fold_expression(*body)),
+        },
+        Expression::Call { callee, arguments, span } => Expression::Call {
+            callee: Box::new(folder.fold_expression(*callee)),
+            arguments: arguments.into_iter().map(|argument| folder.fold_expression(argument)).collect(),
+            span,
+        },
+        Expression::Member { target, name } => Expression::Member {
+            target: Box::new(folder.fold_expression(*target)),
+            name,
+        },
+        Expression::Index { target, index } => Expression::Index {
+            target: Box::new(folder.fold_expression(*target)),
+            index: Box::new(folder.fold_expression(*index)),
+        },
+        Expression::Array { elements } => Expression::Array {
+            elements: elements.into_iter().map(|element| folder.fold_expression(element)).collect(),
+        },
+        Expression::ForRange { variable, start, end, body, span } => Expression::ForRange {
+            variable: folder.fold_identifier(variable),
+            start: Box::new(folder.fold_expression(*start)),
+            end: Box::new(folder.fold_expression(*end)),
+            body: Box::new(folder.fold_expression(*body)),
+            span,
+        },
+        Expression::ForEach { variable, iterable, body, span } => Expression::ForEach {
+            variable: folder.fold_identifier(variable),
+            iterable: Box::new(folder.fold_expression(*iterable)),
+            body: Box::new(folder.fold_expression(*body)),
+            span,
+        },
+        Expression::Return { value, span } => Expression::Return {
+            value: value.map(|value| Box::new(folder.fold_expression(*value))),
+            span,
+        },
+        Expression::Block { statements } => Expression::Block {
+            statements: statements.into_iter().map(|statement| folder.fold_expression(statement)).collect(),
+        },
+        expression @ (Expression::Break
+        | Expression::Continue
+        | Expression::Print { .. }
+        | Expression::Command { .. }
+        | Expression::New { .. }
+        | Expression::This
+        | Expression::Nope) => expression,
+    }
+}
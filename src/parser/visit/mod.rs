@@ -0,0 +1,145 @@
+pub mod visitor;
+pub mod visitor_mut;
+pub mod fold;
+
+pub use visitor::Visitor;
+pub use visitor_mut::VisitorMut;
+pub use fold::Fold;
+
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostics::Span;
+    use crate::parser::structs::{BinaryOperator, Expression, UnaryOperator, Value};
+
+    use super::*;
+
+    fn sample() -> Expression {
+        Expression::Block {
+            statements: vec![
+                Expression::Assignment {
+                    name: "a".to_string(),
+                    value: Box::new(Expression::Binary {
+                        left: Box::new(Expression::Value(Value::Integer(1))),
+                        operator: BinaryOperator::Add,
+                        right: Box::new(Expression::Value(Value::Integer(2))),
+                    }),
+                    span: Span::default(),
+                },
+                Expression::Condition {
+                    condition: Box::new(Expression::Identifier("a".to_string())),
+                    then_branch: Box::new(Expression::Block {
+                        statements: vec![
+                            Expression::Unary {
+                                operator: UnaryOperator::Increment,
+                                right: Box::new(Expression::Identifier("b".to_string())),
+                            },
+                        ],
+                    }),
+                    else_branch: Some(Box::new(Expression::Block {
+                        statements: vec![Expression::Identifier("c".to_string())],
+                    })),
+                    span: Span::default(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_visitor_collects_identifiers() {
+        struct Identifiers(Vec<String>);
+
+        impl Visitor for Identifiers {
+            fn visit_identifier(&mut self, name: &str) {
+                self.0.push(name.to_string());
+            }
+        }
+
+        let mut visitor = Identifiers(vec![]);
+        visitor.visit_expression(&sample());
+
+        assert_eq!(visitor.0, vec!["a", "a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_visitor_counts_values() {
+        struct Values(usize);
+
+        impl Visitor for Values {
+            fn visit_value(&mut self, _value: &Value) {
+                self.0 += 1;
+            }
+        }
+
+        let mut visitor = Values(0);
+        visitor.visit_expression(&sample());
+
+        assert_eq!(visitor.0, 2);
+    }
+
+    #[test]
+    fn test_visitor_mut_renames() {
+        struct Rename;
+
+        impl VisitorMut for Rename {
+            fn visit_identifier_mut(&mut self, name: &mut String) {
+                if name == "a" {
+                    *name = "renamed".to_string();
+                }
+            }
+        }
+
+        let mut expression = sample();
+        Rename.visit_expression_mut(&mut expression);
+
+        match expression {
+            Expression::Block { statements } => {
+                assert!(matches!(&statements[0], Expression::Assignment { name, .. } if name == "renamed"));
+                assert!(matches!(
+                    &statements[1],
+                    Expression::Condition { condition, .. } if **condition == Expression::Identifier("renamed".to_string())
+                ));
+            },
+            _ => panic!("Expected block"),
+        }
+    }
+
+    #[test]
+    fn test_fold_constant() {
+        struct ConstantFold;
+
+        impl Fold for ConstantFold {
+            fn fold_expression(&mut self, expression: Expression) -> Expression {
+                match fold::walk_fold_expression(self, expression) {
+                    Expression::Binary { left, operator: BinaryOperator::Add, right } => match (*left, *right) {
+                        (Expression::Value(Value::Integer(left)), Expression::Value(Value::Integer(right))) => {
+                            Expression::Value(Value::Integer(left + right))
+                        },
+                        (left, right) => Expression::Binary {
+                            left: Box::new(left),
+                            operator: BinaryOperator::Add,
+                            right: Box::new(right),
+                        },
+                    },
+                    expression => expression,
+                }
+            }
+        }
+
+        let expression = ConstantFold.fold_expression(sample());
+
+        match expression {
+            Expression::Block { statements } => {
+                assert_eq!(
+                    statements[0],
+                    Expression::Assignment {
+                        name: "a".to_string(),
+                        value: Box::new(Expression::Value(Value::Integer(3))),
+                        span: Span::default(),
+                    }
+                );
+            },
+            _ => panic!("Expected block"),
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use crate::parser::structs::{BinaryOperator, Expression, UnaryOperator, Value};
+
+
+pub trait Visitor: Sized {
+    fn visit_expression(&mut self, expression: &Expression) {
+        walk_expression(self, expression);
+    }
+
+    fn visit_value(&mut self, value: &Value) {
+        walk_value(self, value);
+    }
+
+    fn visit_identifier(&mut self, name: &str) {
+        walk_identifier(self, name);
+    }
+
+    fn visit_binary_operator(&mut self, operator: &BinaryOperator) {
+        walk_binary_operator(self, operator);
+    }
+
+    fn visit_unary_operator(&mut self, operator: &UnaryOperator) {
+        walk_unary_operator(self, operator);
+    }
+}
+
+
+pub fn walk_expression<V: Visitor>(visitor: &mut V, expression: &Expression) {
+    match expression {
+        Expression::Value(value) => visitor.visit_value(value),
+        Expression::Identifier(name) => visitor.visit_identifier(name),
+        Expression::Binary { left, operator, right } => {
+            visitor.visit_expression(left);
+            visitor.visit_binary_operator(operator);
+            visitor.visit_expression(right);
+        },
+        Expression::Unary { operator, right } => {
+            visitor.visit_unary_operator(operator);
+            visitor.visit_expression(right);
+        },
+        Expression::Assignment { name, value, .. } => {
+            visitor.visit_identifier(name);
+            visitor.visit_expression(value);
+        },
+        Expression::Reassignment { target, operator, value, .. } => {
+            visitor.visit_expression(target);
+
+            if let Some(operator) = operator {
+                visitor.visit_binary_operator(operator);
+            }
+
+            visitor.visit_expression(value);
+        },
+        Expression::Condition { condition, then_branch, else_branch, .. } => {
+            visitor.visit_expression(condition);
+            visitor.visit_expression(then_branch);
+
+            if let Some(else_branch) = else_branch {
+                visitor.visit_expression(else_branch);
+            }
+        },
+        Expression::Function { name, parameters, body } => {
+            visitor.visit_identifier(name);
+
+            for parameter in parameters {
+                visitor.visit_identifier(parameter);
+            }
+
+            visitor.visit_expression(body);
+        },
+        Expression::Call { callee, arguments, .. } => {
+            visitor.visit_expression(callee);
+
+            for argument in arguments {
+                visitor.visit_expression(argument);
+            }
+        },
+        Expression::Member { target, .. } => visitor.visit_expression(target),
+        Expression::Index { target, index } => {
+            visitor.visit_expression(target);
+            visitor.visit_expression(index);
+        },
+        Expression::Array { elements } => {
+            for element in elements {
+                visitor.visit_expression(element);
+            }
+        },
+        Expression::ForRange { variable, start, end, body, .. } => {
+            visitor.visit_identifier(variable);
+            visitor.visit_expression(start);
+            visitor.visit_expression(end);
+            visitor.visit_expression(body);
+        },
+        Expression::ForEach { variable, iterable, body, .. } => {
+            visitor.visit_identifier(variable);
+            visitor.visit_expression(iterable);
+            visitor.visit_expression(body);
+        },
+        Expression::Return { value, .. } => {
+            if let Some(value) = value {
+                visitor.visit_expression(value);
+            }
+        },
+        Expression::Block { statements } => {
+            for statement in statements {
+                visitor.visit_expression(statement);
+            }
+        },
+        Expression::Break
+        | Expression::Continue
+        | Expression::Print { .. }
+        | Expression::Command { .. }
+        | Expression::New { .. }
+        | Expression::This
+        | Expression::Nope => {},
+    }
+}
+
+
+pub fn walk_value<V: Visitor>(_visitor: &mut V, _value: &Value) {}
+
+
+pub fn walk_identifier<V: Visitor>(_visitor: &mut V, _name: &str) {}
+
+
+pub fn walk_binary_operator<V: Visitor>(_visitor: &mut V, _operator: &BinaryOperator) {}
+
+
+pub fn walk_unary_operator<V: Visitor>(_visitor: &mut V, _operator: &UnaryOperator) {}
@@ -0,0 +1,128 @@
+use crate::parser::structs::{BinaryOperator, Expression, UnaryOperator, Value};
+
+
+pub trait VisitorMut: Sized {
+    fn visit_expression_mut(&mut self, expression: &mut Expression) {
+        walk_expression_mut(self, expression);
+    }
+
+    fn visit_value_mut(&mut self, value: &mut Value) {
+        walk_value_mut(self, value);
+    }
+
+    fn visit_identifier_mut(&mut self, name: &mut String) {
+        walk_identifier_mut(self, name);
+    }
+
+    fn visit_binary_operator_mut(&mut self, operator: &mut BinaryOperator) {
+        walk_binary_operator_mut(self, operator);
+    }
+
+    fn visit_unary_operator_mut(&mut self, operator: &mut UnaryOperator) {
+        walk_unary_operator_mut(self, operator);
+    }
+}
+
+
+pub fn walk_expression_mut<V: VisitorMut>(visitor: &mut V, expression: &mut Expression) {
+    match expression {
+        Expression::Value(value) => visitor.visit_value_mut(value),
+        Expression::Identifier(name) => visitor.visit_identifier_mut(name),
+        Expression::Binary { left, operator, right } => {
+            visitor.visit_expression_mut(left);
+            visitor.visit_binary_operator_mut(operator);
+            visitor.visit_expression_mut(right);
+        },
+        Expression::Unary { operator, right } => {
+            visitor.visit_unary_operator_mut(operator);
+            visitor.visit_expression_mut(right);
+        },
+        Expression::Assignment { name, value, .. } => {
+            visitor.visit_identifier_mut(name);
+            visitor.visit_expression_mut(value);
+        },
+        Expression::Reassignment { target, operator, value, .. } => {
+            visitor.visit_expression_mut(target);
+
+            if let Some(operator) = operator {
+                visitor.visit_binary_operator_mut(operator);
+            }
+
+            visitor.visit_expression_mut(value);
+        },
+        Expression::Condition { condition, then_branch, else_branch, .. } => {
+            visitor.visit_expression_mut(condition);
+            visitor.visit_expression_mut(then_branch);
+
+            if let Some(else_branch) = else_branch {
+                visitor.visit_expression_mut(else_branch);
+            }
+        },
+        Expression::Function { name, parameters, body } => {
+            visitor.visit_identifier_mut(name);
+
+            for parameter in parameters {
+                visitor.visit_identifier_mut(parameter);
+            }
+
+            visitor.visit_expression_mut(body);
+        },
+        Expression::Call { callee, arguments, .. } => {
+            visitor.visit_expression_mut(callee);
+
+            for argument in arguments {
+                visitor.visit_expression_mut(argument);
+            }
+        },
+        Expression::Member { target, .. } => visitor.visit_expression_mut(target),
+        Expression::Index { target, index } => {
+            visitor.visit_expression_mut(target);
+            visitor.visit_expression_mut(index);
+        },
+        Expression::Array { elements } => {
+            for element in elements {
+                visitor.visit_expression_mut(element);
+            }
+        },
+        Expression::ForRange { variable, start, end, body, .. } => {
+            visitor.visit_identifier_mut(variable);
+            visitor.visit_expression_mut(start);
+            visitor.visit_expression_mut(end);
+            visitor.visit_expression_mut(body);
+        },
+        Expression::ForEach { variable, iterable, body, .. } => {
+            visitor.visit_identifier_mut(variable);
+            visitor.visit_expression_mut(iterable);
+            visitor.visit_expression_mut(body);
+        },
+        Expression::Return { value, .. } => {
+            if let Some(value) = value {
+                visitor.visit_expression_mut(value);
+            }
+        },
+        Expression::Block { statements } => {
+            for statement in statements {
+                visitor.visit_expression_mut(statement);
+            }
+        },
+        Expression::Break
+        | Expression::Continue
+        | Expression::Print { .. }
+        | Expression::Command { .. }
+        | Expression::New { .. }
+        | Expression::This
+        | Expression::Nope => {},
+    }
+}
+
+
+pub fn walk_value_mut<V: VisitorMut>(_visitor: &mut V, _value: &mut Value) {}
+
+
+pub fn walk_identifier_mut<V: VisitorMut>(_visitor: &mut V, _name: &mut String) {}
+
+
+pub fn walk_binary_operator_mut<V: VisitorMut>(_visitor: &mut V, _operator: &mut BinaryOperator) {}
+
+
+pub fn walk_unary_operator_mut<V: VisitorMut>(_visitor: &mut V, _operator: &mut UnaryOperator) {}